@@ -0,0 +1,340 @@
+use super::sigv4::AwsCredentials;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Credentials written in one of the `Authorization` shorthands REST Client accepts:
+/// `Basic user:pass`, `Basic user pass`, `Digest user pass` and
+/// `AWS accessKey secretKey [token:t] [region:r] [service:s]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Authorization {
+    Basic { username: String, password: String },
+    Digest { username: String, password: String },
+    Aws(AwsCredentials),
+}
+
+impl Authorization {
+    /// Recognizes a shorthand header value. Already encoded `Basic` credentials and
+    /// any other scheme are left alone.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (scheme, credentials) = value.trim().split_once(char::is_whitespace)?;
+        let credentials = credentials.trim();
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            // `user:pass` wins when the first word has a colon, so passwords may hold spaces.
+            let first_word = credentials.split_whitespace().next().unwrap_or_default();
+            let (username, password) = if first_word.contains(':') {
+                credentials.split_once(':')?
+            } else {
+                credentials.split_once(char::is_whitespace)?
+            };
+            return Some(Authorization::Basic {
+                username: username.to_string(),
+                password: password.trim_start().to_string(),
+            });
+        }
+
+        if scheme.eq_ignore_ascii_case("digest") {
+            let (username, password) = credentials.split_once(char::is_whitespace)?;
+            // A full `Digest username="..."` header was written by hand; send it as is.
+            if username.contains('=') {
+                return None;
+            }
+            return Some(Authorization::Digest {
+                username: username.to_string(),
+                password: password.trim_start().to_string(),
+            });
+        }
+
+        if scheme.eq_ignore_ascii_case("aws") {
+            return AwsCredentials::parse(credentials).map(Authorization::Aws);
+        }
+
+        None
+    }
+
+    /// The header value to send up front, or `None` for Digest, which needs a challenge,
+    /// and AWS, which signs the final request.
+    pub fn encode(&self) -> Option<String> {
+        match self {
+            Authorization::Basic { username, password } => Some(format!(
+                "Basic {}",
+                BASE64.encode(format!("{}:{}", username, password))
+            )),
+            Authorization::Digest { .. } | Authorization::Aws(_) => None,
+        }
+    }
+}
+
+/// A `WWW-Authenticate: Digest ...` challenge (RFC 7616).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: String,
+    qop: Option<String>,
+}
+
+static NONCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+impl DigestChallenge {
+    pub(crate) fn parse(header: &str) -> Option<Self> {
+        let (scheme, params) = header.trim().split_once(char::is_whitespace)?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+
+        let mut realm = None;
+        let mut nonce = None;
+        let mut opaque = None;
+        let mut algorithm = "MD5".to_string();
+        let mut qop = None;
+
+        for (name, value) in auth_params(params) {
+            match name.to_ascii_lowercase().as_str() {
+                "realm" => realm = Some(value),
+                "nonce" => nonce = Some(value),
+                "opaque" => opaque = Some(value),
+                "algorithm" => algorithm = value,
+                "qop" => {
+                    // Prefer plain `auth`; `auth-int` would need the exact body bytes.
+                    let options: Vec<&str> = value.split(',').map(str::trim).collect();
+                    if options.contains(&"auth") {
+                        qop = Some("auth".to_string());
+                    } else if options.contains(&"auth-int") {
+                        qop = Some("auth-int".to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            realm: realm?,
+            nonce: nonce?,
+            opaque,
+            algorithm,
+            qop,
+        })
+    }
+
+    /// Builds the `Authorization` header answering this challenge.
+    pub(crate) fn respond(
+        &self,
+        username: &str,
+        password: &str,
+        method: &str,
+        uri: &str,
+        body: &[u8],
+    ) -> Option<String> {
+        let cnonce = client_nonce();
+        self.respond_with_cnonce(username, password, method, uri, body, &cnonce, 1)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn respond_with_cnonce(
+        &self,
+        username: &str,
+        password: &str,
+        method: &str,
+        uri: &str,
+        body: &[u8],
+        cnonce: &str,
+        nonce_count: u32,
+    ) -> Option<String> {
+        let algorithm = self.algorithm.to_ascii_uppercase();
+        let hash: fn(&[u8]) -> String = match algorithm.trim_end_matches("-SESS") {
+            "MD5" => |data| hex(&Md5::digest(data)),
+            "SHA-256" => |data| hex(&Sha256::digest(data)),
+            _ => return None,
+        };
+
+        let mut ha1 = hash(format!("{}:{}:{}", username, self.realm, password).as_bytes());
+        if algorithm.ends_with("-SESS") {
+            ha1 = hash(format!("{}:{}:{}", ha1, self.nonce, cnonce).as_bytes());
+        }
+
+        let ha2 = match self.qop.as_deref() {
+            Some("auth-int") => hash(format!("{}:{}:{}", method, uri, hash(body)).as_bytes()),
+            _ => hash(format!("{}:{}", method, uri).as_bytes()),
+        };
+
+        let nc = format!("{:08x}", nonce_count);
+        let response = match self.qop {
+            Some(ref qop) => {
+                hash(format!("{}:{}:{}:{}:{}:{}", ha1, self.nonce, nc, cnonce, qop, ha2).as_bytes())
+            }
+            None => hash(format!("{}:{}:{}", ha1, self.nonce, ha2).as_bytes()),
+        };
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+            username, self.realm, self.nonce, uri, self.algorithm, response
+        );
+        if let Some(ref qop) = self.qop {
+            header.push_str(&format!(", qop={}, nc={}, cnonce=\"{}\"", qop, nc, cnonce));
+        }
+        if let Some(ref opaque) = self.opaque {
+            header.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+        Some(header)
+    }
+}
+
+/// Splits `name=value, name="quoted, value"` pairs.
+fn auth_params(params: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut rest = params.trim();
+
+    while let Some((name, after)) = rest.split_once('=') {
+        let name = name.trim().trim_start_matches(',').trim().to_string();
+        let after = after.trim_start();
+
+        let (value, remaining) = if let Some(quoted) = after.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            (
+                quoted[..end].to_string(),
+                quoted.get(end + 1..).unwrap_or(""),
+            )
+        } else {
+            let end = after.find(',').unwrap_or(after.len());
+            (after[..end].trim().to_string(), &after[end..])
+        };
+
+        pairs.push((name, value));
+        rest = remaining.trim_start().trim_start_matches(',');
+    }
+
+    pairs
+}
+
+fn client_nonce() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let count = NONCE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    hex(&Md5::digest(format!(
+        "{}:{}:{}",
+        nanos,
+        std::process::id(),
+        count
+    )))[..16]
+        .to_string()
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basic_shorthands() {
+        let expected = Authorization::Basic {
+            username: "user".to_string(),
+            password: "p:ss".to_string(),
+        };
+        assert_eq!(
+            Authorization::parse("Basic user:p:ss"),
+            Some(expected.clone())
+        );
+        assert_eq!(Authorization::parse("Basic user p:ss"), Some(expected));
+        assert_eq!(Authorization::parse("Basic dXNlcjpwYXNz"), None);
+        assert_eq!(Authorization::parse("Bearer token"), None);
+    }
+
+    #[test]
+    fn test_parse_aws() {
+        let Some(Authorization::Aws(credentials)) =
+            Authorization::parse("AWS AKID secret region:eu-west-1")
+        else {
+            panic!("expected AWS credentials");
+        };
+        assert_eq!(credentials.access_key, "AKID");
+        assert_eq!(credentials.region.as_deref(), Some("eu-west-1"));
+        assert_eq!(Authorization::Aws(credentials).encode(), None);
+    }
+
+    #[test]
+    fn test_encode_basic() {
+        let auth = Authorization::parse("Basic Aladdin:open sesame").unwrap();
+        assert_eq!(
+            auth.encode().as_deref(),
+            Some("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==")
+        );
+    }
+
+    #[test]
+    fn test_parse_digest() {
+        assert_eq!(
+            Authorization::parse("Digest Mufasa Circle Of Life"),
+            Some(Authorization::Digest {
+                username: "Mufasa".to_string(),
+                password: "Circle Of Life".to_string(),
+            })
+        );
+        assert_eq!(
+            Authorization::parse("Digest username=\"Mufasa\", realm=\"x\""),
+            None
+        );
+    }
+
+    #[test]
+    fn test_digest_response_rfc_2617_example() {
+        let challenge = DigestChallenge::parse(
+            "Digest realm=\"testrealm@host.com\", qop=\"auth,auth-int\", \
+             nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", \
+             opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"",
+        )
+        .unwrap();
+
+        let header = challenge
+            .respond_with_cnonce(
+                "Mufasa",
+                "Circle Of Life",
+                "GET",
+                "/dir/index.html",
+                b"",
+                "0a4f113b",
+                1,
+            )
+            .unwrap();
+
+        assert!(header.contains("response=\"6629fae49393a05397450978507c4ef1\""));
+        assert!(header.contains("qop=auth, nc=00000001, cnonce=\"0a4f113b\""));
+        assert!(header.contains("opaque=\"5ccc069c403ebaf9f0171e9517f40e41\""));
+    }
+
+    #[test]
+    fn test_digest_sha256_rfc_7616_example() {
+        let challenge = DigestChallenge::parse(
+            "Digest realm=\"http-auth@example.org\", qop=\"auth, auth-int\", \
+             algorithm=SHA-256, nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", \
+             opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"",
+        )
+        .unwrap();
+
+        let header = challenge
+            .respond_with_cnonce(
+                "Mufasa",
+                "Circle of Life",
+                "GET",
+                "/dir/index.html",
+                b"",
+                "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+                1,
+            )
+            .unwrap();
+
+        assert!(header.contains(
+            "response=\"753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1\""
+        ));
+    }
+}
@@ -0,0 +1,226 @@
+use encoding_rs::{DecoderResult, Encoding, UTF_8};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+const BINARY_TYPES: &[&str] = &[
+    "image/",
+    "audio/",
+    "video/",
+    "font/",
+    "application/octet-stream",
+    "application/zip",
+    "application/gzip",
+    "application/x-gzip",
+    "application/pdf",
+    "application/protobuf",
+    "application/x-protobuf",
+    "application/grpc",
+    "application/wasm",
+];
+
+/// Decodes a response body to text using the charset from `content_type`.
+///
+/// Returns `None` when the content type is a known binary type or the bytes are
+/// not valid in the declared (or sniffed) encoding.
+pub fn decode_text(bytes: &[u8], content_type: Option<&str>) -> Option<String> {
+    decode(bytes, content_type, true)
+}
+
+/// Like [`decode_text`], but tolerates a character cut in half at the end of `bytes`.
+pub(crate) fn decode_text_prefix(bytes: &[u8], content_type: Option<&str>) -> Option<String> {
+    decode(bytes, content_type, false)
+}
+
+fn decode(bytes: &[u8], content_type: Option<&str>, complete: bool) -> Option<String> {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    if BINARY_TYPES.iter().any(|binary| mime.starts_with(binary)) {
+        return None;
+    }
+
+    let (encoding, bom_length) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_length)) => (encoding, bom_length),
+        None => (charset(content_type).unwrap_or(UTF_8), 0),
+    };
+
+    let bytes = &bytes[bom_length..];
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text =
+        String::with_capacity(decoder.max_utf8_buffer_length_without_replacement(bytes.len())?);
+    let (result, _) = decoder.decode_to_string_without_replacement(bytes, &mut text, complete);
+    if result != DecoderResult::InputEmpty {
+        return None;
+    }
+
+    if text.contains('\0') {
+        return None;
+    }
+
+    Some(text)
+}
+
+fn charset(content_type: Option<&str>) -> Option<&'static Encoding> {
+    content_type?
+        .split(';')
+        .skip(1)
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .and_then(|(_, value)| Encoding::for_label(value.trim().trim_matches('"').as_bytes()))
+}
+
+/// Accumulates a streamed body, keeping at most `limit` bytes in memory.
+///
+/// Once the limit is exceeded the whole body is written to a temporary file and
+/// only the first `limit` bytes stay in memory for display. The file is removed
+/// again if the buffer is dropped before `finish`, e.g. when the download fails.
+pub(crate) struct BodyBuffer {
+    limit: usize,
+    bytes: Vec<u8>,
+    spill: Option<(File, PathBuf)>,
+    size: u64,
+}
+
+static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+impl BodyBuffer {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            bytes: Vec::new(),
+            spill: None,
+            size: 0,
+        }
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub(crate) async fn push(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.size += chunk.len() as u64;
+
+        if self.spill.is_none() && self.bytes.len() + chunk.len() > self.limit {
+            let path = std::env::temp_dir().join(format!(
+                "poke-{}-{}.body",
+                std::process::id(),
+                SPILL_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            let mut file = File::create(&path).await?;
+            file.write_all(&self.bytes).await?;
+            self.spill = Some((file, path));
+        }
+
+        if let Some((ref mut file, _)) = self.spill {
+            file.write_all(chunk).await?;
+        }
+
+        let room = self.limit.saturating_sub(self.bytes.len());
+        self.bytes
+            .extend_from_slice(&chunk[..chunk.len().min(room)]);
+        Ok(())
+    }
+
+    /// Returns the in-memory bytes and, if the body was too large, the file holding all of it.
+    pub(crate) async fn finish(mut self) -> io::Result<(Vec<u8>, Option<PathBuf>)> {
+        if let Some((ref mut file, _)) = self.spill {
+            file.flush().await?;
+        }
+        let path = self.spill.take().map(|(_, path)| path);
+        Ok((std::mem::take(&mut self.bytes), path))
+    }
+}
+
+impl Drop for BodyBuffer {
+    fn drop(&mut self) {
+        if let Some((file, path)) = self.spill.take() {
+            drop(file);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_utf8_by_default() {
+        let text = decode_text("zażółć".as_bytes(), Some("application/json"));
+        assert_eq!(text.as_deref(), Some("zażółć"));
+    }
+
+    #[test]
+    fn test_decode_with_charset() {
+        let latin1 = [0x63, 0x61, 0x66, 0xe9];
+        let text = decode_text(&latin1, Some("text/plain; charset=ISO-8859-1"));
+        assert_eq!(text.as_deref(), Some("café"));
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_binary() {
+        assert_eq!(decode_text(&[0x80, 0x81, 0xff], None), None);
+    }
+
+    #[test]
+    fn test_binary_content_type() {
+        assert_eq!(decode_text(b"GIF89a", Some("image/gif")), None);
+        assert_eq!(decode_text(b"\x1f\x8b", Some("application/gzip")), None);
+    }
+
+    #[test]
+    fn test_decode_prefix_with_cut_character() {
+        let bytes = "żółw".as_bytes();
+        assert_eq!(decode_text(&bytes[..3], None), None);
+        assert_eq!(decode_text_prefix(&bytes[..3], None).as_deref(), Some("ż"));
+    }
+
+    #[tokio::test]
+    async fn test_body_buffer_spills_past_limit() {
+        let mut buffer = BodyBuffer::new(4);
+        buffer.push(b"abc").await.unwrap();
+        buffer.push(b"defg").await.unwrap();
+        assert_eq!(buffer.size(), 7);
+
+        let (bytes, path) = buffer.finish().await.unwrap();
+        let path = path.unwrap();
+        let spilled = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(bytes, b"abcd");
+        assert_eq!(spilled, b"abcdefg");
+    }
+
+    #[tokio::test]
+    async fn test_body_buffer_removes_file_of_failed_download() {
+        let mut buffer = BodyBuffer::new(4);
+        buffer.push(b"abcdefg").await.unwrap();
+        let path = buffer.spill.as_ref().unwrap().1.clone();
+        assert!(path.exists());
+
+        // A failed download drops the buffer without finishing it.
+        drop(buffer);
+
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_body_buffer_within_limit_stays_in_memory() {
+        let mut buffer = BodyBuffer::new(8);
+        buffer.push(b"abc").await.unwrap();
+
+        let (bytes, path) = buffer.finish().await.unwrap();
+        assert_eq!(bytes, b"abc");
+        assert_eq!(path, None);
+    }
+
+    #[test]
+    fn test_nul_bytes_are_binary() {
+        assert_eq!(decode_text(b"abc\0def", Some("text/plain")), None);
+    }
+}
@@ -0,0 +1,698 @@
+use super::{Client, Response, Timings};
+use crate::error::{GrpcError, HttpError, Result};
+use crate::http::Request;
+use crate::variable;
+use futures_util::stream;
+use prost_reflect::prost::Message;
+use prost_reflect::prost_types::FileDescriptorProto;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Instant;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Status};
+use tonic_reflection::pb::v1::ServerReflectionRequest;
+use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+
+/// The parts of a `GRPC host:port/package.Service/Method` request line.
+#[derive(Debug, PartialEq, Eq)]
+struct GrpcTarget {
+    endpoint: String,
+    tls: bool,
+    service: String,
+    method: String,
+}
+
+impl GrpcTarget {
+    fn parse(url: &str) -> Result<Self> {
+        let invalid = || GrpcError::InvalidTarget(url.to_string());
+
+        let (tls, rest) = match url.split_once("://") {
+            Some(("http" | "grpc", rest)) => (false, rest),
+            Some(("https" | "grpcs", rest)) => (true, rest),
+            Some(_) => return Err(invalid().into()),
+            None => (false, url),
+        };
+
+        let (authority, path) = rest.split_once('/').ok_or_else(invalid)?;
+        let (service, method) = path
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .ok_or_else(invalid)?;
+        if authority.is_empty() || service.is_empty() || method.is_empty() {
+            return Err(invalid().into());
+        }
+
+        let scheme = if tls { "https" } else { "http" };
+        Ok(Self {
+            endpoint: format!("{}://{}", scheme, authority),
+            tls,
+            service: service.to_string(),
+            method: method.to_string(),
+        })
+    }
+}
+
+impl Client {
+    /// Calls the gRPC method named by `request`, encoding its JSON body with descriptors
+    /// from the request's `.proto` files or, without any, from server reflection.
+    ///
+    /// A non-OK gRPC status is returned as a response with a matching HTTP status, so the
+    /// status message and trailers can be inspected like any other response. The call
+    /// is abandoned when `stop` completes.
+    pub(crate) async fn execute_grpc(
+        &self,
+        request: &Request,
+        variables: &HashMap<String, String>,
+        stop: &mut (impl Future + Unpin),
+    ) -> Result<Response> {
+        tokio::select! {
+            response = self.call_grpc(request, variables) => response,
+            _ = stop => Err(HttpError::Cancelled.into()),
+        }
+    }
+
+    async fn call_grpc(
+        &self,
+        request: &Request,
+        variables: &HashMap<String, String>,
+    ) -> Result<Response> {
+        let start = Instant::now();
+        let target = GrpcTarget::parse(&variable::substitute(&request.url, variables)?)?;
+
+        let mut endpoint = Endpoint::from_shared(target.endpoint.clone())
+            .map_err(|_| GrpcError::InvalidTarget(request.url.clone()))?
+            .connect_timeout(self.timeout)
+            .timeout(self.timeout);
+        if target.tls {
+            let tls = self
+                .transport_for(&target.endpoint)
+                .grpc_tls
+                .clone()
+                .ok_or_else(|| {
+                    GrpcError::Transport(
+                        "PKCS #12 client certificates are not supported for gRPC, use clientCertificate and clientCertificateKey".to_string(),
+                    )
+                })?;
+            endpoint = endpoint
+                .tls_config(tls)
+                .map_err(|err| GrpcError::Transport(err.to_string()))?;
+        }
+
+        let connect_start = Instant::now();
+        let channel = endpoint
+            .connect()
+            .await
+            .map_err(|err| GrpcError::Transport(transport_error_message(&err)))?;
+        let connect = connect_start.elapsed();
+
+        let pool = if request.proto_files.is_empty() {
+            reflect(channel.clone(), &target.service).await?
+        } else {
+            compile_protos(&request.proto_files)?
+        };
+        let method = find_method(&pool, &target)?;
+
+        let body = match request.body {
+            Some(ref body) => variable::substitute(body, variables)?,
+            None => "{}".to_string(),
+        };
+        let mut deserializer = serde_json::Deserializer::from_str(&body);
+        let message = DynamicMessage::deserialize(method.input(), &mut deserializer)
+            .and_then(|message| deserializer.end().map(|()| message))
+            .map_err(|err| GrpcError::InvalidMessage(err.to_string()))?;
+
+        let mut call = tonic::Request::new(stream::iter([message]));
+        for (key, value) in &request.headers {
+            let value = variable::substitute(value, variables)?;
+            if let (Ok(key), Ok(value)) = (
+                key.to_ascii_lowercase()
+                    .parse::<tonic::metadata::MetadataKey<_>>(),
+                value.parse(),
+            ) {
+                call.metadata_mut().insert(key, value);
+            }
+        }
+
+        let path = format!("/{}/{}", method.parent_service().full_name(), method.name())
+            .parse()
+            .map_err(|_| GrpcError::InvalidTarget(request.url.clone()))?;
+
+        let send_start = Instant::now();
+        let mut grpc = tonic::client::Grpc::new(channel);
+        grpc.ready()
+            .await
+            .map_err(|err| GrpcError::Transport(err.to_string()))?;
+        let result = grpc
+            .streaming(call, path, DynamicCodec::new(method.output()))
+            .await;
+        let ttfb = send_start.elapsed();
+
+        let download_start = Instant::now();
+        let mut headers = Vec::new();
+        let mut messages = Vec::new();
+        let status = match result {
+            Ok(response) => {
+                headers.extend(metadata_pairs(response.metadata()));
+                let mut stream = response.into_inner();
+                loop {
+                    match stream.message().await {
+                        Ok(Some(message)) => messages.push(message),
+                        Ok(None) => {
+                            if let Ok(Some(trailers)) = stream.trailers().await {
+                                headers.extend(metadata_pairs(&trailers));
+                            }
+                            break Status::ok("");
+                        }
+                        Err(status) => {
+                            headers.extend(metadata_pairs(status.metadata()));
+                            break status;
+                        }
+                    }
+                }
+            }
+            Err(status) => {
+                headers.extend(metadata_pairs(status.metadata()));
+                status
+            }
+        };
+        let download = download_start.elapsed();
+
+        // tonic strips the status from metadata it turns into a `Status`.
+        if !headers.iter().any(|(key, _)| key == "grpc-status") {
+            headers.push((
+                "grpc-status".to_string(),
+                (status.code() as i32).to_string(),
+            ));
+            if !status.message().is_empty() {
+                headers.push(("grpc-message".to_string(), status.message().to_string()));
+            }
+        }
+
+        let text = format_messages(&messages, &status);
+        let (http_status, status_text) = http_status(&status);
+        Ok(Response {
+            status: http_status,
+            status_text,
+            headers,
+            body_size: text.len() as u64,
+            body: text.clone().into_bytes(),
+            text: Some(text),
+            body_file: None,
+            events: Vec::new(),
+            messages: Vec::new(),
+            duration: start.elapsed(),
+            timings: Timings {
+                connect: Some(connect),
+                ttfb,
+                download,
+                tls: target.tls,
+                ..Timings::default()
+            },
+            request: None,
+        })
+    }
+}
+
+/// Compiles `.proto` files, resolving imports relative to each file's directory.
+fn compile_protos(files: &[PathBuf]) -> Result<DescriptorPool> {
+    let includes: Vec<PathBuf> = files
+        .iter()
+        .filter_map(|file| file.parent().map(PathBuf::from))
+        .collect();
+
+    let mut compiler =
+        protox::Compiler::new(includes).map_err(|err| GrpcError::Proto(err.to_string()))?;
+    compiler.include_imports(true);
+    compiler
+        .open_files(files)
+        .map_err(|err| GrpcError::Proto(err.to_string()))?;
+    Ok(compiler.descriptor_pool())
+}
+
+/// Fetches the file defining `service`, and everything it imports, through the
+/// `grpc.reflection.v1` service.
+async fn reflect(channel: Channel, service: &str) -> Result<DescriptorPool> {
+    let mut client = ServerReflectionClient::new(channel);
+    let mut files: Vec<FileDescriptorProto> = Vec::new();
+    let mut requested = HashSet::new();
+    let mut pending = vec![MessageRequest::FileContainingSymbol(service.to_string())];
+
+    while let Some(message_request) = pending.pop() {
+        let request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(message_request),
+        };
+        let mut responses = client
+            .server_reflection_info(stream::iter([request]))
+            .await
+            .map_err(|status| GrpcError::Reflection(status.message().to_string()))?
+            .into_inner();
+        let response = responses
+            .message()
+            .await
+            .map_err(|status| GrpcError::Reflection(status.message().to_string()))?
+            .ok_or_else(|| GrpcError::Reflection("empty reflection response".to_string()))?;
+
+        let descriptors = match response.message_response {
+            Some(MessageResponse::FileDescriptorResponse(response)) => {
+                response.file_descriptor_proto
+            }
+            Some(MessageResponse::ErrorResponse(error)) => {
+                return Err(GrpcError::Reflection(error.error_message).into());
+            }
+            _ => return Err(GrpcError::Reflection("unexpected reflection response".into()).into()),
+        };
+
+        for bytes in descriptors {
+            let file = FileDescriptorProto::decode(bytes.as_slice())
+                .map_err(|err| GrpcError::Reflection(err.to_string()))?;
+            if !requested.insert(file.name().to_string()) {
+                continue;
+            }
+            for dependency in &file.dependency {
+                if !requested.contains(dependency) {
+                    pending.push(MessageRequest::FileByFilename(dependency.clone()));
+                }
+            }
+            files.push(file);
+        }
+    }
+
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(files)
+        .map_err(|err| GrpcError::Reflection(err.to_string()))?;
+    Ok(pool)
+}
+
+fn find_method(pool: &DescriptorPool, target: &GrpcTarget) -> Result<MethodDescriptor> {
+    pool.get_service_by_name(&target.service)
+        .and_then(|service| {
+            service
+                .methods()
+                .find(|method| method.name() == target.method)
+        })
+        .ok_or_else(|| {
+            GrpcError::MethodNotFound(format!("{}/{}", target.service, target.method)).into()
+        })
+}
+
+fn metadata_pairs(metadata: &tonic::metadata::MetadataMap) -> Vec<(String, String)> {
+    metadata
+        .clone()
+        .into_headers()
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+        .collect()
+}
+
+/// Renders the response messages as JSON: a single object for unary calls, an array
+/// for streams, and the status message when the call failed without any.
+fn format_messages(messages: &[DynamicMessage], status: &Status) -> String {
+    let values: Vec<serde_json::Value> = messages
+        .iter()
+        .filter_map(|message| serde_json::to_value(message).ok())
+        .collect();
+
+    let value = match values.len() {
+        0 if status.code() != Code::Ok => {
+            return format!("{:?}: {}", status.code(), status.message());
+        }
+        1 => values.into_iter().next().unwrap_or_default(),
+        _ => serde_json::Value::Array(values),
+    };
+    serde_json::to_string_pretty(&value).unwrap_or_default()
+}
+
+/// Maps a gRPC status to the HTTP status shown in the status panel.
+fn http_status(status: &Status) -> (u16, String) {
+    let code = match status.code() {
+        Code::Ok => return (200, "OK".to_string()),
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => 400,
+        Code::Unauthenticated => 401,
+        Code::PermissionDenied => 403,
+        Code::NotFound => 404,
+        Code::AlreadyExists | Code::Aborted => 409,
+        Code::ResourceExhausted => 429,
+        Code::Cancelled => 499,
+        Code::Unimplemented => 501,
+        Code::Unavailable => 503,
+        Code::DeadlineExceeded => 504,
+        Code::Unknown | Code::Internal | Code::DataLoss => 500,
+    };
+    (
+        code,
+        format!("{:?} (grpc-status {})", status.code(), status.code() as i32),
+    )
+}
+
+fn transport_error_message(err: &tonic::transport::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    message
+}
+
+/// Encodes and decodes messages described at runtime instead of by generated types.
+struct DynamicCodec {
+    decode_as: MessageDescriptor,
+}
+
+impl DynamicCodec {
+    fn new(decode_as: MessageDescriptor) -> Self {
+        Self { decode_as }
+    }
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder(self.decode_as.clone())
+    }
+}
+
+struct DynamicEncoder;
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(
+        &mut self,
+        item: Self::Item,
+        dst: &mut EncodeBuf<'_>,
+    ) -> std::result::Result<(), Self::Error> {
+        item.encode(dst)
+            .map_err(|err| Status::internal(err.to_string()))
+    }
+}
+
+struct DynamicDecoder(MessageDescriptor);
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(
+        &mut self,
+        src: &mut DecodeBuf<'_>,
+    ) -> std::result::Result<Option<Self::Item>, Self::Error> {
+        DynamicMessage::decode(self.0.clone(), src)
+            .map(Some)
+            .map_err(|err| Status::internal(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::{LOCALHOST_CERT, LOCALHOST_KEY};
+    use crate::environment::SslConfiguration;
+    use crate::error::Error;
+    use crate::http::Method;
+    use prost_reflect::Value;
+    use std::convert::Infallible;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+    use tonic::body::Body;
+    use tonic::codegen::http;
+    use tonic::server::{NamedService, UnaryService};
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::{Identity, ServerTlsConfig};
+    use tower_service::Service;
+
+    const ECHO_PROTO: &str = r#"
+syntax = "proto3";
+package echo;
+
+service Echo {
+  rpc Say(EchoRequest) returns (EchoReply);
+}
+
+message EchoRequest {
+  string text = 1;
+  int32 times = 2;
+}
+
+message EchoReply {
+  string text = 1;
+}
+"#;
+
+    type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+    /// Repeats `text` `times` times, rejecting negative counts.
+    #[derive(Clone)]
+    struct EchoServer(DescriptorPool);
+
+    impl NamedService for EchoServer {
+        const NAME: &'static str = "echo.Echo";
+    }
+
+    impl Service<http::Request<Body>> for EchoServer {
+        type Response = http::Response<Body>;
+        type Error = Infallible;
+        type Future = BoxFuture<std::result::Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+            let pool = self.0.clone();
+            Box::pin(async move {
+                let input = pool.get_message_by_name("echo.EchoRequest").unwrap();
+                let mut grpc = tonic::server::Grpc::new(DynamicCodec::new(input));
+                Ok(grpc.unary(Say(pool), request).await)
+            })
+        }
+    }
+
+    struct Say(DescriptorPool);
+
+    impl UnaryService<DynamicMessage> for Say {
+        type Response = DynamicMessage;
+        type Future = BoxFuture<std::result::Result<tonic::Response<DynamicMessage>, Status>>;
+
+        fn call(&mut self, request: tonic::Request<DynamicMessage>) -> Self::Future {
+            let output = self.0.get_message_by_name("echo.EchoReply").unwrap();
+            Box::pin(async move {
+                let request = request.into_inner();
+                let text = request.get_field_by_name("text").unwrap();
+                let times = request.get_field_by_name("times").unwrap();
+                let times = times.as_i32().unwrap();
+                if times < 0 {
+                    return Err(Status::invalid_argument("times must not be negative"));
+                }
+
+                let mut reply = DynamicMessage::new(output);
+                let text = vec![text.as_str().unwrap(); times as usize].join(" ");
+                reply.set_field_by_name("text", Value::String(text));
+                Ok(tonic::Response::new(reply))
+            })
+        }
+    }
+
+    fn write_proto() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("poke-grpc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("echo.proto");
+        std::fs::write(&path, ECHO_PROTO).unwrap();
+        path
+    }
+
+    async fn echo_server() -> std::net::SocketAddr {
+        echo_server_with_tls(None).await
+    }
+
+    async fn echo_server_with_tls(tls: Option<ServerTlsConfig>) -> std::net::SocketAddr {
+        let pool = compile_protos(&[write_proto()]).unwrap();
+        let descriptors: &'static [u8] = pool.encode_to_vec().leak();
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(descriptors)
+            .build_v1()
+            .unwrap();
+
+        let incoming = TcpIncoming::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = incoming.local_addr().unwrap();
+        let mut server = tonic::transport::Server::builder();
+        if let Some(tls) = tls {
+            server = server.tls_config(tls).unwrap();
+        }
+        tokio::spawn(
+            server
+                .add_service(EchoServer(pool))
+                .add_service(reflection)
+                .serve_with_incoming(incoming),
+        );
+        addr
+    }
+
+    #[test]
+    fn test_parse_target() {
+        let target = GrpcTarget::parse("localhost:50051/echo.Echo/Say").unwrap();
+        assert_eq!(
+            target,
+            GrpcTarget {
+                endpoint: "http://localhost:50051".to_string(),
+                tls: false,
+                service: "echo.Echo".to_string(),
+                method: "Say".to_string(),
+            }
+        );
+
+        let target = GrpcTarget::parse("grpcs://api.example.com/pkg.v1.Api/Get").unwrap();
+        assert_eq!(target.endpoint, "https://api.example.com");
+        assert!(target.tls);
+
+        assert!(GrpcTarget::parse("localhost:50051").is_err());
+        assert!(GrpcTarget::parse("ftp://host/pkg.Svc/M").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_call_with_proto_file() {
+        let addr = echo_server().await;
+        let mut request = Request::new(Method::Grpc, format!("{}/echo.Echo/Say", addr))
+            .with_body(r#"{"text": "{{word}}", "times": 2}"#);
+        request.proto_files = vec![write_proto()];
+        let variables = HashMap::from([("word".to_string(), "hi".to_string())]);
+
+        let response = Client::new().execute(&request, &variables).await.unwrap();
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("grpc-status"), Some("0"));
+        let body: serde_json::Value =
+            serde_json::from_str(response.text.as_deref().unwrap()).unwrap();
+        assert_eq!(body, serde_json::json!({"text": "hi hi"}));
+    }
+
+    #[tokio::test]
+    async fn test_call_can_be_stopped() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            loop {
+                // Accept the connection but never speak HTTP/2 on it.
+                let (socket, _) = listener.accept().await.unwrap();
+                sockets.push(socket);
+            }
+        });
+        let request = Request::new(Method::Grpc, format!("grpc://{}/echo.Echo/Say", addr));
+
+        let started = Instant::now();
+        let err = Client::new()
+            .execute_streaming(
+                &request,
+                &HashMap::new(),
+                |_| {},
+                tokio::time::sleep(Duration::from_millis(100)),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, Error::Http(HttpError::Cancelled)), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_call_with_reflection() {
+        let addr = echo_server().await;
+        let request = Request::new(Method::Grpc, format!("grpc://{}/echo.Echo/Say", addr))
+            .with_body(r#"{"text": "ok", "times": 1}"#);
+
+        let response = Client::new()
+            .execute(&request, &HashMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, 200);
+        assert!(response.text.unwrap().contains("\"ok\""));
+    }
+
+    #[tokio::test]
+    async fn test_tls_uses_configured_ca() {
+        let tls =
+            ServerTlsConfig::new().identity(Identity::from_pem(LOCALHOST_CERT, LOCALHOST_KEY));
+        let addr = echo_server_with_tls(Some(tls)).await;
+        let request = Request::new(
+            Method::Grpc,
+            format!("grpcs://localhost:{}/echo.Echo/Say", addr.port()),
+        )
+        .with_body(r#"{"text": "secure", "times": 1}"#);
+
+        let untrusted = Client::new().execute(&request, &HashMap::new()).await;
+        assert!(matches!(
+            untrusted,
+            Err(crate::error::Error::Grpc(GrpcError::Transport(_)))
+        ));
+
+        let dir = std::env::temp_dir().join(format!("poke-grpc-tls-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let ca = dir.join("localhost.pem");
+        std::fs::write(&ca, LOCALHOST_CERT).unwrap();
+        let client = Client::builder()
+            .ssl(SslConfiguration {
+                ca_certificates: vec![ca],
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        let response = client.execute(&request, &HashMap::new()).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert!(response.text.unwrap().contains("\"secure\""));
+    }
+
+    #[tokio::test]
+    async fn test_error_status_is_a_response() {
+        let addr = echo_server().await;
+        let request = Request::new(Method::Grpc, format!("{}/echo.Echo/Say", addr))
+            .with_body(r#"{"text": "no", "times": -1}"#);
+
+        let response = Client::new()
+            .execute(&request, &HashMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, 400);
+        assert_eq!(response.header("grpc-status"), Some("3"));
+        assert_eq!(
+            response.text.as_deref(),
+            Some("InvalidArgument: times must not be negative")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method() {
+        let addr = echo_server().await;
+        let request = Request::new(Method::Grpc, format!("{}/echo.Echo/Shout", addr));
+
+        let err = Client::new()
+            .execute(&request, &HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::Error::Grpc(GrpcError::MethodNotFound(_))
+        ));
+    }
+}
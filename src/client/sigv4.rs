@@ -0,0 +1,403 @@
+use super::auth::hex;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, KeyInit, Mac};
+use reqwest::Url;
+use reqwest::header::HeaderMap;
+use sha2::{Digest, Sha256};
+
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// Credentials from an `Authorization: AWS <accessKey> <secretKey> [token:<t>]
+/// [region:<r>] [service:<s>]` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    pub access_key: String,
+    pub secret_key: String,
+    pub session_token: Option<String>,
+    pub region: Option<String>,
+    pub service: Option<String>,
+}
+
+impl AwsCredentials {
+    pub(crate) fn parse(credentials: &str) -> Option<Self> {
+        let mut words = credentials.split_whitespace();
+        let mut aws = AwsCredentials {
+            access_key: words.next()?.to_string(),
+            secret_key: words.next()?.to_string(),
+            session_token: None,
+            region: None,
+            service: None,
+        };
+
+        for word in words {
+            let (name, value) = word.split_once(':')?;
+            let value = Some(value.to_string());
+            match name.to_ascii_lowercase().as_str() {
+                "token" => aws.session_token = value,
+                "region" => aws.region = value,
+                "service" => aws.service = value,
+                _ => return None,
+            }
+        }
+
+        Some(aws)
+    }
+
+    /// Region and service, taken from the host name when they were not given explicitly.
+    fn scope(&self, url: &Url) -> (String, String) {
+        let (host_service, host_region) = endpoint_scope(url.host_str().unwrap_or_default());
+        let region = self
+            .region
+            .clone()
+            .or(host_region)
+            .unwrap_or_else(|| DEFAULT_REGION.to_string());
+        let service = self
+            .service
+            .clone()
+            .or(host_service)
+            .unwrap_or_else(|| "execute-api".to_string());
+        (region, service)
+    }
+
+    /// Computes the headers that sign the request: `X-Amz-Date`, the optional
+    /// `X-Amz-Security-Token` and `X-Amz-Content-Sha256`, and `Authorization`.
+    ///
+    /// `headers` are the final request headers; all of them are signed along with `Host`.
+    pub(crate) fn sign(
+        &self,
+        method: &str,
+        url: &Url,
+        headers: &HeaderMap,
+        body: &[u8],
+        time: DateTime<Utc>,
+    ) -> Vec<(String, String)> {
+        let (region, service) = self.scope(url);
+        let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = time.format("%Y%m%d").to_string();
+        let payload_hash = hex(&Sha256::digest(body));
+
+        let mut added = vec![("x-amz-date".to_string(), amz_date.clone())];
+        if let Some(ref token) = self.session_token {
+            added.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        if service == "s3" {
+            added.push(("x-amz-content-sha256".to_string(), payload_hash.clone()));
+        }
+
+        let mut signed: Vec<(String, String)> = headers
+            .iter()
+            .filter(|(name, _)| *name != "authorization")
+            .map(|(name, value)| {
+                (
+                    name.as_str().to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).to_string(),
+                )
+            })
+            .chain(added.iter().cloned())
+            .collect();
+        if !signed.iter().any(|(name, _)| name == "host") {
+            signed.push(("host".to_string(), host_header(url)));
+        }
+
+        let canonical_request =
+            canonical_request(method, url, &mut signed, &payload_hash, service == "s3");
+        let signed_headers: Vec<&str> = signed.iter().map(|(name, _)| name.as_str()).collect();
+        let signed_headers = signed_headers.join(";");
+
+        let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key = signing_key(&self.secret_key, &date, &region, &service);
+        let signature = hex(&hmac(&key, string_to_sign.as_bytes()));
+
+        added.push((
+            "authorization".to_string(),
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key, scope, signed_headers, signature
+            ),
+        ));
+        added
+    }
+}
+
+/// Builds the canonical request, sorting and merging `headers` in place so the caller
+/// can list them as `SignedHeaders`.
+fn canonical_request(
+    method: &str,
+    url: &Url,
+    headers: &mut Vec<(String, String)>,
+    payload_hash: &str,
+    single_encode_path: bool,
+) -> String {
+    let path = if url.path().is_empty() {
+        "/"
+    } else {
+        url.path()
+    };
+    // The URL path is already percent-encoded once; every service but S3 expects twice.
+    let path = if single_encode_path {
+        path.to_string()
+    } else {
+        path.split('/')
+            .map(uri_encode)
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| (uri_encode(&key), uri_encode(&value)))
+        .collect();
+    query.sort();
+    let query: Vec<String> = query
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+
+    for (name, value) in headers.iter_mut() {
+        *name = name.to_ascii_lowercase();
+        *value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+    headers.sort_by(|a, b| a.0.cmp(&b.0));
+    headers.dedup_by(|next, kept| {
+        if next.0 == kept.0 {
+            kept.1 = format!("{},{}", kept.1, next.1);
+            true
+        } else {
+            false
+        }
+    });
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let signed_headers: Vec<&str> = headers.iter().map(|(name, _)| name.as_str()).collect();
+
+    format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        path,
+        query.join("&"),
+        canonical_headers,
+        signed_headers.join(";"),
+        payload_hash
+    )
+}
+
+/// Percent-encodes everything except the unreserved characters `A-Z a-z 0-9 - _ . ~`.
+fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn host_header(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+/// Guesses service and region from `service.region.amazonaws.com` style host names.
+fn endpoint_scope(host: &str) -> (Option<String>, Option<String>) {
+    let Some(prefix) = host
+        .strip_suffix(".amazonaws.com")
+        .or_else(|| host.strip_suffix(".amazonaws.com.cn"))
+    else {
+        return (None, None);
+    };
+
+    let parts: Vec<&str> = prefix.split('.').collect();
+    let is_region = |part: &str| part.contains('-') && part.ends_with(|c: char| c.is_ascii_digit());
+    match parts.as_slice() {
+        [.., service, region] if is_region(region) => {
+            (Some(service.to_string()), Some(region.to_string()))
+        }
+        [.., service] => (Some(service.to_string()), None),
+        [] => (None, None),
+    }
+}
+
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    let key = hmac(&key, region.as_bytes());
+    let key = hmac(&key, service.as_bytes());
+    hmac(&key, b"aws4_request")
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use reqwest::header::HeaderValue;
+
+    const SECRET_KEY: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+
+    fn test_suite_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key: "AKIDEXAMPLE".to_string(),
+            secret_key: SECRET_KEY.to_string(),
+            session_token: None,
+            region: Some("us-east-1".to_string()),
+            service: Some("service".to_string()),
+        }
+    }
+
+    fn test_suite_time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap()
+    }
+
+    fn authorization(signed: &[(String, String)]) -> &str {
+        &signed
+            .iter()
+            .find(|(name, _)| name == "authorization")
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn test_parse_credentials() {
+        assert_eq!(
+            AwsCredentials::parse("AKID secret token:t0k region:eu-west-1 service:execute-api"),
+            Some(AwsCredentials {
+                access_key: "AKID".to_string(),
+                secret_key: "secret".to_string(),
+                session_token: Some("t0k".to_string()),
+                region: Some("eu-west-1".to_string()),
+                service: Some("execute-api".to_string()),
+            })
+        );
+        assert_eq!(AwsCredentials::parse("AKID"), None);
+        assert_eq!(AwsCredentials::parse("AKID secret unknown:x"), None);
+    }
+
+    #[test]
+    fn test_endpoint_scope() {
+        assert_eq!(
+            endpoint_scope("abc123.execute-api.eu-west-1.amazonaws.com"),
+            (
+                Some("execute-api".to_string()),
+                Some("eu-west-1".to_string())
+            )
+        );
+        assert_eq!(
+            endpoint_scope("iam.amazonaws.com"),
+            (Some("iam".to_string()), None)
+        );
+        assert_eq!(endpoint_scope("example.com"), (None, None));
+    }
+
+    #[test]
+    fn test_signing_key_example() {
+        let key = signing_key(SECRET_KEY, "20150830", "us-east-1", "iam");
+        assert_eq!(
+            hex(&key),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+    }
+
+    #[test]
+    fn test_get_vanilla() {
+        let url = Url::parse("https://example.amazonaws.com/").unwrap();
+        let signed =
+            test_suite_credentials().sign("GET", &url, &HeaderMap::new(), b"", test_suite_time());
+
+        assert_eq!(
+            authorization(&signed),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_get_vanilla_query_order_key_case() {
+        let url = Url::parse("https://example.amazonaws.com/?Param2=value2&Param1=value1").unwrap();
+        let signed =
+            test_suite_credentials().sign("GET", &url, &HeaderMap::new(), b"", test_suite_time());
+
+        assert!(authorization(&signed).ends_with(
+            "Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+        ));
+    }
+
+    #[test]
+    fn test_post_x_www_form_urlencoded() {
+        let url = Url::parse("https://example.amazonaws.com/").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        let signed = test_suite_credentials().sign(
+            "POST",
+            &url,
+            &headers,
+            b"Param1=value1",
+            test_suite_time(),
+        );
+
+        assert!(authorization(&signed).contains("SignedHeaders=content-type;host;x-amz-date, "));
+        assert!(authorization(&signed).ends_with(
+            "Signature=ff11897932ad3f4e8b18135d722051e5ac45fc38421b1da7b9d196a0fe09473a"
+        ));
+    }
+
+    #[test]
+    fn test_iam_list_users_example() {
+        let url =
+            Url::parse("https://iam.amazonaws.com/?Action=ListUsers&Version=2010-05-08").unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            HeaderValue::from_static("application/x-www-form-urlencoded; charset=utf-8"),
+        );
+        let credentials = AwsCredentials {
+            region: None,
+            service: None,
+            ..test_suite_credentials()
+        };
+        let signed = credentials.sign("GET", &url, &headers, b"", test_suite_time());
+
+        assert_eq!(
+            authorization(&signed),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date, \
+             Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+        );
+    }
+
+    #[test]
+    fn test_session_token_is_sent_and_signed() {
+        let url = Url::parse("https://example.amazonaws.com/").unwrap();
+        let credentials = AwsCredentials {
+            session_token: Some("t0k".to_string()),
+            ..test_suite_credentials()
+        };
+        let signed = credentials.sign("GET", &url, &HeaderMap::new(), b"", test_suite_time());
+
+        assert!(signed.contains(&("x-amz-security-token".to_string(), "t0k".to_string())));
+        assert!(
+            authorization(&signed).contains("SignedHeaders=host;x-amz-date;x-amz-security-token, ")
+        );
+    }
+}
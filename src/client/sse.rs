@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+/// A single event received from a `text/event-stream` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    pub event: String,
+    pub id: Option<String>,
+    pub data: String,
+    /// Time since the stream was opened.
+    pub elapsed: Duration,
+}
+
+/// Incremental parser for the `text/event-stream` format.
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    line: Vec<u8>,
+    after_cr: bool,
+    event: Option<String>,
+    data: Vec<String>,
+    last_id: Option<String>,
+}
+
+impl SseParser {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a chunk of the stream and returns the events completed by it.
+    pub(crate) fn feed(&mut self, chunk: &[u8], elapsed: Duration) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+
+        for &byte in chunk {
+            if self.after_cr {
+                self.after_cr = false;
+                if byte == b'\n' {
+                    continue;
+                }
+            }
+
+            match byte {
+                b'\r' | b'\n' => {
+                    self.after_cr = byte == b'\r';
+                    let line = std::mem::take(&mut self.line);
+                    let line = String::from_utf8_lossy(&line);
+                    if let Some(event) = self.process_line(&line, elapsed) {
+                        events.push(event);
+                    }
+                }
+                _ => self.line.push(byte),
+            }
+        }
+
+        events
+    }
+
+    fn process_line(&mut self, line: &str, elapsed: Duration) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch(elapsed);
+        }
+
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+            _ => {}
+        }
+
+        None
+    }
+
+    fn dispatch(&mut self, elapsed: Duration) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+
+        Some(SseEvent {
+            event: event
+                .filter(|event| !event.is_empty())
+                .unwrap_or_else(|| "message".to_string()),
+            id: self.last_id.clone(),
+            data: std::mem::take(&mut self.data).join("\n"),
+            elapsed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(
+            b": keep-alive\n\nevent: update\nid: 1\ndata: {\"a\":1}\n\ndata: plain\n\n",
+            Duration::ZERO,
+        );
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event, "update");
+        assert_eq!(events[0].id.as_deref(), Some("1"));
+        assert_eq!(events[0].data, "{\"a\":1}");
+        assert_eq!(events[1].event, "message");
+        assert_eq!(events[1].id.as_deref(), Some("1"));
+        assert_eq!(events[1].data, "plain");
+    }
+
+    #[test]
+    fn test_parse_event_split_across_chunks() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"data: first\r\nda", Duration::ZERO).is_empty());
+        assert!(parser.feed(b"ta: second\r", Duration::ZERO).is_empty());
+
+        let events = parser.feed(b"\n\r\n", Duration::from_secs(1));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "first\nsecond");
+        assert_eq!(events[0].elapsed, Duration::from_secs(1));
+    }
+}
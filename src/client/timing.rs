@@ -0,0 +1,219 @@
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use rustls::NamedGroup;
+use rustls::client::Tls13ClientSessionValue;
+use rustls::client::{ClientSessionMemoryCache, ClientSessionStore, Tls12ClientSessionValue};
+use rustls_pki_types::ServerName;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Phase breakdown of a single request.
+///
+/// `dns` and `connect` are `None` when a pooled connection was reused (or, for
+/// `dns`, when the URL host is an IP address). `tls_handshake` is measured on its
+/// own when rustls sets up the connection; connections made through native-tls
+/// (PKCS #12 client certificates) only set `tls`, and `connect` then covers both
+/// the TCP connect and the handshake.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timings {
+    pub dns: Option<Duration>,
+    pub connect: Option<Duration>,
+    #[serde(default)]
+    pub tls_handshake: Option<Duration>,
+    pub ttfb: Duration,
+    pub download: Duration,
+    pub tls: bool,
+}
+
+impl Timings {
+    /// Returns the recorded phases in order, labelled for display.
+    pub fn phases(&self) -> Vec<(&'static str, Duration)> {
+        let mut phases = Vec::new();
+        if let Some(dns) = self.dns {
+            phases.push(("DNS", dns));
+        }
+        if let Some(connect) = self.connect {
+            let label = if self.tls && self.tls_handshake.is_none() {
+                "Connect+TLS"
+            } else {
+                "Connect"
+            };
+            phases.push((label, connect));
+        }
+        if let Some(tls_handshake) = self.tls_handshake {
+            phases.push(("TLS", tls_handshake));
+        }
+        phases.push(("TTFB", self.ttfb));
+        phases.push(("Download", self.download));
+        phases
+    }
+
+    /// Time spent setting up the connection before the request was sent.
+    pub fn connection_setup(&self) -> Duration {
+        self.dns.unwrap_or_default()
+            + self.connect.unwrap_or_default()
+            + self.tls_handshake.unwrap_or_default()
+    }
+}
+
+#[derive(Default)]
+struct Recording {
+    timings: Timings,
+    /// When rustls started the handshake on the connection being set up.
+    handshake_start: Option<Instant>,
+}
+
+tokio::task_local! {
+    static CONNECT_TIMINGS: RefCell<Recording>;
+}
+
+/// Runs `future` while recording DNS, connect and TLS phases triggered by it.
+///
+/// Connection setup is polled as part of the request future, so the resolver,
+/// connector layer and TLS session store below can report into a task-local
+/// without any shared state between concurrent requests.
+pub(crate) async fn record_connect<F: Future>(future: F) -> (F::Output, Timings) {
+    CONNECT_TIMINGS
+        .scope(RefCell::new(Recording::default()), async {
+            let output = future.await;
+            let timings = CONNECT_TIMINGS.with(|recording| recording.borrow().timings);
+            (output, timings)
+        })
+        .await
+}
+
+fn record(update: impl FnOnce(&mut Recording)) {
+    let _ = CONNECT_TIMINGS.try_with(|recording| update(&mut recording.borrow_mut()));
+}
+
+/// System resolver that reports how long each lookup took.
+pub(crate) struct TimingResolver;
+
+impl Resolve for TimingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let start = Instant::now();
+            let addrs: Vec<_> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            record(|recording| recording.timings.dns = Some(start.elapsed()));
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Connector layer that reports how long establishing a new connection took.
+#[derive(Clone)]
+pub(crate) struct ConnectTimingLayer;
+
+impl<S> Layer<S> for ConnectTimingLayer {
+    type Service = ConnectTiming<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectTiming { inner }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ConnectTiming<S> {
+    inner: S,
+}
+
+impl<S, R> Service<R> for ConnectTiming<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let connecting = self.inner.call(request);
+        Box::pin(async move {
+            let start = Instant::now();
+            record(|recording| recording.handshake_start = None);
+            let result = connecting.await;
+            let end = Instant::now();
+            record(|recording| {
+                let timings = &mut recording.timings;
+                let tcp_end = match recording.handshake_start.take() {
+                    Some(handshake_start) if handshake_start >= start => {
+                        timings.tls_handshake = Some(end - handshake_start);
+                        handshake_start
+                    }
+                    _ => end,
+                };
+                // The resolver runs inside the connector, so take the lookup out again.
+                timings.connect =
+                    Some((tcp_end - start).saturating_sub(timings.dns.unwrap_or_default()));
+            });
+            result
+        })
+    }
+}
+
+/// TLS session cache that notes when a handshake starts.
+///
+/// rustls looks up a session to resume while it builds the ClientHello, which is
+/// the first thing it does once the TCP connection is up, so the lookup splits the
+/// TCP connect from the TLS handshake.
+#[derive(Debug)]
+pub(crate) struct HandshakeTimingStore {
+    inner: ClientSessionMemoryCache,
+}
+
+impl Default for HandshakeTimingStore {
+    fn default() -> Self {
+        Self {
+            inner: ClientSessionMemoryCache::new(256),
+        }
+    }
+}
+
+impl ClientSessionStore for HandshakeTimingStore {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.inner.set_kx_hint(server_name, group);
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        self.inner.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.inner.set_tls12_session(server_name, value);
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.inner.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.inner.remove_tls12_session(server_name);
+    }
+
+    fn insert_tls13_ticket(
+        &self,
+        server_name: ServerName<'static>,
+        value: Tls13ClientSessionValue,
+    ) {
+        self.inner.insert_tls13_ticket(server_name, value);
+    }
+
+    fn take_tls13_ticket(
+        &self,
+        server_name: &ServerName<'static>,
+    ) -> Option<Tls13ClientSessionValue> {
+        record(|recording| {
+            recording.handshake_start.get_or_insert_with(Instant::now);
+        });
+        self.inner.take_tls13_ticket(server_name)
+    }
+}
@@ -0,0 +1,349 @@
+use super::{Client, Response, Timings};
+use crate::error::{HttpError, Result};
+use crate::http::Request;
+use crate::variable;
+use futures_util::{SinkExt, StreamExt};
+use reqwest::cookie::CookieStore;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+
+/// Headers tungstenite sets itself for the opening handshake.
+const HANDSHAKE_HEADERS: &[&str] = &[
+    "connection",
+    "upgrade",
+    "sec-websocket-key",
+    "sec-websocket-version",
+    "host",
+];
+
+/// Longest binary payload shown in full in a session transcript.
+const BINARY_PREVIEW_BYTES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDirection {
+    Sent,
+    Received,
+}
+
+impl MessageDirection {
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            MessageDirection::Sent => "→",
+            MessageDirection::Received => "←",
+        }
+    }
+}
+
+/// A single frame exchanged during a WebSocket session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSocketMessage {
+    pub direction: MessageDirection,
+    /// Text payload, or a hex preview for binary frames.
+    pub data: String,
+    pub binary: bool,
+    /// Time since the session was opened.
+    pub elapsed: Duration,
+}
+
+impl Client {
+    /// Opens a WebSocket session for `request` and sends the messages from its body.
+    ///
+    /// Afterwards every message received on `outgoing` is sent, and each frame in either
+    /// direction is reported through `on_message`. The session lasts until the server
+    /// closes it or `stop` completes; the client timeout only covers fetching OAuth2
+    /// tokens and the handshake.
+    pub async fn execute_websocket(
+        &self,
+        request: &Request,
+        variables: &HashMap<String, String>,
+        mut outgoing: mpsc::UnboundedReceiver<String>,
+        on_message: impl Fn(&WebSocketMessage),
+        stop: impl Future,
+    ) -> Result<Response> {
+        let start = Instant::now();
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        let mut stop = std::pin::pin!(stop);
+        let request = &*self
+            .resolve_auth_tokens(request, variables, deadline, &mut stop)
+            .await?;
+
+        let url = websocket_url(&variable::substitute(&request.url, variables)?);
+        let mut handshake = url
+            .as_str()
+            .into_client_request()
+            .map_err(HttpError::from)?;
+
+        for (key, value) in &request.headers {
+            if HANDSHAKE_HEADERS.contains(&key.to_ascii_lowercase().as_str()) {
+                continue;
+            }
+            let value = variable::substitute(value, variables)?;
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::try_from(key.as_str()),
+                HeaderValue::try_from(value.as_str()),
+            ) {
+                handshake.headers_mut().insert(name, value);
+            }
+        }
+
+        if !request.no_cookie_jar
+            && !handshake.headers().contains_key("cookie")
+            && let Ok(cookie_url) = reqwest::Url::parse(&cookie_url(&url))
+            && let Some(cookies) = self.cookie_jar.cookies(&cookie_url)
+            && let Ok(cookies) = HeaderValue::from_bytes(cookies.as_bytes())
+        {
+            handshake.headers_mut().insert("cookie", cookies);
+        }
+
+        let (socket, handshake_response) =
+            tokio::time::timeout_at(deadline, tokio_tungstenite::connect_async(handshake))
+                .await
+                .map_err(|_| HttpError::Timeout)?
+                .map_err(HttpError::from)?;
+        let ttfb = start.elapsed();
+
+        let status = handshake_response.status();
+        let headers: Vec<(String, String)> = handshake_response
+            .headers()
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+            .collect();
+
+        let session_start = Instant::now();
+        let (mut sink, mut stream) = socket.split();
+        let mut messages = Vec::new();
+        let mut record = |direction, data, binary| {
+            let message = WebSocketMessage {
+                direction,
+                data,
+                binary,
+                elapsed: session_start.elapsed(),
+            };
+            on_message(&message);
+            messages.push(message);
+        };
+
+        for message in request.websocket_messages() {
+            let message = variable::substitute(&message, variables)?;
+            sink.send(Message::text(message.clone()))
+                .await
+                .map_err(HttpError::from)?;
+            record(MessageDirection::Sent, message, false);
+        }
+
+        loop {
+            tokio::select! {
+                _ = &mut stop => {
+                    let _ = sink.send(Message::Close(None)).await;
+                    break;
+                }
+                Some(message) = outgoing.recv() => {
+                    sink.send(Message::text(message.clone()))
+                        .await
+                        .map_err(HttpError::from)?;
+                    record(MessageDirection::Sent, message, false);
+                }
+                frame = stream.next() => match frame {
+                    Some(Ok(Message::Text(text))) => {
+                        record(MessageDirection::Received, text.to_string(), false);
+                    }
+                    Some(Ok(Message::Binary(bytes))) => {
+                        record(MessageDirection::Received, binary_preview(&bytes), true);
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => return Err(HttpError::from(err).into()),
+                    None => break,
+                },
+            }
+        }
+
+        let transcript: String = messages
+            .iter()
+            .map(|message| format!("{} {}\n", message.direction.arrow(), message.data))
+            .collect();
+        Ok(Response {
+            status: status.as_u16(),
+            status_text: status.canonical_reason().unwrap_or("Unknown").to_string(),
+            headers,
+            body_size: transcript.len() as u64,
+            body: transcript.clone().into_bytes(),
+            text: Some(transcript),
+            body_file: None,
+            events: Vec::new(),
+            messages,
+            duration: start.elapsed(),
+            timings: Timings {
+                ttfb,
+                download: session_start.elapsed(),
+                tls: url.starts_with("wss://"),
+                ..Timings::default()
+            },
+            request: None,
+        })
+    }
+}
+
+/// Accepts `http(s)://` URLs for WebSocket requests by switching to the `ws(s)` scheme.
+fn websocket_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else {
+        url.to_string()
+    }
+}
+
+/// Maps a WebSocket URL back to HTTP so cookies are matched like for regular requests.
+fn cookie_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("ws://") {
+        format!("http://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("wss://") {
+        format!("https://{}", rest)
+    } else {
+        url.to_string()
+    }
+}
+
+fn binary_preview(bytes: &[u8]) -> String {
+    let hex: Vec<String> = bytes
+        .iter()
+        .take(BINARY_PREVIEW_BYTES)
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let ellipsis = if bytes.len() > BINARY_PREVIEW_BYTES {
+        " …"
+    } else {
+        ""
+    };
+    format!("{} bytes: {}{}", bytes.len(), hex.join(" "), ellipsis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::http::Method;
+    use crate::oauth::OAuth2Config;
+    use tokio::net::TcpListener;
+
+    async fn echo_server() -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(socket).await.unwrap();
+            while let Some(Ok(message)) = socket.next().await {
+                if message.is_text() || message.is_binary() {
+                    socket.send(message).await.unwrap();
+                }
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(websocket_url("http://host/ws"), "ws://host/ws");
+        assert_eq!(websocket_url("https://host/ws"), "wss://host/ws");
+        assert_eq!(websocket_url("ws://host/ws"), "ws://host/ws");
+    }
+
+    #[tokio::test]
+    async fn test_echo_session() {
+        let addr = echo_server().await;
+        let client = Client::new();
+        let request = Request::new(Method::WebSocket, format!("ws://{}/echo", addr))
+            .with_body("{\"n\": {{n}}}\n===\nsecond");
+        let variables = HashMap::from([("n".to_string(), "1".to_string())]);
+
+        let (outgoing_tx, outgoing_rx) = mpsc::unbounded_channel();
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let stop_tx = std::sync::Mutex::new(Some(stop_tx));
+        let received = std::sync::Mutex::new(0);
+
+        let response = client
+            .execute_websocket(
+                &request,
+                &variables,
+                outgoing_rx,
+                |message| {
+                    if message.direction != MessageDirection::Received {
+                        return;
+                    }
+                    let mut received = received.lock().unwrap();
+                    *received += 1;
+                    match *received {
+                        2 => outgoing_tx.send("typed".to_string()).unwrap(),
+                        3 => {
+                            if let Some(stop_tx) = stop_tx.lock().unwrap().take() {
+                                let _ = stop_tx.send(());
+                            }
+                        }
+                        _ => {}
+                    }
+                },
+                stop_rx,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, 101);
+        let received: Vec<_> = response
+            .messages
+            .iter()
+            .filter(|message| message.direction == MessageDirection::Received)
+            .map(|message| message.data.as_str())
+            .collect();
+        assert_eq!(received, ["{\"n\": 1}", "second", "typed"]);
+        assert_eq!(response.messages.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_token_request_times_out_and_can_be_stopped() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            loop {
+                // Accept the token request but never answer it.
+                let (socket, _) = listener.accept().await.unwrap();
+                sockets.push(socket);
+            }
+        });
+
+        let config: OAuth2Config = serde_json::from_value(serde_json::json!({
+            "Type": "OAuth2",
+            "Grant Type": "Client Credentials",
+            "Token URL": format!("http://{}/token", addr),
+            "Client ID": "poke"
+        }))
+        .unwrap();
+        let client = Client::builder()
+            .timeout(Duration::from_millis(200))
+            .auth(HashMap::from([("my-auth".to_string(), config)]))
+            .build()
+            .unwrap();
+        let request = Request::new(Method::WebSocket, format!("ws://{}/ws", addr))
+            .with_header("Authorization", "Bearer {{$auth.token(\"my-auth\")}}");
+        let variables = HashMap::new();
+        let run = |stop| {
+            let (_outgoing_tx, outgoing_rx) = mpsc::unbounded_channel();
+            client.execute_websocket(&request, &variables, outgoing_rx, |_| {}, stop)
+        };
+
+        let (_stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let err = run(stop_rx).await.unwrap_err();
+        assert!(matches!(err, Error::Http(HttpError::Timeout)), "{err}");
+
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        stop_tx.send(()).unwrap();
+        let err = run(stop_rx).await.unwrap_err();
+        assert!(matches!(err, Error::Http(HttpError::Cancelled)), "{err}");
+    }
+}
@@ -0,0 +1,49 @@
+use super::{Snippet, quoted};
+
+pub(super) fn generate(snippet: &Snippet) -> String {
+    let mut out = String::new();
+    for note in &snippet.notes {
+        out.push_str(&format!("// {}\n", note));
+    }
+    out.push_str("package main\n\n");
+    out.push_str("import (\n\t\"fmt\"\n\t\"io\"\n\t\"net/http\"\n");
+    if snippet.body.is_some() {
+        out.push_str("\t\"strings\"\n");
+    }
+    out.push_str(")\n\n");
+    out.push_str("func main() {\n");
+
+    let body = match snippet.body {
+        Some(ref body) => {
+            out.push_str(&format!("\tbody := strings.NewReader({})\n", quoted(body)));
+            "body"
+        }
+        None => "nil",
+    };
+    out.push_str(&format!(
+        "\treq, err := http.NewRequest({}, {}, {})\n",
+        quoted(snippet.method),
+        quoted(&snippet.url),
+        body
+    ));
+    out.push_str(CHECK);
+    for (key, value) in &snippet.headers {
+        out.push_str(&format!(
+            "\treq.Header.Set({}, {})\n",
+            quoted(key),
+            quoted(value)
+        ));
+    }
+
+    out.push_str("\n\tresp, err := http.DefaultClient.Do(req)\n");
+    out.push_str(CHECK);
+    out.push_str("\tdefer resp.Body.Close()\n\n");
+    out.push_str("\trespBody, err := io.ReadAll(resp.Body)\n");
+    out.push_str(CHECK);
+    out.push_str("\tfmt.Println(resp.Status)\n");
+    out.push_str("\tfmt.Println(string(respBody))\n");
+    out.push_str("}\n");
+    out
+}
+
+const CHECK: &str = "\tif err != nil {\n\t\tpanic(err)\n\t}\n";
@@ -0,0 +1,21 @@
+use super::Snippet;
+use crate::curl::quote;
+
+pub(super) fn generate(snippet: &Snippet) -> String {
+    let mut out = String::new();
+    for note in &snippet.notes {
+        out.push_str(&format!("# {}\n", note));
+    }
+
+    let mut args = vec!["http".to_string()];
+    if let Some(ref body) = snippet.body {
+        args.push(format!("--raw {}", quote(body)));
+    }
+    args.push(format!("{} {}", snippet.method, quote(&snippet.url)));
+    for (key, value) in &snippet.headers {
+        args.push(quote(&format!("{}:{}", key, value)));
+    }
+    out.push_str(&args.join(" \\\n  "));
+    out.push('\n');
+    out
+}
@@ -0,0 +1,42 @@
+use super::{Snippet, quoted};
+
+pub(super) fn generate(snippet: &Snippet) -> String {
+    let mut out = String::new();
+    for note in &snippet.notes {
+        out.push_str(&format!("// {}\n", note));
+    }
+
+    let mut options = Vec::new();
+    if snippet.method != "GET" {
+        options.push(format!("  method: {},\n", quoted(snippet.method)));
+    }
+    if !snippet.headers.is_empty() {
+        let mut headers = "  headers: {\n".to_string();
+        for (key, value) in &snippet.headers {
+            headers.push_str(&format!("    {}: {},\n", quoted(key), quoted(value)));
+        }
+        headers.push_str("  },\n");
+        options.push(headers);
+    }
+    if let Some(ref body) = snippet.body {
+        options.push(format!("  body: {},\n", quoted(body)));
+    }
+
+    if options.is_empty() {
+        out.push_str(&format!(
+            "const response = await fetch({});\n",
+            quoted(&snippet.url)
+        ));
+    } else {
+        out.push_str(&format!(
+            "const response = await fetch({}, {{\n",
+            quoted(&snippet.url)
+        ));
+        out.push_str(&options.concat());
+        out.push_str("});\n");
+    }
+
+    out.push_str("\nconsole.log(response.status);\n");
+    out.push_str("console.log(await response.text());\n");
+    out
+}
@@ -0,0 +1,268 @@
+mod go;
+mod httpie;
+mod javascript;
+mod python;
+mod rust;
+
+use crate::client::Authorization;
+use crate::curl;
+use crate::error::{CodegenError, Result};
+use crate::http::{Method, Request};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Curl,
+    Rust,
+    Python,
+    JavaScript,
+    Go,
+    Httpie,
+}
+
+impl Language {
+    pub const ALL: [Language; 6] = [
+        Language::Curl,
+        Language::Rust,
+        Language::Python,
+        Language::JavaScript,
+        Language::Go,
+        Language::Httpie,
+    ];
+
+    /// Name shown in the language picker.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Language::Curl => "cURL",
+            Language::Rust => "Rust (reqwest)",
+            Language::Python => "Python (requests)",
+            Language::JavaScript => "JavaScript (fetch)",
+            Language::Go => "Go (net/http)",
+            Language::Httpie => "HTTPie",
+        }
+    }
+
+    /// Name accepted on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Curl => "curl",
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::Go => "go",
+            Language::Httpie => "httpie",
+        }
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "js" => Ok(Language::JavaScript),
+            "py" => Ok(Language::Python),
+            "http" => Ok(Language::Httpie),
+            name => Language::ALL
+                .into_iter()
+                .find(|language| language.name() == name)
+                .ok_or_else(|| {
+                    let names: Vec<_> = Language::ALL.iter().map(Language::name).collect();
+                    format!(
+                        "unknown language '{}', expected one of {}",
+                        s,
+                        names.join(", ")
+                    )
+                }),
+        }
+    }
+}
+
+/// A request ready to be rendered: variables substituted and shorthand credentials
+/// encoded, with headers in alphabetical order.
+struct Snippet {
+    method: &'static str,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+    /// Things the snippet does not do, emitted as comments.
+    notes: Vec<String>,
+}
+
+/// Renders `request`, with `variables` substituted, as code in `language`.
+pub fn generate(
+    language: Language,
+    request: &Request,
+    variables: &HashMap<String, String>,
+) -> Result<String> {
+    let snippet = || Snippet::new(request, variables);
+    Ok(match language {
+        Language::Curl => {
+            if request.method == Method::Grpc {
+                return Err(CodegenError::UnsupportedMethod(request.method.to_string()).into());
+            }
+            curl::to_curl(request, variables)?
+        }
+        Language::Rust => rust::generate(&snippet()?),
+        Language::Python => python::generate(&snippet()?),
+        Language::JavaScript => javascript::generate(&snippet()?),
+        Language::Go => go::generate(&snippet()?),
+        Language::Httpie => httpie::generate(&snippet()?),
+    })
+}
+
+impl Snippet {
+    fn new(request: &Request, variables: &HashMap<String, String>) -> Result<Self> {
+        let method = match request.method {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Head => "HEAD",
+            Method::Options => "OPTIONS",
+            Method::WebSocket | Method::Grpc => {
+                return Err(CodegenError::UnsupportedMethod(request.method.to_string()).into());
+            }
+        };
+        let request = request.substituted(variables)?;
+
+        let mut notes = Vec::new();
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for (key, value) in &request.headers {
+            if key.eq_ignore_ascii_case("authorization") {
+                match Authorization::parse(value) {
+                    Some(Authorization::Digest { .. }) => {
+                        notes.push("Digest authentication is not generated".to_string());
+                        continue;
+                    }
+                    Some(Authorization::Aws(_)) => {
+                        notes.push("AWS Signature Version 4 signing is not generated".to_string());
+                        continue;
+                    }
+                    Some(auth) => {
+                        if let Some(encoded) = auth.encode() {
+                            headers.push((key.clone(), encoded));
+                            continue;
+                        }
+                    }
+                    None => {}
+                }
+            }
+            headers.push((key.clone(), value.clone()));
+        }
+        headers.sort();
+
+        Ok(Self {
+            method,
+            url: request.url,
+            headers,
+            body: request.body,
+            notes,
+        })
+    }
+}
+
+/// A double-quoted string literal with JSON escapes, valid in Python, JavaScript and Go.
+fn quoted(text: &str) -> String {
+    serde_json::to_string(text).expect("strings serialize to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn post() -> Request {
+        Request::new(Method::Post, "{{host}}/items?draft=true")
+            .with_header("Content-Type", "application/json")
+            .with_header("Authorization", "Basic admin:{{password}}")
+            .with_header("X-Request-Id", "42")
+            .with_body("{\n  \"name\": \"Widget \\\"Deluxe\\\"\",\n  \"price\": \"9€\"\n}")
+    }
+
+    fn get() -> Request {
+        Request::new(Method::Get, "{{host}}/items/1").with_header("Accept", "application/json")
+    }
+
+    fn variables() -> HashMap<String, String> {
+        HashMap::from([
+            ("host".to_string(), "https://api.example.com".to_string()),
+            ("password".to_string(), "s3cret".to_string()),
+        ])
+    }
+
+    /// Compares generated code with `tests/golden/codegen/<name>`. Set `UPDATE_GOLDEN=1`
+    /// to rewrite the golden files instead.
+    fn assert_golden(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden/codegen")
+            .join(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("reading {}: {}", path.display(), err));
+        assert_eq!(actual, expected, "generated code differs from {}", name);
+    }
+
+    fn check(language: Language, extension: &str) {
+        for (fixture, request) in [("post", post()), ("get", get())] {
+            let code = generate(language, &request, &variables()).unwrap();
+            assert_golden(&format!("{}.{}", fixture, extension), &code);
+        }
+    }
+
+    #[test]
+    fn test_rust() {
+        check(Language::Rust, "rs");
+    }
+
+    #[test]
+    fn test_python() {
+        check(Language::Python, "py");
+    }
+
+    #[test]
+    fn test_javascript() {
+        check(Language::JavaScript, "js");
+    }
+
+    #[test]
+    fn test_go() {
+        check(Language::Go, "go");
+    }
+
+    #[test]
+    fn test_httpie() {
+        check(Language::Httpie, "sh");
+    }
+
+    #[test]
+    fn test_unconverted_auth_is_noted() {
+        let request =
+            Request::new(Method::Get, "https://x.io").with_header("Authorization", "Digest a b");
+        let code = generate(Language::Python, &request, &HashMap::new()).unwrap();
+
+        assert!(code.starts_with("# Digest authentication is not generated\n"));
+        assert!(!code.contains("Authorization"));
+    }
+
+    #[test]
+    fn test_language_names() {
+        for language in Language::ALL {
+            assert_eq!(language.name().parse(), Ok(language));
+        }
+        assert_eq!("JS".parse(), Ok(Language::JavaScript));
+        assert!("cobol".parse::<Language>().is_err());
+        assert!(
+            generate(
+                Language::Go,
+                &Request::new(Method::Grpc, "localhost:50051/a.B/C"),
+                &HashMap::new()
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use super::{Snippet, quoted};
+
+pub(super) fn generate(snippet: &Snippet) -> String {
+    let mut out = String::new();
+    for note in &snippet.notes {
+        out.push_str(&format!("# {}\n", note));
+    }
+    out.push_str("import requests\n\n");
+    out.push_str(&format!("url = {}\n", quoted(&snippet.url)));
+
+    let mut arguments = vec![quoted(snippet.method), "url".to_string()];
+    if !snippet.headers.is_empty() {
+        out.push_str("headers = {\n");
+        for (key, value) in &snippet.headers {
+            out.push_str(&format!("    {}: {},\n", quoted(key), quoted(value)));
+        }
+        out.push_str("}\n");
+        arguments.push("headers=headers".to_string());
+    }
+    if let Some(ref body) = snippet.body {
+        out.push_str(&format!("data = {}\n", quoted(body)));
+        arguments.push("data=data.encode(\"utf-8\")".to_string());
+    }
+
+    out.push_str(&format!(
+        "\nresponse = requests.request({})\n\n",
+        arguments.join(", ")
+    ));
+    out.push_str("print(response.status_code)\n");
+    out.push_str("print(response.text)\n");
+    out
+}
@@ -0,0 +1,57 @@
+use super::Snippet;
+
+pub(super) fn generate(snippet: &Snippet) -> String {
+    let mut out = String::new();
+    for note in &snippet.notes {
+        out.push_str(&format!("// {}\n", note));
+    }
+    out.push_str("#[tokio::main]\n");
+    out.push_str("async fn main() -> Result<(), reqwest::Error> {\n");
+    out.push_str("    let client = reqwest::Client::new();\n");
+    out.push_str("    let response = client\n");
+
+    let url = string_literal(&snippet.url);
+    match snippet.method {
+        "GET" | "POST" | "PUT" | "PATCH" | "DELETE" | "HEAD" => {
+            out.push_str(&format!(
+                "        .{}({})\n",
+                snippet.method.to_lowercase(),
+                url
+            ));
+        }
+        method => out.push_str(&format!(
+            "        .request(reqwest::Method::{}, {})\n",
+            method, url
+        )),
+    }
+    for (key, value) in &snippet.headers {
+        out.push_str(&format!(
+            "        .header({}, {})\n",
+            string_literal(key),
+            string_literal(value)
+        ));
+    }
+    if let Some(ref body) = snippet.body {
+        out.push_str(&format!("        .body({})\n", string_literal(body)));
+    }
+    out.push_str("        .send()\n");
+    out.push_str("        .await?;\n\n");
+    out.push_str("    println!(\"{}\", response.status());\n");
+    out.push_str("    println!(\"{}\", response.text().await?);\n");
+    out.push_str("    Ok(())\n");
+    out.push_str("}\n");
+    out
+}
+
+/// A Rust string literal, raw when `text` has quotes or backslashes.
+fn string_literal(text: &str) -> String {
+    if !text.contains(['"', '\\']) && !text.contains(char::is_control) {
+        return format!("\"{}\"", text);
+    }
+
+    let mut hashes = "#".to_string();
+    while text.contains(&format!("\"{}", hashes)) {
+        hashes.push('#');
+    }
+    format!("r{}\"{}\"{}", hashes, text, hashes)
+}
@@ -0,0 +1,208 @@
+use crate::error::{CookieError, Result};
+use chrono::{DateTime, Utc};
+use cookie_store::{CookieExpiration, CookieStore, RawCookie};
+use reqwest::Url;
+use reqwest::header::HeaderValue;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// A snapshot of a single stored cookie, used for display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieInfo {
+    pub domain: String,
+    pub path: String,
+    pub name: String,
+    pub value: String,
+    pub expires: Option<DateTime<Utc>>,
+    pub secure: bool,
+    pub http_only: bool,
+}
+
+/// Cookie store shared by all requests sent through a [`crate::Client`].
+///
+/// When created with [`CookieJar::load`], the jar is backed by a JSON file and
+/// [`CookieJar::save`] writes persistent cookies back to it.
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    store: RwLock<CookieStore>,
+    path: Option<PathBuf>,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the jar from `path`, starting empty when the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        let store = if path.exists() {
+            let reader = BufReader::new(File::open(path)?);
+            cookie_store::serde::json::load(reader)
+                .map_err(|err| CookieError::InvalidStore(err.to_string()))?
+        } else {
+            CookieStore::default()
+        };
+
+        Ok(Self {
+            store: RwLock::new(store),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Returns the cookie file used for `http_file`, e.g. `api.cookies.json` for `api.http`.
+    pub fn path_for(http_file: &Path) -> PathBuf {
+        let stem = http_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "poke".to_string());
+        http_file.with_file_name(format!("{}.cookies.json", stem))
+    }
+
+    /// Writes persistent cookies to the backing file. Does nothing for in-memory jars.
+    pub fn save(&self) -> Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        let store = self.store.read().unwrap();
+        cookie_store::serde::json::save(&store, &mut writer)
+            .map_err(|err| CookieError::InvalidStore(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Returns all unexpired cookies ordered by domain, path and name.
+    pub fn entries(&self) -> Vec<CookieInfo> {
+        let store = self.store.read().unwrap();
+        let mut cookies: Vec<CookieInfo> = store
+            .iter_unexpired()
+            .map(|cookie| CookieInfo {
+                domain: cookie
+                    .domain
+                    .as_cow()
+                    .map(|domain| domain.into_owned())
+                    .unwrap_or_default(),
+                path: cookie.path.to_string(),
+                name: cookie.name().to_string(),
+                value: cookie.value().to_string(),
+                expires: match cookie.expires {
+                    CookieExpiration::AtUtc(at) => DateTime::from_timestamp(at.unix_timestamp(), 0),
+                    CookieExpiration::SessionEnd => None,
+                },
+                secure: cookie.secure().unwrap_or(false),
+                http_only: cookie.http_only().unwrap_or(false),
+            })
+            .collect();
+
+        cookies.sort_by(|a, b| (&a.domain, &a.path, &a.name).cmp(&(&b.domain, &b.path, &b.name)));
+        cookies
+    }
+
+    pub fn remove(&self, domain: &str, path: &str, name: &str) -> bool {
+        self.store
+            .write()
+            .unwrap()
+            .remove(domain, path, name)
+            .is_some()
+    }
+
+    /// Removes every cookie stored for `domain` and returns how many were removed.
+    pub fn remove_domain(&self, domain: &str) -> usize {
+        let cookies: Vec<CookieInfo> = self
+            .entries()
+            .into_iter()
+            .filter(|cookie| cookie.domain == domain)
+            .collect();
+
+        let mut store = self.store.write().unwrap();
+        cookies
+            .iter()
+            .filter(|cookie| {
+                store
+                    .remove(&cookie.domain, &cookie.path, &cookie.name)
+                    .is_some()
+            })
+            .count()
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers.filter_map(|value| {
+            let value = value.to_str().ok()?;
+            RawCookie::parse(value.to_string()).ok()
+        });
+        self.store
+            .write()
+            .unwrap()
+            .store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let value = self
+            .store
+            .read()
+            .unwrap()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if value.is_empty() {
+            return None;
+        }
+
+        HeaderValue::from_str(&value).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore as _;
+
+    fn set(jar: &CookieJar, url: &str, header: &str) {
+        let value = HeaderValue::from_str(header).unwrap();
+        jar.set_cookies(&mut std::iter::once(&value), &Url::parse(url).unwrap());
+    }
+
+    #[test]
+    fn test_cookies_sent_back_to_matching_domain() {
+        let jar = CookieJar::new();
+        set(&jar, "https://example.com/login", "session=abc; Path=/");
+
+        let header = jar.cookies(&Url::parse("https://example.com/users").unwrap());
+        assert_eq!(header.unwrap().to_str().unwrap(), "session=abc");
+        assert!(
+            jar.cookies(&Url::parse("https://other.com/").unwrap())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_remove_domain() {
+        let jar = CookieJar::new();
+        set(&jar, "https://example.com/", "a=1; Path=/");
+        set(&jar, "https://example.com/", "b=2; Path=/");
+        set(&jar, "https://other.com/", "c=3; Path=/");
+
+        assert_eq!(jar.remove_domain("example.com"), 2);
+        let cookies = jar.entries();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].name, "c");
+    }
+
+    #[test]
+    fn test_path_for_http_file() {
+        let path = CookieJar::path_for(Path::new("/tmp/api.http"));
+        assert_eq!(path, PathBuf::from("/tmp/api.cookies.json"));
+    }
+}
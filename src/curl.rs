@@ -0,0 +1,527 @@
+use crate::client::Authorization;
+use crate::error::{ImportError, Result};
+use crate::http::{Method, Request};
+use std::collections::HashMap;
+
+/// Options that take no value and do not change the request.
+const IGNORED_FLAGS: &[&str] = &[
+    "--compressed",
+    "-s",
+    "--silent",
+    "-S",
+    "--show-error",
+    "-k",
+    "--insecure",
+    "-L",
+    "--location",
+    "-i",
+    "--include",
+    "-v",
+    "--verbose",
+    "-f",
+    "--fail",
+    "-N",
+    "--no-buffer",
+    "--http1.1",
+    "--http2",
+    "--globoff",
+];
+
+/// Options whose value is ignored because it only affects curl itself.
+const IGNORED_OPTIONS: &[&str] = &[
+    "-o",
+    "--output",
+    "-m",
+    "--max-time",
+    "--connect-timeout",
+    "--retry",
+    "-w",
+    "--write-out",
+    "-x",
+    "--proxy",
+    "--cacert",
+    "-E",
+    "--cert",
+    "--key",
+    "-c",
+    "--cookie-jar",
+    "--resolve",
+];
+
+/// Renders `request` with `variables` substituted as a `curl` command line. Shorthand
+/// credentials are passed the way curl expects them; `$auth.token` references are left
+/// as they are.
+pub fn to_curl(request: &Request, variables: &HashMap<String, String>) -> Result<String> {
+    let request = request.substituted(variables)?;
+    let mut args = vec!["curl".to_string()];
+
+    match request.method {
+        Method::Get | Method::WebSocket => {}
+        Method::Head => args.push("--head".to_string()),
+        ref method => args.push(format!("-X {}", method)),
+    }
+    args.push(quote(&request.url));
+
+    let mut headers: Vec<_> = request.headers.iter().collect();
+    headers.sort();
+    for (key, value) in headers {
+        if key.eq_ignore_ascii_case("authorization") {
+            match Authorization::parse(value) {
+                Some(Authorization::Digest { username, password }) => {
+                    args.push("--digest".to_string());
+                    args.push(format!(
+                        "--user {}",
+                        quote(&format!("{}:{}", username, password))
+                    ));
+                    continue;
+                }
+                Some(Authorization::Aws(credentials)) => {
+                    let mut provider = "aws:amz".to_string();
+                    if let Some(ref region) = credentials.region {
+                        provider.push_str(&format!(":{}", region));
+                        if let Some(ref service) = credentials.service {
+                            provider.push_str(&format!(":{}", service));
+                        }
+                    }
+                    args.push(format!("--aws-sigv4 {}", quote(&provider)));
+                    args.push(format!(
+                        "--user {}",
+                        quote(&format!(
+                            "{}:{}",
+                            credentials.access_key, credentials.secret_key
+                        ))
+                    ));
+                    if let Some(ref token) = credentials.session_token {
+                        args.push(format!(
+                            "-H {}",
+                            quote(&format!("x-amz-security-token: {}", token))
+                        ));
+                    }
+                    continue;
+                }
+                Some(auth) => {
+                    if let Some(encoded) = auth.encode() {
+                        args.push(format!("-H {}", quote(&format!("{}: {}", key, encoded))));
+                        continue;
+                    }
+                }
+                None => {}
+            }
+        }
+        args.push(format!("-H {}", quote(&format!("{}: {}", key, value))));
+    }
+
+    if let Some(ref body) = request.body {
+        args.push(format!("--data-raw {}", quote(body)));
+    }
+
+    Ok(args.join(" \\\n  "))
+}
+
+/// Parses a `curl` command line, such as one copied from browser devtools, into a
+/// request named after its method and path.
+pub fn parse_curl(command: &str) -> Result<Request> {
+    let words = split_words(command)?;
+    let mut words = words.into_iter();
+    if words.next().as_deref() != Some("curl") {
+        return Err(invalid("not a curl command"));
+    }
+
+    let mut method: Option<Method> = None;
+    let mut url: Option<String> = None;
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut data: Vec<String> = Vec::new();
+    let mut user: Option<String> = None;
+    let mut digest = false;
+    let mut aws_sigv4: Option<String> = None;
+    let mut data_in_query = false;
+
+    while let Some(word) = words.next() {
+        if !word.starts_with('-') || word == "-" {
+            url = Some(word);
+            continue;
+        }
+
+        // Short options may carry their value, as in `-XPOST`.
+        let (option, attached) =
+            if !word.starts_with("--") && word.len() > 2 && word.is_char_boundary(2) {
+                let (option, rest) = word.split_at(2);
+                (option.to_string(), Some(rest.to_string()))
+            } else {
+                (word.clone(), None)
+            };
+        let mut value = |name: &str| {
+            attached
+                .clone()
+                .or_else(|| words.next())
+                .ok_or_else(|| invalid(&format!("{} needs a value", name)))
+        };
+
+        match option.as_str() {
+            "-X" | "--request" => {
+                let name = value(&option)?;
+                method = Some(
+                    name.parse()
+                        .map_err(|_| invalid(&format!("unsupported method {}", name)))?,
+                );
+            }
+            "-H" | "--header" => {
+                let header = value(&option)?;
+                if let Some((key, value)) = header.split_once(':') {
+                    headers.push((key.trim().to_string(), value.trim().to_string()));
+                } else if let Some(key) = header.strip_suffix(';') {
+                    headers.push((key.trim().to_string(), String::new()));
+                }
+            }
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" => {
+                data.push(value(&option)?);
+            }
+            "--data-urlencode" => {
+                let field = value(&option)?;
+                data.push(match field.split_once('=') {
+                    Some((name, content)) => format!("{}={}", name, url_encode(content)),
+                    None => url_encode(&field),
+                });
+            }
+            "--json" => {
+                data.push(value(&option)?);
+                for (key, default) in [
+                    ("Content-Type", "application/json"),
+                    ("Accept", "application/json"),
+                ] {
+                    if !headers
+                        .iter()
+                        .any(|(name, _)| name.eq_ignore_ascii_case(key))
+                    {
+                        headers.push((key.to_string(), default.to_string()));
+                    }
+                }
+            }
+            "-u" | "--user" => user = Some(value(&option)?),
+            "--digest" => digest = true,
+            "--aws-sigv4" => aws_sigv4 = Some(value(&option)?),
+            "-b" | "--cookie" => {
+                let cookie = value(&option)?;
+                // Without `=` the value names a cookie file.
+                if cookie.contains('=') {
+                    headers.push(("Cookie".to_string(), cookie));
+                }
+            }
+            "-A" | "--user-agent" => headers.push(("User-Agent".to_string(), value(&option)?)),
+            "-e" | "--referer" => headers.push(("Referer".to_string(), value(&option)?)),
+            "--url" => url = Some(value(&option)?),
+            "-G" | "--get" => data_in_query = true,
+            "-I" | "--head" => method = Some(Method::Head),
+            "-F" | "--form" => return Err(invalid("multipart forms (-F) are not supported")),
+            _ if IGNORED_FLAGS.contains(&word.as_str()) => {}
+            _ if IGNORED_OPTIONS.contains(&option.as_str()) => {
+                value(&option)?;
+            }
+            _ if is_flag_group(&word) => {}
+            _ => return Err(invalid(&format!("unsupported option {}", word))),
+        }
+    }
+
+    let mut url = url.ok_or_else(|| invalid("no URL"))?;
+    if !url.contains("://") {
+        url = format!("http://{}", url);
+    }
+
+    let mut body = (!data.is_empty()).then(|| data.join("&"));
+    if data_in_query && let Some(query) = body.take() {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&query);
+    }
+
+    let method = method.unwrap_or(if body.is_some() {
+        Method::Post
+    } else {
+        Method::Get
+    });
+    let name = format!("{} {}", method, url_path(&url));
+    let mut request = Request::new(method, url).with_name(name);
+
+    if body.is_some()
+        && !headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+    {
+        headers.push((
+            "Content-Type".to_string(),
+            "application/x-www-form-urlencoded".to_string(),
+        ));
+    }
+    for (key, value) in headers {
+        request.headers.insert(key, value);
+    }
+
+    if let Some(provider) = aws_sigv4 {
+        let (access_key, secret_key) = user
+            .take()
+            .and_then(|user| {
+                user.split_once(':')
+                    .map(|(key, secret)| (key.to_string(), secret.to_string()))
+            })
+            .ok_or_else(|| invalid("--aws-sigv4 needs --user KEY:SECRET"))?;
+        let mut value = format!("AWS {} {}", access_key, secret_key);
+        if let Some(token) = request.headers.remove("x-amz-security-token") {
+            value.push_str(&format!(" token:{}", token));
+        }
+        let mut scope = provider.split(':').skip(2);
+        if let Some(region) = scope.next() {
+            value.push_str(&format!(" region:{}", region));
+        }
+        if let Some(service) = scope.next() {
+            value.push_str(&format!(" service:{}", service));
+        }
+        request.headers.insert("Authorization".to_string(), value);
+    }
+    if let Some(user) = user {
+        // Without a password curl would prompt for one; Basic sends it empty.
+        let (username, password) = user.split_once(':').unwrap_or((&user, ""));
+        let value = if digest {
+            if password.is_empty() {
+                return Err(invalid("--digest needs --user USER:PASSWORD"));
+            }
+            format!("Digest {} {}", username, password)
+        } else {
+            format!("Basic {}:{}", username, password)
+        };
+        request.headers.insert("Authorization".to_string(), value);
+    }
+
+    request.body = body;
+    Ok(request)
+}
+
+fn invalid(message: &str) -> crate::error::Error {
+    ImportError::InvalidCurl(message.to_string()).into()
+}
+
+/// Whether `word` is a group of short flags that take no value, like `-sSL`.
+fn is_flag_group(word: &str) -> bool {
+    word.len() > 2
+        && !word.starts_with("--")
+        && word[1..]
+            .chars()
+            .all(|c| IGNORED_FLAGS.contains(&format!("-{}", c).as_str()))
+}
+
+/// Quotes `text` for a POSIX shell.
+pub(crate) fn quote(text: &str) -> String {
+    if !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c))
+    {
+        return text.to_string();
+    }
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Splits a shell command line into words, handling single, double and `$'...'` quotes,
+/// backslash escapes and line continuations.
+fn split_words(command: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(word) = current.take() {
+                    words.push(word);
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(escaped) => current.get_or_insert_default().push(escaped),
+                None => {}
+            },
+            '\'' => {
+                let word = current.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(invalid("unterminated quote")),
+                    }
+                }
+            }
+            '"' => {
+                let word = current.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(invalid("unterminated quote")),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(invalid("unterminated quote")),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                let word = current.get_or_insert_default();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => word.push(ansi_c_escape(&mut chars)?),
+                        Some(c) => word.push(c),
+                        None => return Err(invalid("unterminated quote")),
+                    }
+                }
+            }
+            c => current.get_or_insert_default().push(c),
+        }
+    }
+    if let Some(word) = current {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Decodes the escape sequence after a backslash inside `$'...'`.
+fn ansi_c_escape(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<char> {
+    let len = match chars.next() {
+        Some('n') => return Ok('\n'),
+        Some('r') => return Ok('\r'),
+        Some('t') => return Ok('\t'),
+        Some('0') => return Ok('\0'),
+        Some('x') => 2,
+        Some('u') => 4,
+        Some('U') => 8,
+        Some(c) => return Ok(c),
+        None => return Err(invalid("unterminated quote")),
+    };
+
+    let mut digits = String::new();
+    while digits.len() < len
+        && let Some(&c) = chars.peek()
+        && c.is_ascii_hexdigit()
+    {
+        digits.push(c);
+        chars.next();
+    }
+    u32::from_str_radix(&digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| invalid("invalid escape sequence"))
+}
+
+pub(crate) fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b' ' => "+".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn url_path(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .map(|parsed| parsed.path().to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_curl() {
+        let request = Request::new(Method::Post, "{{host}}/items")
+            .with_header("Content-Type", "application/json")
+            .with_header("Authorization", "Basic user:{{password}}")
+            .with_body("{\"name\": \"it's\"}");
+        let variables = HashMap::from([
+            ("host".to_string(), "https://api.example.com".to_string()),
+            ("password".to_string(), "secret".to_string()),
+        ]);
+
+        assert_eq!(
+            to_curl(&request, &variables).unwrap(),
+            "curl \\\n  \
+             -X POST \\\n  \
+             https://api.example.com/items \\\n  \
+             -H 'Authorization: Basic dXNlcjpzZWNyZXQ=' \\\n  \
+             -H 'Content-Type: application/json' \\\n  \
+             --data-raw '{\"name\": \"it'\\''s\"}'"
+        );
+    }
+
+    #[test]
+    fn test_parse_devtools_command() {
+        let command = r#"curl 'https://api.example.com/users?page=2' \
+  -H 'accept: application/json' \
+  -H 'content-type: application/json' \
+  -b 'session=abc' \
+  --data-raw $'{"name":"O\'Brien\u00e9"}' \
+  --compressed"#;
+
+        let request = parse_curl(command).unwrap();
+
+        assert_eq!(request.method, Method::Post);
+        assert_eq!(request.name.as_deref(), Some("POST /users"));
+        assert_eq!(request.url, "https://api.example.com/users?page=2");
+        assert_eq!(request.headers["accept"], "application/json");
+        assert_eq!(request.headers["Cookie"], "session=abc");
+        assert_eq!(
+            request.body.as_deref(),
+            Some("{\"name\":\"O'Brien\u{e9}\"}")
+        );
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let request =
+            parse_curl("curl -sSL -XPUT -u alice:pw --digest example.com/a -d x=1 -d y=2").unwrap();
+        assert_eq!(request.method, Method::Put);
+        assert_eq!(request.url, "http://example.com/a");
+        assert_eq!(request.headers["Authorization"], "Digest alice pw");
+        assert_eq!(
+            request.headers["Content-Type"],
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(request.body.as_deref(), Some("x=1&y=2"));
+
+        let request = parse_curl("curl -u alice example.com").unwrap();
+        assert_eq!(request.headers["Authorization"], "Basic alice:");
+        assert!(parse_curl("curl --digest -u alice example.com").is_err());
+
+        let request = parse_curl("curl -G https://x.io/s --data-urlencode 'q=a b'").unwrap();
+        assert_eq!(request.method, Method::Get);
+        assert_eq!(request.url, "https://x.io/s?q=a+b");
+        assert_eq!(request.body, None);
+
+        assert!(parse_curl("curl -F file=@a.png https://x.io").is_err());
+        assert!(parse_curl("wget https://x.io").is_err());
+        assert!(parse_curl("curl 'https://x.io").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let request = Request::new(Method::Delete, "https://x.io/items/1")
+            .with_header("Authorization", "Digest bob p:w")
+            .with_header("X-Note", "it's \"quoted\"");
+
+        let parsed = parse_curl(&to_curl(&request, &HashMap::new()).unwrap()).unwrap();
+
+        assert_eq!(parsed.method, Method::Delete);
+        assert_eq!(parsed.url, request.url);
+        assert_eq!(parsed.headers, request.headers);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::client::Response;
+use serde_json::Value;
+
+/// Longest bodies, in lines, compared line by line; longer ones are compared as a whole.
+const MAX_DIFF_LINES: usize = 2000;
+
+/// A difference found at `path`, a header name or a JSON path like `.items[0].id`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added {
+        path: String,
+        value: String,
+    },
+    Removed {
+        path: String,
+        value: String,
+    },
+    Changed {
+        path: String,
+        old: String,
+        new: String,
+    },
+}
+
+/// One row of a line diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BodyDiff {
+    /// Both bodies are JSON and were compared structurally.
+    Json(Vec<Change>),
+    Text(Vec<DiffLine>),
+    Binary {
+        old_size: u64,
+        new_size: u64,
+        equal: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseDiff {
+    /// Old and new status, if they differ.
+    pub status: Option<(u16, u16)>,
+    pub headers: Vec<Change>,
+    pub body: BodyDiff,
+}
+
+impl ResponseDiff {
+    pub fn new(old: &Response, new: &Response) -> Self {
+        let status = (old.status != new.status).then_some((old.status, new.status));
+        let headers = diff_headers(&old.headers, &new.headers);
+
+        let body = match (old.text.as_deref(), new.text.as_deref()) {
+            (Some(old_text), Some(new_text)) => {
+                match (
+                    serde_json::from_str::<Value>(old_text),
+                    serde_json::from_str::<Value>(new_text),
+                ) {
+                    (Ok(old_json), Ok(new_json)) => BodyDiff::Json(diff_json(&old_json, &new_json)),
+                    _ => BodyDiff::Text(diff_lines(old_text, new_text)),
+                }
+            }
+            _ => BodyDiff::Binary {
+                old_size: old.body_size,
+                new_size: new.body_size,
+                equal: old.body == new.body,
+            },
+        };
+
+        Self {
+            status,
+            headers,
+            body,
+        }
+    }
+}
+
+/// Compares headers by lowercased name; repeated headers are joined with `, `.
+pub fn diff_headers(old: &[(String, String)], new: &[(String, String)]) -> Vec<Change> {
+    let collect = |headers: &[(String, String)]| {
+        let mut map: std::collections::BTreeMap<String, String> = Default::default();
+        for (key, value) in headers {
+            map.entry(key.to_lowercase())
+                .and_modify(|existing| {
+                    existing.push_str(", ");
+                    existing.push_str(value);
+                })
+                .or_insert_with(|| value.clone());
+        }
+        map
+    };
+    let old = collect(old);
+    let new = collect(new);
+
+    let mut changes = Vec::new();
+    for (key, old_value) in &old {
+        match new.get(key) {
+            None => changes.push(Change::Removed {
+                path: key.clone(),
+                value: old_value.clone(),
+            }),
+            Some(new_value) if new_value != old_value => changes.push(Change::Changed {
+                path: key.clone(),
+                old: old_value.clone(),
+                new: new_value.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, new_value) in &new {
+        if !old.contains_key(key) {
+            changes.push(Change::Added {
+                path: key.clone(),
+                value: new_value.clone(),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.path().cmp(b.path()));
+    changes
+}
+
+/// Lists added, removed and changed values by JSON path.
+pub fn diff_json(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    walk_json(".", old, new, &mut changes);
+    changes
+}
+
+fn walk_json(path: &str, old: &Value, new: &Value, changes: &mut Vec<Change>) {
+    let child = |key: &str| {
+        if path == "." {
+            format!(".{}", key)
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+
+    match (old, new) {
+        (Value::Object(old_fields), Value::Object(new_fields)) => {
+            for (key, old_value) in old_fields {
+                match new_fields.get(key) {
+                    Some(new_value) => walk_json(&child(key), old_value, new_value, changes),
+                    None => changes.push(Change::Removed {
+                        path: child(key),
+                        value: old_value.to_string(),
+                    }),
+                }
+            }
+            for (key, new_value) in new_fields {
+                if !old_fields.contains_key(key) {
+                    changes.push(Change::Added {
+                        path: child(key),
+                        value: new_value.to_string(),
+                    });
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            let base = if path == "." { "" } else { path };
+            for (i, old_item) in old_items.iter().enumerate() {
+                let item_path = format!("{}[{}]", base, i);
+                match new_items.get(i) {
+                    Some(new_item) => walk_json(&item_path, old_item, new_item, changes),
+                    None => changes.push(Change::Removed {
+                        path: item_path,
+                        value: old_item.to_string(),
+                    }),
+                }
+            }
+            for (i, new_item) in new_items.iter().enumerate().skip(old_items.len()) {
+                changes.push(Change::Added {
+                    path: format!("{}[{}]", base, i),
+                    value: new_item.to_string(),
+                });
+            }
+        }
+        _ if old != new => changes.push(Change::Changed {
+            path: path.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        }),
+        _ => {}
+    }
+}
+
+/// Line diff based on the longest common subsequence.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    if old.len() > MAX_DIFF_LINES || new.len() > MAX_DIFF_LINES {
+        if old == new {
+            return old
+                .iter()
+                .map(|line| DiffLine::Same(line.to_string()))
+                .collect();
+        }
+        return old
+            .iter()
+            .map(|line| DiffLine::Removed(line.to_string()))
+            .chain(new.iter().map(|line| DiffLine::Added(line.to_string())))
+            .collect();
+    }
+
+    // lengths[i][j] is the LCS length of old[i..] and new[j..].
+    let mut lengths = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(
+        old[i..]
+            .iter()
+            .map(|line| DiffLine::Removed(line.to_string())),
+    );
+    lines.extend(
+        new[j..]
+            .iter()
+            .map(|line| DiffLine::Added(line.to_string())),
+    );
+    lines
+}
+
+impl Change {
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_json_by_path() {
+        let old = json!({"id": 1, "name": "a", "tags": ["x", "y"], "meta": {"v": 1}});
+        let new = json!({"id": 1, "name": "b", "tags": ["x"], "meta": {"v": 1, "w": true}});
+
+        assert_eq!(
+            diff_json(&old, &new),
+            vec![
+                Change::Added {
+                    path: ".meta.w".to_string(),
+                    value: "true".to_string()
+                },
+                Change::Changed {
+                    path: ".name".to_string(),
+                    old: "\"a\"".to_string(),
+                    new: "\"b\"".to_string()
+                },
+                Change::Removed {
+                    path: ".tags[1]".to_string(),
+                    value: "\"y\"".to_string()
+                },
+            ]
+        );
+        assert!(diff_json(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_diff_json_root_array() {
+        assert_eq!(
+            diff_json(&json!([1]), &json!([2, 3])),
+            vec![
+                Change::Changed {
+                    path: "[0]".to_string(),
+                    old: "1".to_string(),
+                    new: "2".to_string()
+                },
+                Change::Added {
+                    path: "[1]".to_string(),
+                    value: "3".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_headers() {
+        let old = vec![
+            ("Content-Type".to_string(), "text/plain".to_string()),
+            ("X-Old".to_string(), "1".to_string()),
+        ];
+        let new = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("x-new".to_string(), "2".to_string()),
+        ];
+
+        let changes = diff_headers(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(changes[0], Change::Changed { ref path, .. } if path == "content-type"));
+        assert!(matches!(changes[1], Change::Added { ref path, .. } if path == "x-new"));
+        assert!(matches!(changes[2], Change::Removed { ref path, .. } if path == "x-old"));
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(
+            diff_lines("a\nb\nc", "a\nc\nd"),
+            vec![
+                DiffLine::Same("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Same("c".to_string()),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_response_diff() {
+        let mut old = Response::error("plain text");
+        old.status = 200;
+        let mut new = Response::error("{\"a\": 1}");
+        new.status = 500;
+
+        let diff = ResponseDiff::new(&old, &new);
+        assert_eq!(diff.status, Some((200, 500)));
+        assert!(matches!(diff.body, BodyDiff::Text(_)));
+
+        let diff = ResponseDiff::new(&new, &new);
+        assert_eq!(diff.status, None);
+        assert_eq!(diff.body, BodyDiff::Json(Vec::new()));
+    }
+}
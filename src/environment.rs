@@ -0,0 +1,392 @@
+use crate::error::{EnvironmentError, Result};
+use crate::oauth::OAuth2Config;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const ENV_FILE: &str = "http-client.env.json";
+pub const PRIVATE_ENV_FILE: &str = "http-client.private.env.json";
+
+/// Section whose values apply to every environment.
+pub const SHARED_ENV: &str = "$shared";
+const SSL_CONFIGURATION: &str = "SSLConfiguration";
+const SECURITY: &str = "Security";
+
+/// A named environment loaded from `http-client.env.json` (and its private counterpart).
+///
+/// ```json
+/// {
+///   "$shared": { "contentType": "application/json" },
+///   "dev": {
+///     "baseUrl": "https://dev.internal",
+///     "SSLConfiguration": {
+///       "caCertificates": ["certs/ca.pem"],
+///       "clientCertificate": "certs/client.pem",
+///       "clientCertificateKey": "certs/client.key",
+///       "hosts": {
+///         "billing.internal:8443": { "pkcs12": "certs/billing.p12", "passphrase": "secret" }
+///       }
+///     },
+///     "Security": {
+///       "Auth": {
+///         "my-auth": { "Type": "OAuth2", "Grant Type": "Client Credentials", "...": "..." }
+///       }
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    pub name: String,
+    pub variables: HashMap<String, String>,
+    pub ssl: SslConfiguration,
+    /// OAuth2 configurations referenced as `{{$auth.token("name")}}`.
+    pub auth: HashMap<String, OAuth2Config>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Security {
+    #[serde(rename = "Auth", default)]
+    auth: HashMap<String, OAuth2Config>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SslConfiguration {
+    /// Extra PEM bundles trusted in addition to the system roots.
+    #[serde(default)]
+    pub ca_certificates: Vec<PathBuf>,
+    /// Client certificate used for hosts without an entry in `hosts`.
+    #[serde(flatten)]
+    pub client_certificate: ClientCertificate,
+    /// Client certificates keyed by `host` or `host:port`.
+    #[serde(default)]
+    pub hosts: HashMap<String, ClientCertificate>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientCertificate {
+    /// PEM certificate chain. May also contain the private key.
+    pub client_certificate: Option<PathBuf>,
+    /// PEM (PKCS#8) private key for `client_certificate`.
+    pub client_certificate_key: Option<PathBuf>,
+    /// PKCS#12 archive holding both certificate and key.
+    pub pkcs12: Option<PathBuf>,
+    pub passphrase: Option<String>,
+}
+
+impl ClientCertificate {
+    fn resolve_paths(&mut self, dir: &Path) {
+        for path in [
+            &mut self.client_certificate,
+            &mut self.client_certificate_key,
+            &mut self.pkcs12,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *path = dir.join(&*path);
+        }
+    }
+}
+
+impl SslConfiguration {
+    fn resolve_paths(&mut self, dir: &Path) {
+        for path in &mut self.ca_certificates {
+            *path = dir.join(&*path);
+        }
+        self.client_certificate.resolve_paths(dir);
+        for certificate in self.hosts.values_mut() {
+            certificate.resolve_paths(dir);
+        }
+    }
+}
+
+impl Environment {
+    /// Loads environment `name` from the env files in `dir`.
+    ///
+    /// Values from `$shared` apply to every environment, and the private file
+    /// overrides the public one. Relative certificate paths are resolved against `dir`.
+    pub fn load(dir: &Path, name: &str) -> Result<Self> {
+        let files: Vec<Map<String, Value>> = [ENV_FILE, PRIVATE_ENV_FILE]
+            .iter()
+            .map(|file| dir.join(file))
+            .filter(|path| path.exists())
+            .map(|path| Self::read_file(&path))
+            .collect::<Result<_>>()?;
+
+        if files.is_empty() {
+            return Err(EnvironmentError::FileNotFound(dir.join(ENV_FILE)).into());
+        }
+
+        if !files.iter().any(|file| file.contains_key(name)) {
+            let mut available: Vec<&str> = files
+                .iter()
+                .flat_map(|file| file.keys())
+                .map(String::as_str)
+                .filter(|env| *env != SHARED_ENV)
+                .collect();
+            available.sort_unstable();
+            available.dedup();
+            return Err(EnvironmentError::UnknownEnvironment(
+                name.to_string(),
+                available.join(", "),
+            )
+            .into());
+        }
+
+        let mut environment = Environment {
+            name: name.to_string(),
+            ..Default::default()
+        };
+
+        for section in [SHARED_ENV, name] {
+            for file in &files {
+                if let Some(values) = file.get(section) {
+                    environment.merge(section, values)?;
+                }
+            }
+        }
+
+        environment.ssl.resolve_paths(dir);
+        Ok(environment)
+    }
+
+    /// Adds `variables` to environment `name` in the env file at `path`, creating the
+    /// file or the environment as needed. Variables already set to a different value
+    /// are an error rather than overwritten.
+    pub fn add_variables(path: &Path, name: &str, variables: &[(String, String)]) -> Result<()> {
+        let mut file = if path.exists() {
+            Self::read_file(path)?
+        } else {
+            Map::new()
+        };
+
+        let invalid =
+            |message: String| EnvironmentError::Invalid(path.display().to_string(), message);
+        let Value::Object(section) = file
+            .entry(name)
+            .or_insert_with(|| Value::Object(Map::new()))
+        else {
+            return Err(invalid(format!("{} is not an object", name)).into());
+        };
+        for (key, value) in variables {
+            match section.get(key) {
+                None => {
+                    section.insert(key.clone(), Value::String(value.clone()));
+                }
+                Some(Value::String(existing)) if existing == value => {}
+                Some(_) => {
+                    return Err(invalid(format!("{} already defines {}", name, key)).into());
+                }
+            }
+        }
+
+        let json = serde_json::to_string_pretty(&file).expect("environment serializes to JSON");
+        fs::write(path, json + "\n")?;
+        Ok(())
+    }
+
+    fn read_file(path: &Path) -> Result<Map<String, Value>> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|err| {
+            EnvironmentError::Invalid(path.display().to_string(), err.to_string()).into()
+        })
+    }
+
+    fn merge(&mut self, section: &str, values: &Value) -> Result<()> {
+        let Value::Object(values) = values else {
+            return Err(EnvironmentError::Invalid(
+                section.to_string(),
+                "expected an object".to_string(),
+            )
+            .into());
+        };
+
+        for (key, value) in values {
+            if key == SSL_CONFIGURATION {
+                self.ssl = serde_json::from_value(value.clone()).map_err(|err| {
+                    EnvironmentError::Invalid(format!("{}.{}", section, key), err.to_string())
+                })?;
+                continue;
+            }
+
+            if key == SECURITY {
+                let security: Security = serde_json::from_value(value.clone()).map_err(|err| {
+                    EnvironmentError::Invalid(format!("{}.{}", section, key), err.to_string())
+                })?;
+                self.auth.extend(security.auth);
+                continue;
+            }
+
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            self.variables.insert(key.clone(), value);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_env(dir: &Path, file: &str, content: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(file), content).unwrap();
+    }
+
+    #[test]
+    fn test_load_merges_shared_and_private_values() {
+        let dir = std::env::temp_dir().join(format!("poke-env-merge-{}", std::process::id()));
+        write_env(
+            &dir,
+            ENV_FILE,
+            r#"{
+                "$shared": { "contentType": "application/json", "retries": 3 },
+                "dev": { "baseUrl": "https://dev.internal", "token": "public" }
+            }"#,
+        );
+        write_env(
+            &dir,
+            PRIVATE_ENV_FILE,
+            r#"{ "dev": { "token": "private" } }"#,
+        );
+
+        let env = Environment::load(&dir, "dev").unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(env.variables["baseUrl"], "https://dev.internal");
+        assert_eq!(env.variables["contentType"], "application/json");
+        assert_eq!(env.variables["retries"], "3");
+        assert_eq!(env.variables["token"], "private");
+    }
+
+    #[test]
+    fn test_load_ssl_configuration() {
+        let dir = std::env::temp_dir().join(format!("poke-env-ssl-{}", std::process::id()));
+        write_env(
+            &dir,
+            ENV_FILE,
+            r#"{
+                "dev": {
+                    "SSLConfiguration": {
+                        "caCertificates": ["ca.pem"],
+                        "clientCertificate": "client.pem",
+                        "clientCertificateKey": "client.key",
+                        "hosts": { "billing.internal:8443": { "pkcs12": "billing.p12", "passphrase": "secret" } }
+                    }
+                }
+            }"#,
+        );
+
+        let env = Environment::load(&dir, "dev").unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(env.variables.is_empty());
+        assert_eq!(env.ssl.ca_certificates, vec![dir.join("ca.pem")]);
+        assert_eq!(
+            env.ssl.client_certificate.client_certificate,
+            Some(dir.join("client.pem"))
+        );
+        let billing = &env.ssl.hosts["billing.internal:8443"];
+        assert_eq!(billing.pkcs12, Some(dir.join("billing.p12")));
+        assert_eq!(billing.passphrase.as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn test_load_auth_configuration() {
+        let dir = std::env::temp_dir().join(format!("poke-env-auth-{}", std::process::id()));
+        write_env(
+            &dir,
+            ENV_FILE,
+            r#"{
+                "dev": {
+                    "Security": {
+                        "Auth": {
+                            "my-auth": {
+                                "Type": "OAuth2",
+                                "Grant Type": "Client Credentials",
+                                "Token URL": "https://auth.internal/token",
+                                "Client ID": "poke",
+                                "Client Secret": "{{secret}}"
+                            }
+                        }
+                    }
+                }
+            }"#,
+        );
+        write_env(
+            &dir,
+            PRIVATE_ENV_FILE,
+            r#"{ "dev": { "secret": "s3cret" } }"#,
+        );
+
+        let env = Environment::load(&dir, "dev").unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        let auth = &env.auth["my-auth"];
+        assert_eq!(auth.token_url, "https://auth.internal/token");
+        assert_eq!(auth.client_secret.as_deref(), Some("{{secret}}"));
+        assert_eq!(env.variables.len(), 1);
+    }
+
+    #[test]
+    fn test_load_unknown_environment() {
+        let dir = std::env::temp_dir().join(format!("poke-env-unknown-{}", std::process::id()));
+        write_env(&dir, ENV_FILE, r#"{ "dev": {}, "prod": {} }"#);
+
+        let err = Environment::load(&dir, "staging").unwrap_err();
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(err.to_string().contains("staging"));
+        assert!(err.to_string().contains("dev, prod"));
+    }
+
+    #[test]
+    fn test_add_variables() {
+        let dir = std::env::temp_dir().join(format!("poke-env-add-{}", std::process::id()));
+        write_env(
+            &dir,
+            ENV_FILE,
+            r#"{ "dev": { "baseUrl": "https://dev.internal" } }"#,
+        );
+        let path = dir.join(ENV_FILE);
+
+        let vars = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        Environment::add_variables(
+            &path,
+            "dev",
+            &vars(&[("baseUrl", "https://dev.internal"), ("user", "ann")]),
+        )
+        .unwrap();
+        Environment::add_variables(&path, "prod", &vars(&[("user", "bob")])).unwrap();
+        let conflict = Environment::add_variables(&path, "dev", &vars(&[("user", "bob")]));
+
+        let dev = Environment::load(&dir, "dev").unwrap();
+        let prod = Environment::load(&dir, "prod").unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(dev.variables.len(), 2);
+        assert_eq!(dev.variables["user"], "ann");
+        assert_eq!(prod.variables["user"], "bob");
+        assert!(
+            conflict
+                .unwrap_err()
+                .to_string()
+                .contains("dev already defines user")
+        );
+    }
+}
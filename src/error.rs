@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -15,6 +16,27 @@ pub enum Error {
 
     #[error("Variable error: {0}")]
     Variable(#[from] VariableError),
+
+    #[error("Cookie error: {0}")]
+    Cookie(#[from] CookieError),
+
+    #[error("Environment error: {0}")]
+    Environment(#[from] EnvironmentError),
+
+    #[error("gRPC error: {0}")]
+    Grpc(#[from] GrpcError),
+
+    #[error("Auth error: {0}")]
+    Auth(#[from] AuthError),
+
+    #[error("History error: {0}")]
+    History(#[from] HistoryError),
+
+    #[error("Import error: {0}")]
+    Import(#[from] ImportError),
+
+    #[error("Code generation error: {0}")]
+    Codegen(#[from] CodegenError),
 }
 
 #[derive(Error, Debug)]
@@ -27,6 +49,9 @@ pub enum ParseError {
 
     #[error("IO error: {0}")]
     IoError(String),
+
+    #[error("{} changed on disk since it was read", .0.display())]
+    ChangedOnDisk(PathBuf),
 }
 
 #[derive(Error, Debug)]
@@ -39,6 +64,18 @@ pub enum HttpError {
 
     #[error("Timeout")]
     Timeout,
+
+    #[error("Cancelled")]
+    Cancelled,
+
+    #[error("TLS handshake failed: {0}")]
+    TlsHandshake(String),
+
+    #[error("Invalid certificate: {0}")]
+    InvalidCertificate(String),
+
+    #[error("WebSocket error: {0}")]
+    WebSocket(String),
 }
 
 #[derive(Error, Debug)]
@@ -47,12 +84,121 @@ pub enum VariableError {
     UndefinedVariable(String),
 }
 
+#[derive(Error, Debug)]
+pub enum CookieError {
+    #[error("Invalid cookie store: {0}")]
+    InvalidStore(String),
+}
+
+#[derive(Error, Debug)]
+pub enum EnvironmentError {
+    #[error("Environment file not found: {0}")]
+    FileNotFound(PathBuf),
+
+    #[error("Unknown environment '{0}' (available: {1})")]
+    UnknownEnvironment(String, String),
+
+    #[error("Invalid environment file {0}: {1}")]
+    Invalid(String, String),
+}
+
 impl From<reqwest::Error> for HttpError {
     fn from(err: reqwest::Error) -> Self {
         if err.is_timeout() {
-            HttpError::Timeout
-        } else {
-            HttpError::RequestFailed(err.to_string())
+            return HttpError::Timeout;
+        }
+
+        let mut source = std::error::Error::source(&err);
+        while let Some(cause) = source {
+            if let Some(tls_err) = cause.downcast_ref::<native_tls::Error>() {
+                return HttpError::TlsHandshake(tls_err.to_string());
+            }
+            // rustls errors arrive wrapped in io::Errors, whose `source` skips them.
+            let mut inner: &(dyn std::error::Error + 'static) = cause;
+            while let Some(err) = inner
+                .downcast_ref::<std::io::Error>()
+                .and_then(|err| err.get_ref())
+            {
+                inner = err;
+            }
+            if let Some(tls_err) = inner.downcast_ref::<rustls::Error>() {
+                return HttpError::TlsHandshake(tls_err.to_string());
+            }
+            source = cause.source();
+        }
+
+        HttpError::RequestFailed(err.to_string())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum GrpcError {
+    #[error("Invalid gRPC target {0}, expected host:port/package.Service/Method")]
+    InvalidTarget(String),
+
+    #[error("Connection failed: {0}")]
+    Transport(String),
+
+    #[error("Failed to compile .proto files: {0}")]
+    Proto(String),
+
+    #[error("Server reflection failed: {0}")]
+    Reflection(String),
+
+    #[error("Method not found: {0}")]
+    MethodNotFound(String),
+
+    #[error("Request body does not match the input message: {0}")]
+    InvalidMessage(String),
+}
+
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error("Unknown auth configuration '{0}'")]
+    UnknownConfig(String),
+
+    #[error("Invalid auth configuration '{0}': {1}")]
+    InvalidConfig(String, String),
+
+    #[error("Token request failed: {0}")]
+    TokenRequestFailed(String),
+}
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("Failed to serialize entry: {0}")]
+    Serialize(String),
+
+    #[error("No history entry {0} (history has {1} entries)")]
+    EntryNotFound(usize, usize),
+}
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("Invalid {0} file: {1}")]
+    InvalidFile(&'static str, String),
+
+    #[error("Invalid curl command: {0}")]
+    InvalidCurl(String),
+
+    #[error("{0} already exists")]
+    FileExists(PathBuf),
+}
+
+#[derive(Error, Debug)]
+pub enum CodegenError {
+    #[error("{0} requests cannot be converted to code")]
+    UnsupportedMethod(String),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for HttpError {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        use tokio_tungstenite::tungstenite::Error as WsError;
+
+        match err {
+            WsError::Tls(err) => HttpError::TlsHandshake(err.to_string()),
+            WsError::Url(err) => HttpError::InvalidUrl(err.to_string()),
+            err => HttpError::WebSocket(err.to_string()),
         }
     }
 }
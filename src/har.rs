@@ -0,0 +1,545 @@
+use crate::error::{ImportError, Result};
+use crate::history::HistoryEntry;
+use crate::http::Request;
+use crate::import::{Collection, Item};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Request headers that browsers record but the client sets on its own.
+const SKIPPED_IMPORT_HEADERS: &[&str] =
+    &["host", "content-length", "connection", "accept-encoding"];
+
+/// An HTTP Archive, as defined by the HAR 1.2 specification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Har {
+    pub log: Log,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Log {
+    pub version: String,
+    pub creator: Creator,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Creator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    pub started_date_time: String,
+    /// Total time in milliseconds.
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    #[serde(default)]
+    pub cache: serde_json::Value,
+    pub timings: HarTimings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<Cookie>,
+    #[serde(default)]
+    pub headers: Vec<NameValue>,
+    #[serde(default)]
+    pub query_string: Vec<NameValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<PostData>,
+    #[serde(default = "unknown_size")]
+    pub headers_size: i64,
+    #[serde(default = "unknown_size")]
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub status_text: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<Cookie>,
+    #[serde(default)]
+    pub headers: Vec<NameValue>,
+    pub content: Content,
+    #[serde(default, rename = "redirectURL")]
+    pub redirect_url: String,
+    #[serde(default = "unknown_size")]
+    pub headers_size: i64,
+    #[serde(default = "unknown_size")]
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NameValue {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_only: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secure: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostData {
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<NameValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Content {
+    pub size: i64,
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+/// Phase durations in milliseconds; `-1` marks a phase that does not apply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarTimings {
+    #[serde(default = "not_applicable")]
+    pub blocked: f64,
+    #[serde(default = "not_applicable")]
+    pub dns: f64,
+    #[serde(default = "not_applicable")]
+    pub connect: f64,
+    #[serde(default)]
+    pub send: f64,
+    #[serde(default)]
+    pub wait: f64,
+    #[serde(default)]
+    pub receive: f64,
+    #[serde(default = "not_applicable")]
+    pub ssl: f64,
+}
+
+fn unknown_size() -> i64 {
+    -1
+}
+
+fn not_applicable() -> f64 {
+    -1.0
+}
+
+impl Har {
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a HistoryEntry>) -> Self {
+        Self {
+            log: Log {
+                version: "1.2".to_string(),
+                creator: Creator {
+                    name: env!("CARGO_PKG_NAME").to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+                entries: entries.into_iter().map(Entry::from).collect(),
+            },
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        serde_json::from_str(content)
+            .map_err(|err| ImportError::InvalidFile("HAR", err.to_string()).into())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("HAR serializes to JSON")
+    }
+
+    /// Converts the recorded requests, one per entry, named after method and path.
+    /// Entries with a method that cannot be sent are left out.
+    pub fn requests(&self) -> Vec<Request> {
+        self.log
+            .entries
+            .iter()
+            .filter_map(|entry| entry.request.to_request())
+            .collect()
+    }
+
+    /// Converts the recorded requests into a collection called `name`.
+    pub fn to_collection(&self, name: &str) -> Collection {
+        let mut collection = Collection::new(name);
+        for entry in &self.log.entries {
+            match entry.request.to_request() {
+                Some(request) => collection.items.push(Item::Request(Box::new(request))),
+                None => collection
+                    .warnings
+                    .push(format!("{}: unsupported method", entry.request.name())),
+            }
+        }
+        collection
+    }
+}
+
+impl HarRequest {
+    fn name(&self) -> String {
+        format!("{} {}", self.method, url_path(&self.url))
+    }
+
+    /// The recorded request, or `None` if its method is not supported.
+    fn to_request(&self) -> Option<Request> {
+        let method = self.method.parse().ok()?;
+        let mut request = Request::new(method, self.url.clone()).with_name(self.name());
+
+        for header in &self.headers {
+            let name = header.name.to_lowercase();
+            if name.starts_with(':') || SKIPPED_IMPORT_HEADERS.contains(&name.as_str()) {
+                continue;
+            }
+            request
+                .headers
+                .insert(header.name.clone(), header.value.clone());
+        }
+
+        if let Some(ref post_data) = self.post_data {
+            let body = match post_data.text {
+                Some(ref text) => text.clone(),
+                None => post_data
+                    .params
+                    .iter()
+                    .map(|param| format!("{}={}", param.name, param.value))
+                    .collect::<Vec<_>>()
+                    .join("&"),
+            };
+            let has_content_type = request
+                .headers
+                .keys()
+                .any(|key| key.eq_ignore_ascii_case("content-type"));
+            if !body.is_empty() {
+                if !has_content_type && !post_data.mime_type.is_empty() {
+                    request
+                        .headers
+                        .insert("Content-Type".to_string(), post_data.mime_type.clone());
+                }
+                request.body = Some(body);
+            }
+        }
+        Some(request)
+    }
+}
+
+impl From<&HistoryEntry> for Entry {
+    fn from(entry: &HistoryEntry) -> Self {
+        let sent = &entry.sent;
+        let response = &entry.response;
+        let timings = &response.timings;
+
+        let request_headers = name_values(&sent.headers);
+        let query_string = reqwest::Url::parse(&sent.url)
+            .map(|url| {
+                url.query_pairs()
+                    .map(|(name, value)| NameValue {
+                        name: name.into_owned(),
+                        value: value.into_owned(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let post_data = sent.body.as_ref().map(|body| PostData {
+            mime_type: sent.header("content-type").unwrap_or_default().to_string(),
+            text: Some(body.clone()),
+            params: Vec::new(),
+        });
+
+        let (text, encoding) = match response.text {
+            Some(ref text) => (Some(text.clone()), None),
+            None if response.body.is_empty() => (None, None),
+            None => (
+                Some(BASE64.encode(&response.body)),
+                Some("base64".to_string()),
+            ),
+        };
+
+        let timings = HarTimings {
+            blocked: -1.0,
+            dns: timings.dns.map_or(-1.0, millis),
+            // HAR counts the TLS handshake as part of `connect` as well as in `ssl`.
+            connect: timings.connect.map_or(-1.0, |connect| {
+                millis(connect + timings.tls_handshake.unwrap_or_default())
+            }),
+            send: 0.0,
+            wait: millis(timings.ttfb),
+            receive: millis(timings.download),
+            ssl: timings.tls_handshake.map_or(-1.0, millis),
+        };
+        let time = [timings.dns, timings.connect, timings.wait, timings.receive]
+            .iter()
+            .filter(|phase| **phase > 0.0)
+            .sum();
+        let started: DateTime<Utc> = entry.timestamp.into();
+
+        Self {
+            started_date_time: started.to_rfc3339(),
+            time,
+            request: HarRequest {
+                method: sent.method.to_string(),
+                url: sent.url.clone(),
+                http_version: "HTTP/1.1".to_string(),
+                cookies: sent
+                    .header("cookie")
+                    .map(request_cookies)
+                    .unwrap_or_default(),
+                headers: request_headers,
+                query_string,
+                post_data,
+                headers_size: -1,
+                body_size: sent.body.as_ref().map_or(0, |body| body.len() as i64),
+            },
+            response: HarResponse {
+                status: response.status,
+                status_text: response.status_text.clone(),
+                http_version: "HTTP/1.1".to_string(),
+                cookies: response
+                    .headers
+                    .iter()
+                    .filter(|(key, _)| key.eq_ignore_ascii_case("set-cookie"))
+                    .filter_map(|(_, value)| response_cookie(value))
+                    .collect(),
+                headers: name_values(&response.headers),
+                content: Content {
+                    size: response.body_size as i64,
+                    mime_type: response.content_type().unwrap_or_default().to_string(),
+                    text,
+                    encoding,
+                },
+                redirect_url: response.header("location").unwrap_or_default().to_string(),
+                headers_size: -1,
+                body_size: response.body_size as i64,
+            },
+            cache: serde_json::json!({}),
+            timings,
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn name_values(headers: &[(String, String)]) -> Vec<NameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| NameValue {
+            name: name.clone(),
+            value: value.clone(),
+        })
+        .collect()
+}
+
+/// Splits a `Cookie` request header into its cookies.
+fn request_cookies(header: &str) -> Vec<Cookie> {
+    header
+        .split(';')
+        .filter_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            Some(Cookie {
+                name: name.to_string(),
+                value: value.to_string(),
+                path: None,
+                domain: None,
+                expires: None,
+                http_only: None,
+                secure: None,
+            })
+        })
+        .collect()
+}
+
+/// Parses a `Set-Cookie` response header.
+fn response_cookie(header: &str) -> Option<Cookie> {
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.trim().split_once('=')?;
+    let mut cookie = Cookie {
+        name: name.to_string(),
+        value: value.to_string(),
+        path: None,
+        domain: None,
+        expires: None,
+        http_only: Some(false),
+        secure: Some(false),
+    };
+
+    for attribute in parts {
+        let (key, value) = attribute
+            .trim()
+            .split_once('=')
+            .unwrap_or((attribute.trim(), ""));
+        match key.to_lowercase().as_str() {
+            "path" => cookie.path = Some(value.to_string()),
+            "domain" => cookie.domain = Some(value.to_string()),
+            "expires" => {
+                cookie.expires = DateTime::parse_from_rfc2822(value)
+                    .map(|time| time.with_timezone(&Utc).to_rfc3339())
+                    .ok()
+                    .or_else(|| Some(value.to_string()));
+            }
+            "httponly" => cookie.http_only = Some(true),
+            "secure" => cookie.secure = Some(true),
+            _ => {}
+        }
+    }
+    Some(cookie)
+}
+
+/// The path of `url` for request names, e.g. `/users/1`.
+fn url_path(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .map(|parsed| parsed.path().to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Response, SentRequest, Timings};
+    use crate::http::{Method, Parser};
+    use std::path::Path;
+    use std::time::SystemTime;
+
+    fn entry() -> HistoryEntry {
+        let mut response = Response::error("{\"id\": 1}");
+        response.status = 201;
+        response.status_text = "Created".to_string();
+        response.headers = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            (
+                "set-cookie".to_string(),
+                "session=abc; Path=/; HttpOnly".to_string(),
+            ),
+        ];
+        response.timings = Timings {
+            dns: Some(Duration::from_millis(2)),
+            connect: Some(Duration::from_millis(10)),
+            ttfb: Duration::from_millis(30),
+            download: Duration::from_millis(5),
+            ..Timings::default()
+        };
+
+        HistoryEntry {
+            request: Request::new(Method::Post, "{{host}}/items"),
+            sent: SentRequest {
+                method: Method::Post,
+                url: "http://localhost/items?page=2".to_string(),
+                headers: vec![
+                    ("host".to_string(), "localhost".to_string()),
+                    ("content-type".to_string(), "application/json".to_string()),
+                    ("cookie".to_string(), "a=1; b=2".to_string()),
+                ],
+                body: Some("{\"name\": \"Widget\"}".to_string()),
+            },
+            response,
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(1714557600),
+        }
+    }
+
+    #[test]
+    fn test_export_entry() {
+        let har = Har::from_entries([&entry()]);
+        let json: serde_json::Value = serde_json::from_str(&har.to_json()).unwrap();
+        let entry = &json["log"]["entries"][0];
+
+        assert_eq!(json["log"]["version"], "1.2");
+        assert_eq!(entry["startedDateTime"], "2024-05-01T10:00:00+00:00");
+        assert_eq!(entry["time"], 47.0);
+        assert_eq!(entry["timings"]["wait"], 30.0);
+        assert_eq!(entry["timings"]["ssl"], -1.0);
+        assert_eq!(entry["request"]["queryString"][0]["name"], "page");
+        assert_eq!(entry["request"]["cookies"][1]["name"], "b");
+        assert_eq!(
+            entry["request"]["postData"]["text"],
+            "{\"name\": \"Widget\"}"
+        );
+        assert_eq!(entry["response"]["cookies"][0]["httpOnly"], true);
+        assert_eq!(entry["response"]["cookies"][0]["path"], "/");
+        assert_eq!(entry["response"]["content"]["mimeType"], "application/json");
+        assert_eq!(entry["response"]["content"]["text"], "{\"id\": 1}");
+    }
+
+    #[test]
+    fn test_import_as_http_file() {
+        let har = Har::parse(
+            r#"{"log": {"version": "1.2", "creator": {"name": "WebInspector", "version": "537.36"},
+            "entries": [
+                {"startedDateTime": "2024-05-01T10:00:00.000Z", "time": 12.5,
+                 "request": {"method": "GET", "url": "https://api.example.com/users?page=1",
+                    "httpVersion": "http/2.0",
+                    "headers": [{"name": ":authority", "value": "api.example.com"},
+                                {"name": "accept", "value": "application/json"},
+                                {"name": "accept-encoding", "value": "gzip"}]},
+                 "response": {"status": 200, "statusText": "", "content": {"size": 0, "mimeType": ""}},
+                 "timings": {"send": 0, "wait": 10, "receive": 2.5}},
+                {"startedDateTime": "2024-05-01T10:00:01.000Z", "time": 8,
+                 "request": {"method": "POST", "url": "https://api.example.com/login",
+                    "headers": [],
+                    "postData": {"mimeType": "application/x-www-form-urlencoded",
+                                 "params": [{"name": "user", "value": "alice"}]}},
+                 "response": {"status": 302, "content": {"size": 0}},
+                 "timings": {"wait": 8}},
+                {"startedDateTime": "2024-05-01T10:00:02.000Z", "time": 3,
+                 "request": {"method": "TRACE", "url": "https://api.example.com/debug",
+                    "headers": []},
+                 "response": {"status": 405, "content": {"size": 0}},
+                 "timings": {"wait": 3}}
+            ]}}"#,
+        )
+        .unwrap();
+
+        let collection = har.to_collection("session");
+        assert_eq!(collection.warnings, ["TRACE /debug: unsupported method"]);
+        let http = collection.http_file(Path::new("session.http")).to_http();
+        let (requests, _) = Parser::parse_content(&http).unwrap();
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].name.as_deref(), Some("GET /users"));
+        assert_eq!(requests[0].url, "https://api.example.com/users?page=1");
+        assert_eq!(requests[0].headers.len(), 1);
+        assert_eq!(requests[0].headers["accept"], "application/json");
+        assert_eq!(requests[1].method, Method::Post);
+        assert_eq!(
+            requests[1].headers["Content-Type"],
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(requests[1].body.as_deref(), Some("user=alice"));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Har::parse("{\"log\": {}}").is_err());
+    }
+}
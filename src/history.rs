@@ -0,0 +1,737 @@
+use crate::client::{Response, SentRequest, Timings, decode_text};
+use crate::error::{HistoryError, Result};
+use crate::http::Request;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Number of entries kept in a history file unless configured otherwise.
+pub const DEFAULT_HISTORY_LIMIT: usize = 500;
+
+/// Headers whose values are not written to history files.
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-auth-token",
+];
+
+/// Query parameters whose values are not written to history files.
+const SENSITIVE_QUERY_PARAMETERS: &[&str] = &[
+    "access_token",
+    "api-key",
+    "api_key",
+    "apikey",
+    "client_secret",
+    "key",
+    "password",
+    "refresh_token",
+    "token",
+];
+
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    /// The request as written in the `.http` file.
+    pub request: Request,
+    /// The request as sent, including headers added by the client.
+    pub sent: SentRequest,
+    pub response: Response,
+    pub timestamp: SystemTime,
+}
+
+impl HistoryEntry {
+    /// A one-line summary: local time, status, method, duration and URL as sent.
+    pub fn summary(&self) -> String {
+        let timestamp: DateTime<Local> = self.timestamp.into();
+        format!(
+            "{}  {:3}  {:9} {:>7}  {}",
+            timestamp.format("%Y-%m-%d %H:%M:%S"),
+            self.response.status,
+            self.sent.method.to_string(),
+            format!("{}ms", self.response.duration.as_millis()),
+            self.sent.url
+        )
+    }
+
+    /// The request as sent and the response, in a `curl -v` like layout.
+    pub fn details(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("> {} {}\n", self.sent.method, self.sent.url));
+        for (key, value) in &self.sent.headers {
+            out.push_str(&format!("> {}: {}\n", key, value));
+        }
+        if let Some(ref body) = self.sent.body {
+            out.push_str(&format!(">\n{}\n", body));
+        }
+
+        out.push_str(&format!(
+            "\n< {} {} ({}ms)\n",
+            self.response.status,
+            self.response.status_text,
+            self.response.duration.as_millis()
+        ));
+        for (key, value) in &self.response.headers {
+            out.push_str(&format!("< {}: {}\n", key, value));
+        }
+        match self.response.text {
+            Some(ref text) => out.push_str(&format!("\n{}\n", text)),
+            None => out.push_str(&format!(
+                "\n[binary body, {} bytes]\n",
+                self.response.body_size
+            )),
+        }
+        out
+    }
+}
+
+/// One line of a history file.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    timestamp: DateTime<Utc>,
+    request: Request,
+    sent: SentRequest,
+    response: StoredResponse,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredResponse {
+    status: u16,
+    status_text: String,
+    headers: Vec<(String, String)>,
+    /// Decoded body text, or base64 for binary bodies.
+    body: String,
+    #[serde(default)]
+    binary: bool,
+    body_size: u64,
+    duration: Duration,
+    timings: Timings,
+}
+
+impl From<&HistoryEntry> for Record {
+    fn from(entry: &HistoryEntry) -> Self {
+        let response = &entry.response;
+        let (body, binary) = match response.text {
+            Some(ref text) => (text.clone(), false),
+            None => (BASE64.encode(&response.body), true),
+        };
+
+        let mut request = entry.request.clone();
+        request.url = redact_query(&request.url, true);
+        for (key, value) in request.headers.iter_mut() {
+            if is_sensitive_header(key) && !value.contains("{{") {
+                *value = REDACTED.to_string();
+            }
+        }
+        let mut sent = entry.sent.clone();
+        sent.url = redact_query(&sent.url, false);
+        sent.headers = redact(&sent.headers);
+        Self {
+            timestamp: entry.timestamp.into(),
+            request,
+            sent,
+            response: StoredResponse {
+                status: response.status,
+                status_text: response.status_text.clone(),
+                headers: redact(&response.headers),
+                body,
+                binary,
+                body_size: response.body_size,
+                duration: response.duration,
+                timings: response.timings,
+            },
+        }
+    }
+}
+
+/// Replaces the values of credentials and cookies, which should not end up in a file
+/// that may be shared or committed along with the `.http` file.
+fn redact(headers: &[(String, String)]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(key, value)| {
+            let value = if is_sensitive_header(key) {
+                REDACTED
+            } else {
+                value
+            };
+            (key.clone(), value.to_string())
+        })
+        .collect()
+}
+
+fn is_sensitive_header(name: &str) -> bool {
+    SENSITIVE_HEADERS
+        .iter()
+        .any(|sensitive| name.eq_ignore_ascii_case(sensitive))
+}
+
+/// Replaces the values of credential query parameters in `url`. With `keep_variables`,
+/// values referring to `{{variables}}` are kept, as templates do not hold the secret.
+fn redact_query(url: &str, keep_variables: bool) -> String {
+    let (url, fragment) = match url.split_once('#') {
+        Some((url, fragment)) => (url, Some(fragment)),
+        None => (url, None),
+    };
+    let Some((base, query)) = url.split_once('?') else {
+        return match fragment {
+            Some(fragment) => format!("{}#{}", url, fragment),
+            None => url.to_string(),
+        };
+    };
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, value))
+                if SENSITIVE_QUERY_PARAMETERS
+                    .iter()
+                    .any(|sensitive| name.eq_ignore_ascii_case(sensitive))
+                    && !(keep_variables && value.contains("{{")) =>
+            {
+                format!("{}={}", name, REDACTED)
+            }
+            _ => pair.to_string(),
+        })
+        .collect();
+    let mut redacted = format!("{}?{}", base, query.join("&"));
+    if let Some(fragment) = fragment {
+        redacted.push('#');
+        redacted.push_str(fragment);
+    }
+    redacted
+}
+
+impl From<Record> for HistoryEntry {
+    fn from(record: Record) -> Self {
+        let stored = record.response;
+        let body = if stored.binary {
+            BASE64.decode(&stored.body).unwrap_or_default()
+        } else {
+            stored.body.into_bytes()
+        };
+
+        let mut response = Response::error(String::new());
+        response.status = stored.status;
+        response.status_text = stored.status_text;
+        response.headers = stored.headers;
+        response.text = if stored.binary {
+            None
+        } else {
+            decode_text(&body, response.content_type())
+        };
+        response.body = body;
+        response.body_size = stored.body_size;
+        response.duration = stored.duration;
+        response.timings = stored.timings;
+
+        Self {
+            request: record.request,
+            sent: record.sent,
+            response,
+            timestamp: record.timestamp.into(),
+        }
+    }
+}
+
+/// History persisted as one JSON record per line, keeping the most recent `limit` entries.
+///
+/// Complete bodies of responses that were spilled to a temporary file are not kept;
+/// only the part held in memory is stored.
+pub struct HistoryStore {
+    path: PathBuf,
+    limit: usize,
+    len: usize,
+}
+
+impl HistoryStore {
+    /// Returns the history file used for `http_file`, e.g. `api.history.jsonl` for `api.http`.
+    pub fn path_for(http_file: &Path) -> PathBuf {
+        let stem = http_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "poke".to_string());
+        http_file.with_file_name(format!("{}.history.jsonl", stem))
+    }
+
+    /// Opens the history file at `path`, returning the stored entries oldest first.
+    pub fn open(path: &Path, limit: usize) -> Result<(Self, Vec<HistoryEntry>)> {
+        let mut store = Self {
+            path: path.to_path_buf(),
+            limit,
+            len: 0,
+        };
+        let (records, damaged) = store.read_records()?;
+        store.len = records.len();
+        if store.len > limit || damaged {
+            store.compact()?;
+        }
+
+        let skip = records.len().saturating_sub(limit);
+        let entries = records.into_iter().skip(skip).map(Into::into).collect();
+        Ok((store, entries))
+    }
+
+    /// Reads the entries stored at `path` without modifying the file.
+    pub fn read(path: &Path) -> Result<Vec<HistoryEntry>> {
+        let store = Self {
+            path: path.to_path_buf(),
+            limit: usize::MAX,
+            len: 0,
+        };
+        let (records, _) = store.read_records()?;
+        Ok(records.into_iter().map(Into::into).collect())
+    }
+
+    pub fn append(&mut self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", to_line(&Record::from(entry))?)?;
+        self.len += 1;
+
+        // Rewriting on every append would copy every stored body, so allow some slack.
+        if self.len > self.limit + self.limit / 10 {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Reads all records, also reporting whether unreadable lines were skipped.
+    fn read_records(&self) -> Result<(Vec<Record>, bool)> {
+        if !self.path.exists() {
+            return Ok((Vec::new(), false));
+        }
+
+        let reader = BufReader::new(File::open(&self.path)?);
+        let mut records = Vec::new();
+        let mut damaged = false;
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // A line cut short by a crash should not make the whole history unreadable.
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(_) => damaged = true,
+            }
+        }
+        Ok((records, damaged))
+    }
+
+    /// Drops all but the newest `limit` entries from the file.
+    fn compact(&mut self) -> Result<()> {
+        let (records, _) = self.read_records()?;
+        let skip = records.len().saturating_sub(self.limit);
+
+        let temp_path = self.path.with_extension("jsonl.tmp");
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        for record in &records[skip..] {
+            writeln!(writer, "{}", to_line(record)?)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&temp_path, &self.path)?;
+
+        self.len = records.len() - skip;
+        Ok(())
+    }
+}
+
+fn to_line(record: &Record) -> Result<String> {
+    serde_json::to_string(record).map_err(|err| HistoryError::Serialize(err.to_string()).into())
+}
+
+/// A history filter such as `post status:5xx users`.
+///
+/// Whitespace-separated terms must all match. `status:` takes a code (`404`), a class
+/// (`5xx`) or a range (`400-499`); `method:`, `url:`, `name:` and `body:` restrict a
+/// term to one field. Plain terms match the method, URL, request name or either body,
+/// ignoring case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryFilter {
+    terms: Vec<FilterTerm>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FilterTerm {
+    Status(u16, u16),
+    Method(String),
+    Url(String),
+    Name(String),
+    Body(String),
+    Any(String),
+}
+
+impl HistoryFilter {
+    pub fn parse(text: &str) -> Self {
+        let terms = text
+            .split_whitespace()
+            .map(|term| {
+                let Some((field, value)) = term.split_once(':') else {
+                    return FilterTerm::Any(term.to_lowercase());
+                };
+                let value = value.to_lowercase();
+                match field.to_lowercase().as_str() {
+                    "status" => match status_range(&value) {
+                        Some((low, high)) => FilterTerm::Status(low, high),
+                        None => FilterTerm::Any(term.to_lowercase()),
+                    },
+                    "method" => FilterTerm::Method(value),
+                    "url" => FilterTerm::Url(value),
+                    "name" => FilterTerm::Name(value),
+                    "body" => FilterTerm::Body(value),
+                    // Keeps `http://host` and similar terms searchable.
+                    _ => FilterTerm::Any(term.to_lowercase()),
+                }
+            })
+            .collect();
+        Self { terms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        let contains = |text: &str, needle: &str| text.to_lowercase().contains(needle);
+        let method = entry.sent.method.to_string().to_lowercase();
+        let name = entry.request.name.as_deref().unwrap_or_default();
+        let request_body = entry.sent.body.as_deref().unwrap_or_default();
+        let response_body = entry.response.text.as_deref().unwrap_or_default();
+        let body_matches =
+            |needle: &str| contains(request_body, needle) || contains(response_body, needle);
+
+        self.terms.iter().all(|term| match term {
+            FilterTerm::Status(low, high) => (*low..=*high).contains(&entry.response.status),
+            FilterTerm::Method(value) => method == *value,
+            FilterTerm::Url(value) => contains(&entry.sent.url, value),
+            FilterTerm::Name(value) => contains(name, value),
+            FilterTerm::Body(value) => body_matches(value),
+            FilterTerm::Any(value) => {
+                method == *value
+                    || contains(&entry.sent.url, value)
+                    || contains(name, value)
+                    || body_matches(value)
+            }
+        })
+    }
+}
+
+/// Parses `404`, `5xx` or `400-499` into an inclusive range.
+fn status_range(value: &str) -> Option<(u16, u16)> {
+    if let Some((low, high)) = value.split_once('-') {
+        return Some((low.parse().ok()?, high.parse().ok()?));
+    }
+    if let Some(class) = value.strip_suffix("xx") {
+        let class: u16 = class.parse().ok()?;
+        let low = class.checked_mul(100)?;
+        return Some((low, low.checked_add(99)?));
+    }
+    let status = value.parse().ok()?;
+    Some((status, status))
+}
+
+/// Order of the history list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HistorySort {
+    /// Newest first.
+    #[default]
+    Time,
+    /// Slowest first.
+    Duration,
+    /// Largest response body first.
+    Size,
+}
+
+impl HistorySort {
+    pub fn next(self) -> Self {
+        match self {
+            HistorySort::Time => HistorySort::Duration,
+            HistorySort::Duration => HistorySort::Size,
+            HistorySort::Size => HistorySort::Time,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HistorySort::Time => "time",
+            HistorySort::Duration => "duration",
+            HistorySort::Size => "size",
+        }
+    }
+
+    /// Sorts `(index, entry)` pairs; ties keep the newest entry first.
+    pub fn sort(self, entries: &mut [(usize, &HistoryEntry)]) {
+        entries.sort_by(|(a_index, a), (b_index, b)| {
+            let order = match self {
+                HistorySort::Time => a.timestamp.cmp(&b.timestamp),
+                HistorySort::Duration => a.response.duration.cmp(&b.response.duration),
+                HistorySort::Size => a.response.body_size.cmp(&b.response.body_size),
+            };
+            order.then(a_index.cmp(b_index)).reverse()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Method;
+    use std::collections::HashMap;
+
+    fn entry(n: u16) -> HistoryEntry {
+        let request = Request::new(Method::Get, "{{host}}/items/{{id}}");
+        let variables = HashMap::from([
+            ("host".to_string(), "http://localhost".to_string()),
+            ("id".to_string(), n.to_string()),
+        ]);
+        let mut response = Response::error(format!("{{\"id\": {}}}", n));
+        response.status = 200;
+        response.status_text = "OK".to_string();
+        response.headers = vec![("content-type".to_string(), "application/json".to_string())];
+        response.duration = Duration::from_millis(12);
+
+        HistoryEntry {
+            sent: request.substituted(&variables).unwrap().into(),
+            request,
+            response,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "poke-history-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_details() {
+        let mut entry = entry(7);
+        entry
+            .sent
+            .headers
+            .push(("Accept".to_string(), "*/*".to_string()));
+
+        assert!(
+            entry
+                .summary()
+                .ends_with("200  GET          12ms  http://localhost/items/7")
+        );
+        assert_eq!(
+            entry.details(),
+            "> GET http://localhost/items/7\n\
+             > Accept: */*\n\
+             \n\
+             < 200 OK (12ms)\n\
+             < content-type: application/json\n\
+             \n\
+             {\"id\": 7}\n"
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut failed = entry(2);
+        failed.response.status = 503;
+        failed.sent.method = Method::Post;
+        failed.sent.body = Some("{\"name\": \"Widget\"}".to_string());
+        failed.request.name = Some("Create item".to_string());
+        let ok = entry(1);
+
+        let matching = |filter: &str| {
+            let filter = HistoryFilter::parse(filter);
+            [&ok, &failed]
+                .iter()
+                .filter(|entry| filter.matches(entry))
+                .count()
+        };
+
+        assert_eq!(matching(""), 2);
+        assert_eq!(matching("status:5xx"), 1);
+        assert_eq!(matching("status:200-299"), 1);
+        assert_eq!(matching("status:404"), 0);
+        assert_eq!(matching("post"), 1);
+        assert_eq!(matching("method:get"), 1);
+        assert_eq!(matching("widget"), 1);
+        assert_eq!(matching("name:create status:503"), 1);
+        assert_eq!(matching("url:items/1"), 1);
+        assert_eq!(matching("http://localhost"), 2);
+        // Out of range classes are searched as text instead of overflowing.
+        assert_eq!(matching("status:700xx"), 0);
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut slow = entry(1);
+        slow.response.duration = Duration::from_secs(2);
+        let mut large = entry(2);
+        large.response.body_size = 1024;
+        let newest = entry(3);
+        let entries = [slow, large, newest];
+
+        let order = |sort: HistorySort| {
+            let mut list: Vec<(usize, &HistoryEntry)> = entries.iter().enumerate().collect();
+            sort.sort(&mut list);
+            list.iter().map(|(index, _)| *index).collect::<Vec<_>>()
+        };
+
+        assert_eq!(order(HistorySort::Time), [2, 1, 0]);
+        assert_eq!(order(HistorySort::Duration), [0, 2, 1]);
+        assert_eq!(order(HistorySort::Size), [1, 2, 0]);
+    }
+
+    #[test]
+    fn test_path_for() {
+        assert_eq!(
+            HistoryStore::path_for(Path::new("/tmp/api.http")),
+            PathBuf::from("/tmp/api.history.jsonl")
+        );
+    }
+
+    #[test]
+    fn test_entries_survive_reopening() {
+        let path = temp_path("reopen");
+        fs::remove_file(&path).ok();
+
+        let (mut store, entries) = HistoryStore::open(&path, 10).unwrap();
+        assert!(entries.is_empty());
+        store.append(&entry(1)).unwrap();
+        let mut binary = entry(2);
+        binary.response.text = None;
+        binary.response.body = vec![0, 159, 146, 150];
+        store.append(&binary).unwrap();
+
+        let (_, entries) = HistoryStore::open(&path, 10).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].request.url, "{{host}}/items/{{id}}");
+        assert_eq!(entries[0].sent.url, "http://localhost/items/1");
+        assert_eq!(entries[0].response.status, 200);
+        assert_eq!(entries[0].response.text.as_deref(), Some("{\"id\": 1}"));
+        assert_eq!(entries[0].response.duration, Duration::from_millis(12));
+        assert_eq!(entries[1].response.body, vec![0, 159, 146, 150]);
+    }
+
+    #[test]
+    fn test_credentials_are_not_stored() {
+        let path = temp_path("redact");
+        fs::remove_file(&path).ok();
+
+        let mut entry = entry(1);
+        entry.sent.headers = vec![
+            ("Authorization".to_string(), "Bearer abc".to_string()),
+            ("Cookie".to_string(), "session=1".to_string()),
+            ("Accept".to_string(), "*/*".to_string()),
+        ];
+        entry
+            .response
+            .headers
+            .push(("Set-Cookie".to_string(), "session=2".to_string()));
+        entry.request.url =
+            "{{host}}/items?api_key={{apiKey}}&access_token=letmein#top".to_string();
+        entry.request.headers.insert(
+            "Authorization".to_string(),
+            "Basic alice:s3cret".to_string(),
+        );
+        entry.request.headers.insert(
+            "Proxy-Authorization".to_string(),
+            "Bearer {{proxyToken}}".to_string(),
+        );
+        entry.sent.url =
+            "http://localhost/items?api_key=s3cret&access_token=letmein&page=2#top".to_string();
+        let (mut store, _) = HistoryStore::open(&path, 10).unwrap();
+        store.append(&entry).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        let entries = HistoryStore::read(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert!(!contents.contains("abc") && !contents.contains("session="));
+        assert!(!contents.contains("s3cret") && !contents.contains("letmein"));
+        assert_eq!(
+            entries[0].request.url,
+            "{{host}}/items?api_key={{apiKey}}&access_token=[redacted]#top"
+        );
+        assert_eq!(entries[0].request.headers["Authorization"], REDACTED);
+        assert_eq!(
+            entries[0].request.headers["Proxy-Authorization"],
+            "Bearer {{proxyToken}}"
+        );
+        assert_eq!(
+            entries[0].sent.url,
+            "http://localhost/items?api_key=[redacted]&access_token=[redacted]&page=2#top"
+        );
+        assert_eq!(entries[0].sent.header("authorization"), Some(REDACTED));
+        assert_eq!(entries[0].sent.header("accept"), Some("*/*"));
+        assert_eq!(
+            entries[0].response.headers[1],
+            ("Set-Cookie".to_string(), REDACTED.to_string())
+        );
+    }
+
+    #[test]
+    fn test_retention_keeps_newest_entries() {
+        let path = temp_path("retention");
+        fs::remove_file(&path).ok();
+
+        let (mut store, _) = HistoryStore::open(&path, 3).unwrap();
+        for n in 1..=5 {
+            store.append(&entry(n)).unwrap();
+        }
+        let (_, entries) = HistoryStore::open(&path, 3).unwrap();
+        let stored = HistoryStore::read(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        let urls: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry.sent.url.as_str())
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "http://localhost/items/3",
+                "http://localhost/items/4",
+                "http://localhost/items/5"
+            ]
+        );
+        assert_eq!(stored.len(), 3);
+    }
+
+    #[test]
+    fn test_truncated_line_is_skipped() {
+        let path = temp_path("truncated");
+        fs::remove_file(&path).ok();
+
+        let (mut store, _) = HistoryStore::open(&path, 10).unwrap();
+        store.append(&entry(1)).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"timestamp\": ").unwrap();
+
+        assert_eq!(HistoryStore::read(&path).unwrap().len(), 1);
+        let (mut store, _) = HistoryStore::open(&path, 10).unwrap();
+        store.append(&entry(2)).unwrap();
+        let entries = HistoryStore::read(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(entries.len(), 2);
+    }
+}
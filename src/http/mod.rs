@@ -1,5 +1,7 @@
 mod parser;
 mod request;
+mod serializer;
 
 pub use parser::Parser;
 pub use request::{HttpFile, Method, Request};
+pub use serializer::Serializer;
@@ -1,8 +1,9 @@
 use crate::error::{ParseError, Result};
+use crate::http::serializer::{Layout, LineKind, Section};
 use crate::http::{HttpFile, Method, Request};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct Parser;
 
@@ -10,82 +11,134 @@ impl Parser {
     pub fn parse_file(path: &Path) -> Result<HttpFile> {
         let content =
             fs::read_to_string(path).map_err(|err| ParseError::IoError(err.to_string()))?;
+        Self::parse_file_content(path, &content)
+    }
 
+    /// Parses `content` as the contents of the file at `path`, keeping its layout so
+    /// [`HttpFile::to_http`] can write it back unchanged.
+    pub fn parse_file_content(path: &Path, content: &str) -> Result<HttpFile> {
         let mut http_file = HttpFile::new(path);
-        let (requests, variables) = Self::parse_content(&content)?;
+        let (mut requests, variables, mut layout) = Self::parse(content)?;
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for request in &mut requests {
+            for proto_file in &mut request.proto_files {
+                if proto_file.is_relative() {
+                    *proto_file = dir.join(&*proto_file);
+                }
+            }
+        }
+
+        layout.requests = requests.clone();
+        layout.variables = variables.clone();
         http_file.requests = requests;
         http_file.variables = variables;
+        http_file.layout = Some(layout);
         Ok(http_file)
     }
 
     pub fn parse_content(content: &str) -> Result<(Vec<Request>, HashMap<String, String>)> {
+        let (requests, variables, _) = Self::parse(content)?;
+        Ok((requests, variables))
+    }
+
+    fn parse(content: &str) -> Result<(Vec<Request>, HashMap<String, String>, Layout)> {
         let mut requests = Vec::new();
         let mut variables = HashMap::new();
+        let mut layout = Layout::new(content);
         let mut current_name: Option<String> = None;
         let mut current_request: Option<RequestBuilder> = None;
+        let mut no_cookie_jar = false;
+        let mut proto_files = Vec::new();
 
-        for line in content.lines() {
+        for (index, line) in layout.lines.iter().enumerate() {
             let line = line.trim_end();
+            let kind = 'line: {
+                if let Some((name, value)) = Self::try_parse_variable(line) {
+                    variables.insert(name.clone(), value);
+                    break 'line LineKind::Variable(name);
+                }
 
-            if let Some((name, value)) = Self::try_parse_variable(line) {
-                variables.insert(name, value);
-                continue;
-            }
+                if line.starts_with("###") {
+                    if let Some(builder) = current_request.take() {
+                        requests.push(builder.build(requests.len())?);
+                    }
 
-            if line.starts_with("###") {
-                if let Some(builder) = current_request.take() {
-                    requests.push(builder.build()?);
+                    let name = line.trim_start_matches('#').trim();
+                    current_name = if name.is_empty() {
+                        None
+                    } else {
+                        Some(name.to_string())
+                    };
+                    layout.sections.push(Section::new(index));
+                    break 'line LineKind::Separator;
                 }
 
-                let name = line.trim_start_matches('#').trim();
-                current_name = if name.is_empty() {
-                    None
-                } else {
-                    Some(name.to_string())
-                };
-                continue;
-            }
-
-            if line.starts_with('#') || line.starts_with("//") {
-                continue;
-            }
+                if line.starts_with('#') || line.starts_with("//") {
+                    // Directives belong to the request below them; inside a request
+                    // they are plain comments.
+                    if current_request.is_some() {
+                        break 'line LineKind::Comment;
+                    }
+                    break 'line match Self::try_parse_directive(line) {
+                        Some(("no-cookie-jar", _)) => {
+                            no_cookie_jar = true;
+                            LineKind::Directive
+                        }
+                        Some(("proto", path)) if !path.is_empty() => {
+                            proto_files.push(PathBuf::from(path));
+                            LineKind::Directive
+                        }
+                        _ => LineKind::Comment,
+                    };
+                }
 
-            if line.is_empty() {
-                if let Some(ref mut builder) = current_request {
-                    if builder.headers_done {
-                        builder.body_lines.push(String::new());
-                    } else {
+                if line.is_empty() {
+                    if let Some(ref mut builder) = current_request {
+                        if builder.headers_done {
+                            builder.body_lines.push(String::new());
+                            break 'line LineKind::Body;
+                        }
                         builder.headers_done = true;
                     }
+                    break 'line LineKind::Blank;
                 }
-                continue;
-            }
 
-            if current_request.is_none()
-                && let Some(builder) = Self::try_parse_request_line(line, current_name.take())?
-            {
-                current_request = Some(builder);
-                continue;
-            }
+                if current_request.is_none()
+                    && let Some(mut builder) =
+                        Self::try_parse_request_line(line, current_name.take())?
+                {
+                    builder.no_cookie_jar = std::mem::take(&mut no_cookie_jar);
+                    builder.proto_files = std::mem::take(&mut proto_files);
+                    current_request = Some(builder);
+                    if let Some(section) = layout.sections.last_mut() {
+                        section.request = Some(requests.len());
+                    }
+                    break 'line LineKind::RequestLine;
+                }
 
-            if let Some(ref mut builder) = current_request {
+                let Some(ref mut builder) = current_request else {
+                    break 'line LineKind::Other;
+                };
                 if !builder.headers_done {
                     if let Some((key, value)) = Self::try_parse_header(line) {
-                        builder.headers.push((key, value));
-                        continue;
+                        builder.headers.push((key.clone(), value));
+                        break 'line LineKind::Header(key);
                     }
                     builder.headers_done = true;
                 }
 
                 builder.body_lines.push(line.to_string());
-            }
+                LineKind::Body
+            };
+            layout.kinds.push(kind);
         }
 
         if let Some(builder) = current_request {
-            requests.push(builder.build()?);
+            requests.push(builder.build(requests.len())?);
         }
 
-        Ok((requests, variables))
+        Ok((requests, variables, layout))
     }
 
     fn try_parse_variable(line: &str) -> Option<(String, String)> {
@@ -102,6 +155,22 @@ impl Parser {
         Some((name, value))
     }
 
+    /// Parses a `# @name value` comment into its name and (possibly empty) value.
+    fn try_parse_directive(line: &str) -> Option<(&str, &str)> {
+        let comment = line
+            .strip_prefix("//")
+            .or_else(|| line.strip_prefix('#'))?
+            .trim_start();
+        let directive = comment.strip_prefix('@')?;
+        let (name, value) = directive
+            .split_once(char::is_whitespace)
+            .unwrap_or((directive, ""));
+        if name.is_empty() {
+            return None;
+        }
+        Some((name, value.trim()))
+    }
+
     fn try_parse_request_line(line: &str, name: Option<String>) -> Result<Option<RequestBuilder>> {
         let parts: Vec<&str> = line.split_whitespace().collect();
 
@@ -123,6 +192,8 @@ impl Parser {
             headers: Vec::new(),
             body_lines: Vec::new(),
             headers_done: false,
+            no_cookie_jar: false,
+            proto_files: Vec::new(),
         }))
     }
 
@@ -146,12 +217,17 @@ struct RequestBuilder {
     headers: Vec<(String, String)>,
     body_lines: Vec<String>,
     headers_done: bool,
+    no_cookie_jar: bool,
+    proto_files: Vec<PathBuf>,
 }
 
 impl RequestBuilder {
-    fn build(self) -> Result<Request> {
+    fn build(self, index: usize) -> Result<Request> {
         let mut request = Request::new(self.method, self.url);
         request.name = self.name;
+        request.source = Some(index);
+        request.no_cookie_jar = self.no_cookie_jar;
+        request.proto_files = self.proto_files;
 
         for (key, value) in self.headers {
             request.headers.insert(key, value);
@@ -276,4 +352,115 @@ GET https://api.example.com/users
 
         assert_eq!(variables.get("message"), Some(&"hello world".to_string()));
     }
+
+    #[test]
+    fn test_parse_no_cookie_jar_directive() {
+        let content = r#"
+### Login
+POST https://api.example.com/login
+
+### Anonymous
+# @no-cookie-jar
+GET https://api.example.com/public
+"#;
+        let (requests, _) = Parser::parse_content(content).unwrap();
+
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].no_cookie_jar);
+        assert!(requests[1].no_cookie_jar);
+    }
+
+    #[test]
+    fn test_parse_directive_inside_request_is_a_comment() {
+        let content = r#"
+### Login
+POST https://api.example.com/login
+# @no-cookie-jar
+# @proto greeter.proto
+Content-Type: application/json
+
+### Profile
+GET https://api.example.com/me
+"#;
+        let (requests, _) = Parser::parse_content(content).unwrap();
+
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].no_cookie_jar);
+        assert!(!requests[1].no_cookie_jar);
+        assert!(requests[1].proto_files.is_empty());
+        assert_eq!(requests[0].headers["Content-Type"], "application/json");
+    }
+
+    #[test]
+    fn test_parse_websocket_request() {
+        let content = r#"
+### Chat
+WEBSOCKET ws://localhost:8080/chat
+Authorization: Bearer token
+
+{"type": "hello"}
+===
+{"type": "ping"}
+
+### Upgrade
+GET http://localhost:8080/chat
+Upgrade: websocket
+"#;
+        let (requests, _) = Parser::parse_content(content).unwrap();
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, Method::WebSocket);
+        assert!(requests[0].is_websocket());
+        assert_eq!(
+            requests[0].websocket_messages(),
+            vec!["{\"type\": \"hello\"}", "{\"type\": \"ping\"}"]
+        );
+        assert!(requests[1].is_websocket());
+        assert!(requests[1].websocket_messages().is_empty());
+    }
+
+    #[test]
+    fn test_parse_grpc_request_with_proto() {
+        let content = r#"
+### Say hello
+# @proto protos/greeter.proto
+GRPC localhost:50051/helloworld.Greeter/SayHello
+
+{"name": "poke"}
+"#;
+        let (requests, _) = Parser::parse_content(content).unwrap();
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::Grpc);
+        assert_eq!(
+            requests[0].url,
+            "localhost:50051/helloworld.Greeter/SayHello"
+        );
+        assert_eq!(
+            requests[0].proto_files,
+            vec![PathBuf::from("protos/greeter.proto")]
+        );
+        assert_eq!(requests[0].body, Some("{\"name\": \"poke\"}".to_string()));
+    }
+
+    #[test]
+    fn test_request_to_http_round_trip() {
+        let request = Request::new(Method::Post, "{{host}}/items")
+            .with_name("Create item")
+            .with_header("Content-Type", "application/json")
+            .with_header("Accept", "*/*")
+            .with_body("{\n  \"name\": \"Widget\"\n}");
+        let mut anonymous = Request::new(Method::Get, "{{host}}/items");
+        anonymous.no_cookie_jar = true;
+
+        let content = format!("{}\n{}", request.to_http(), anonymous.to_http());
+        let (requests, _) = Parser::parse_content(&content).unwrap();
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].name.as_deref(), Some("Create item"));
+        assert_eq!(requests[0].headers, request.headers);
+        assert_eq!(requests[0].body, request.body);
+        assert_eq!(requests[1].name, None);
+        assert!(requests[1].no_cookie_jar);
+    }
 }
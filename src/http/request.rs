@@ -1,9 +1,16 @@
+use crate::error::{ParseError, Result};
+use crate::http::Parser;
+use crate::http::serializer::{Layout, Serializer};
+use crate::variable;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
+    fs,
     path::{Path, PathBuf},
 };
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum Method {
     Get,
     Post,
@@ -12,6 +19,8 @@ pub enum Method {
     Delete,
     Head,
     Options,
+    WebSocket,
+    Grpc,
 }
 
 impl std::fmt::Display for Method {
@@ -24,6 +33,8 @@ impl std::fmt::Display for Method {
             Method::Delete => write!(f, "DELETE"),
             Method::Head => write!(f, "HEAD"),
             Method::Options => write!(f, "OPTIONS"),
+            Method::WebSocket => write!(f, "WEBSOCKET"),
+            Method::Grpc => write!(f, "GRPC"),
         }
     }
 }
@@ -31,7 +42,7 @@ impl std::fmt::Display for Method {
 impl std::str::FromStr for Method {
     type Err = crate::error::ParseError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s.to_uppercase().as_str() {
             "GET" => Ok(Method::Get),
             "POST" => Ok(Method::Post),
@@ -40,18 +51,42 @@ impl std::str::FromStr for Method {
             "DELETE" => Ok(Method::Delete),
             "HEAD" => Ok(Method::Head),
             "OPTIONS" => Ok(Method::Options),
+            "WEBSOCKET" => Ok(Method::WebSocket),
+            "GRPC" => Ok(Method::Grpc),
             _ => Err(crate::error::ParseError::InvalidMethod(s.to_string())),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Request {
     pub name: Option<String>,
     pub method: Method,
     pub url: String,
     pub headers: HashMap<String, String>,
     pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_cookie_jar: bool,
+    /// `.proto` files describing a gRPC request, from `# @proto` directives.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proto_files: Vec<PathBuf>,
+    /// Index of the request in the file it was parsed from, so edits can be written
+    /// back in place.
+    #[serde(skip)]
+    pub(crate) source: Option<usize>,
+}
+
+/// Requests are equal when they send the same thing, wherever they were parsed from.
+impl PartialEq for Request {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.method == other.method
+            && self.url == other.url
+            && self.headers == other.headers
+            && self.body == other.body
+            && self.no_cookie_jar == other.no_cookie_jar
+            && self.proto_files == other.proto_files
+    }
 }
 
 impl Request {
@@ -62,6 +97,9 @@ impl Request {
             url: url.into(),
             headers: HashMap::new(),
             body: None,
+            no_cookie_jar: false,
+            proto_files: Vec::new(),
+            source: None,
         }
     }
 
@@ -80,6 +118,83 @@ impl Request {
         self
     }
 
+    /// Whether this request opens a WebSocket session, either through the `WEBSOCKET`
+    /// method or a `GET` carrying `Upgrade: websocket`.
+    pub fn is_websocket(&self) -> bool {
+        self.method == Method::WebSocket
+            || (self.method == Method::Get
+                && self.headers.iter().any(|(key, value)| {
+                    key.eq_ignore_ascii_case("upgrade") && value.eq_ignore_ascii_case("websocket")
+                }))
+    }
+
+    /// Splits the body into the messages sent once a WebSocket session is open.
+    /// Messages are separated by lines consisting of `===`.
+    pub fn websocket_messages(&self) -> Vec<String> {
+        let Some(ref body) = self.body else {
+            return Vec::new();
+        };
+
+        let mut messages = Vec::new();
+        let mut current: Vec<&str> = Vec::new();
+        for line in body.lines() {
+            if line.trim() == "===" {
+                messages.push(current.join("\n"));
+                current.clear();
+            } else {
+                current.push(line);
+            }
+        }
+        messages.push(current.join("\n"));
+
+        messages
+            .into_iter()
+            .map(|message| message.trim().to_string())
+            .filter(|message| !message.is_empty())
+            .collect()
+    }
+
+    /// Returns a copy with `variables` substituted into the URL, headers and body.
+    pub fn substituted(&self, variables: &HashMap<String, String>) -> Result<Self> {
+        let mut request = self.clone();
+        request.url = variable::substitute(&self.url, variables)?;
+        for value in request.headers.values_mut() {
+            *value = variable::substitute(value, variables)?;
+        }
+        if let Some(ref body) = self.body {
+            request.body = Some(variable::substitute(body, variables)?);
+        }
+        Ok(request)
+    }
+
+    /// Formats the request in .http syntax, starting with its `###` separator line.
+    /// Headers are written in alphabetical order.
+    pub fn to_http(&self) -> String {
+        let mut out = match self.name {
+            Some(ref name) => format!("### {}\n", name),
+            None => "###\n".to_string(),
+        };
+        if self.no_cookie_jar {
+            out.push_str("# @no-cookie-jar\n");
+        }
+        for proto_file in &self.proto_files {
+            out.push_str(&format!("# @proto {}\n", proto_file.display()));
+        }
+        out.push_str(&format!("{} {}\n", self.method, self.url));
+
+        let mut headers: Vec<_> = self.headers.iter().collect();
+        headers.sort();
+        for (key, value) in headers {
+            out.push_str(&format!("{}: {}\n", key, value));
+        }
+        if let Some(ref body) = self.body {
+            out.push('\n');
+            out.push_str(body.trim_end_matches('\n'));
+            out.push('\n');
+        }
+        out
+    }
+
     pub fn display_name(&self) -> String {
         self.name
             .clone()
@@ -92,6 +207,8 @@ pub struct HttpFile {
     pub path: PathBuf,
     pub requests: Vec<Request>,
     pub variables: HashMap<String, String>,
+    /// The text the file was parsed from, if it was.
+    pub(crate) layout: Option<Layout>,
 }
 
 impl HttpFile {
@@ -100,6 +217,100 @@ impl HttpFile {
             path: path.into(),
             requests: Vec::new(),
             variables: HashMap::new(),
+            layout: None,
+        }
+    }
+
+    /// Formats the file in .http syntax, keeping the text of a parsed file apart from
+    /// what has been edited. See [`Serializer::serialize`].
+    pub fn to_http(&self) -> String {
+        Serializer::serialize(self)
+    }
+
+    /// Writes the file back to `path`, keeping the formatting of everything that was
+    /// not edited, and re-reads the requests from what was written.
+    ///
+    /// Variables are written as the file defined them, since `variables` may also
+    /// hold those of the selected environment. Fails without writing if the file was
+    /// changed on disk since it was read.
+    pub fn save(&mut self) -> Result<()> {
+        // Refuse rather than overwrite changes made by another program.
+        if let Some(ref layout) = self.layout {
+            match fs::read_to_string(&self.path) {
+                Ok(current) if current != layout.text() => {
+                    return Err(ParseError::ChangedOnDisk(self.path.clone()).into());
+                }
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+
+        let mut file = self.clone();
+        if let Some(ref layout) = self.layout {
+            file.variables = layout.variables.clone();
+        }
+        let content = file.to_http();
+        fs::write(&self.path, &content)?;
+
+        let saved = Parser::parse_file_content(&self.path, &content)?;
+        self.requests = saved.requests;
+        self.layout = saved.layout;
+        Ok(())
+    }
+
+    /// Reads the file again from `path`, e.g. after it was changed by another program.
+    ///
+    /// Variables that the file did not define, such as those of the selected
+    /// environment, are kept.
+    pub fn reload(&mut self) -> Result<()> {
+        let file = Parser::parse_file(&self.path)?;
+        self.replace_with(file);
+        Ok(())
+    }
+
+    /// Takes the requests and layout of `file`, re-read from disk, keeping the
+    /// variables that the file did not define.
+    fn replace_with(&mut self, mut file: HttpFile) {
+        let defined = self.layout.as_ref().map(|layout| &layout.variables);
+        for (name, value) in &self.variables {
+            if !defined.is_some_and(|defined| defined.contains_key(name)) {
+                file.variables
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+        *self = file;
+    }
+
+    /// The line number, counting from 1, of the request line of `requests[index]` in
+    /// the file as it was last read or saved.
+    pub fn request_line(&self, index: usize) -> Option<usize> {
+        let layout = self.layout.as_ref()?;
+        let source = self.requests.get(index)?.source?;
+        layout
+            .request_line(layout.section_of(source))
+            .map(|line| line + 1)
+    }
+
+    /// Appends `request` to the end of the file on disk and reads the file again, so
+    /// that it is the last of `requests`.
+    pub fn append_request(&mut self, request: Request) -> Result<()> {
+        let mut content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+        if !content.is_empty() {
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push('\n');
         }
+        content.push_str(&request.to_http());
+        fs::write(&self.path, &content)?;
+
+        let file = Parser::parse_file_content(&self.path, &content)?;
+        self.replace_with(file);
+        Ok(())
     }
 }
@@ -0,0 +1,966 @@
+use crate::http::{HttpFile, Request};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::Path;
+
+/// The text a file was parsed from, with what each line meant to the parser, so that
+/// unchanged parts can be written back exactly as they were.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Layout {
+    /// The lines of the file, including any `\r` before the newline.
+    pub(crate) lines: Vec<String>,
+    pub(crate) kinds: Vec<LineKind>,
+    /// The part before the first `###` line, then one section per `###` line.
+    pub(crate) sections: Vec<Section>,
+    /// The requests and variables as parsed, to tell what has been edited since.
+    pub(crate) requests: Vec<Request>,
+    pub(crate) variables: HashMap<String, String>,
+    trailing_newline: bool,
+    crlf: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LineKind {
+    Variable(String),
+    Separator,
+    Directive,
+    Comment,
+    Blank,
+    RequestLine,
+    Header(String),
+    Body,
+    /// Text outside of any request.
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Section {
+    pub(crate) start: usize,
+    /// Index of the request parsed from this section, if any.
+    pub(crate) request: Option<usize>,
+}
+
+impl Section {
+    pub(crate) fn new(start: usize) -> Self {
+        Self {
+            start,
+            request: None,
+        }
+    }
+}
+
+impl Layout {
+    pub(crate) fn new(content: &str) -> Self {
+        let lines = match content.strip_suffix('\n').unwrap_or(content) {
+            _ if content.is_empty() => Vec::new(),
+            text => text.split('\n').map(String::from).collect(),
+        };
+        Self {
+            lines,
+            sections: vec![Section::new(0)],
+            trailing_newline: content.ends_with('\n'),
+            crlf: content.contains("\r\n"),
+            ..Default::default()
+        }
+    }
+
+    fn range(&self, section: usize) -> Range<usize> {
+        let end = self
+            .sections
+            .get(section + 1)
+            .map_or(self.lines.len(), |next| next.start);
+        self.sections[section].start..end
+    }
+
+    pub(crate) fn request_line(&self, section: usize) -> Option<usize> {
+        self.range(section)
+            .find(|&index| self.kinds[index] == LineKind::RequestLine)
+    }
+
+    /// The text the layout was made from.
+    pub(crate) fn text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        if self.trailing_newline {
+            text.push('\n');
+        }
+        text
+    }
+
+    pub(crate) fn section_of(&self, request: usize) -> usize {
+        self.sections
+            .iter()
+            .position(|section| section.request == Some(request))
+            .unwrap_or(0)
+    }
+}
+
+pub struct Serializer;
+
+impl Serializer {
+    /// Writes `file` as .http text.
+    ///
+    /// Files that were parsed keep their text: requests and variables that were not
+    /// edited are written back byte for byte, edited ones are changed line by line
+    /// where possible, and new requests are added in their place in the list.
+    /// Other files are written with variables in alphabetical order, then the
+    /// requests separated by blank lines.
+    pub fn serialize(file: &HttpFile) -> String {
+        let dir = file.path.parent().unwrap_or(Path::new(""));
+        let Some(ref layout) = file.layout else {
+            return Self::serialize_new(file, dir);
+        };
+
+        let mut writer = Writer {
+            file,
+            layout,
+            dir,
+            out: Vec::new(),
+            separate: false,
+            requests_written: false,
+            ends_generated: false,
+            last_definitions: HashMap::new(),
+        };
+        for (index, kind) in layout.kinds.iter().enumerate() {
+            if let LineKind::Variable(name) = kind {
+                writer.last_definitions.insert(name.as_str(), index);
+            }
+        }
+        writer.write();
+
+        let mut text = writer.out.join("\n");
+        if !writer.out.is_empty() && (layout.trailing_newline || writer.ends_generated) {
+            text.push('\n');
+        }
+        text
+    }
+
+    fn serialize_new(file: &HttpFile, dir: &Path) -> String {
+        let mut variables: Vec<_> = file.variables.iter().collect();
+        variables.sort();
+        let mut out: String = variables
+            .into_iter()
+            .map(|(name, value)| format!("@{} = {}\n", name, value))
+            .collect();
+        for request in &file.requests {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&render(request, dir));
+        }
+        out
+    }
+}
+
+/// A request in .http syntax, with `.proto` paths relative to the file again.
+fn render(request: &Request, dir: &Path) -> String {
+    let mut request = request.clone();
+    for proto_file in &mut request.proto_files {
+        if let Ok(relative) = proto_file.strip_prefix(dir)
+            && !dir.as_os_str().is_empty()
+        {
+            *proto_file = relative.to_path_buf();
+        }
+    }
+    request.to_http()
+}
+
+struct Writer<'a> {
+    file: &'a HttpFile,
+    layout: &'a Layout,
+    dir: &'a Path,
+    out: Vec<String>,
+    /// Set after a new request, so the next line is separated from it by a blank line.
+    separate: bool,
+    requests_written: bool,
+    ends_generated: bool,
+    /// The line of the definition that wins for each variable.
+    last_definitions: HashMap<&'a str, usize>,
+}
+
+impl Writer<'_> {
+    fn write(&mut self) {
+        let layout = self.layout;
+        let mut claimed = HashSet::new();
+        let claims: Vec<Option<usize>> = self
+            .file
+            .requests
+            .iter()
+            .map(|request| {
+                request
+                    .source
+                    .filter(|&source| source < layout.requests.len() && claimed.insert(source))
+            })
+            .collect();
+
+        if !layout
+            .kinds
+            .iter()
+            .any(|kind| matches!(kind, LineKind::Variable(_)))
+        {
+            self.write_new_variables();
+        }
+
+        let last_section = claims
+            .iter()
+            .flatten()
+            .map(|&source| layout.section_of(source))
+            .max();
+
+        // The start of the file comes first even if its request was moved or deleted.
+        let head_end = layout
+            .request_line(0)
+            .unwrap_or_else(|| layout.range(0).end);
+        let head_request_deleted = layout.sections[0]
+            .request
+            .is_some_and(|request| !claimed.contains(&request));
+        for index in 0..head_end {
+            if !(head_request_deleted && layout.kinds[index] == LineKind::Directive) {
+                self.write_original(index);
+            }
+        }
+
+        let mut next = 0;
+        for (request, claim) in self.file.requests.iter().zip(claims) {
+            match claim {
+                Some(source) => {
+                    let section = layout.section_of(source);
+                    while next < section {
+                        self.write_unclaimed(next, &claimed);
+                        next += 1;
+                    }
+                    self.write_section(section, request, &layout.requests[source]);
+                    next = next.max(section + 1);
+                    // Sections after the last request written stay with it, rather
+                    // than following requests added at the end.
+                    if Some(section) == last_section {
+                        while next < layout.sections.len() {
+                            self.write_unclaimed(next, &claimed);
+                            next += 1;
+                        }
+                    }
+                }
+                None => self.write_new_request(request),
+            }
+        }
+        while next < layout.sections.len() {
+            self.write_unclaimed(next, &claimed);
+            next += 1;
+        }
+    }
+
+    /// Writes a section whose request is not in the file anymore, keeping only its
+    /// variables, or a section without a request as it is.
+    fn write_unclaimed(&mut self, section: usize, claimed: &HashSet<usize>) {
+        let layout = self.layout;
+        let range = match layout.sections[section].request {
+            Some(request) if claimed.contains(&request) => return,
+            Some(_) if section == 0 => layout.request_line(0).unwrap_or(0)..layout.range(0).end,
+            Some(_) => layout.range(section),
+            None if section == 0 => return,
+            None => {
+                for index in layout.range(section) {
+                    self.write_original(index);
+                }
+                return;
+            }
+        };
+        for index in range {
+            if matches!(layout.kinds[index], LineKind::Variable(_)) {
+                self.write_original(index);
+            }
+        }
+    }
+
+    /// Writes the section a request was parsed from, changing only the lines for
+    /// what was edited.
+    fn write_section(&mut self, section: usize, request: &Request, original: &Request) {
+        let layout = self.layout;
+        let mut range = layout.range(section);
+        let Some(request_line) = layout.request_line(section) else {
+            return;
+        };
+        if section == 0 {
+            range.start = request_line;
+            if request.name.is_some() || self.requests_written {
+                self.write_separator(request);
+            }
+        }
+        self.requests_written = true;
+
+        let directives_changed = request.no_cookie_jar != original.no_cookie_jar
+            || request.proto_files != original.proto_files;
+        let last_header = range
+            .clone()
+            .rev()
+            .find(|&index| matches!(layout.kinds[index], LineKind::Header(_)))
+            .unwrap_or(request_line);
+        let mut new_headers: Vec<_> = request
+            .headers
+            .iter()
+            .filter(|(key, _)| !original.headers.contains_key(*key))
+            .collect();
+        new_headers.sort();
+
+        let body_lines: Vec<usize> = range
+            .clone()
+            .filter(|&index| {
+                layout.kinds[index] == LineKind::Body && !layout.lines[index].trim().is_empty()
+            })
+            .collect();
+        let body_range = match (body_lines.first(), body_lines.last()) {
+            (Some(&first), Some(&last)) if request.body != original.body => first..last + 1,
+            _ => 0..0,
+        };
+
+        for index in range {
+            match layout.kinds[index] {
+                LineKind::Separator if request.name != original.name => {
+                    self.write_separator(request)
+                }
+                LineKind::Directive if index < request_line && directives_changed => {}
+                LineKind::RequestLine => {
+                    if directives_changed {
+                        for line in directive_lines(request, self.dir) {
+                            self.write_line(line);
+                        }
+                    }
+                    if request.method == original.method && request.url == original.url {
+                        self.write_original(index);
+                    } else {
+                        let line = layout.lines[index].trim_end();
+                        let rest: Vec<_> = line.split_whitespace().skip(2).collect();
+                        let mut text = format!("{} {}", request.method, request.url);
+                        if !rest.is_empty() {
+                            text.push(' ');
+                            text.push_str(&rest.join(" "));
+                        }
+                        self.write_line(text);
+                    }
+                }
+                LineKind::Header(ref key) => match request.headers.get(key) {
+                    None => {}
+                    Some(value) if original.headers.get(key) == Some(value) => {
+                        self.write_original(index)
+                    }
+                    Some(value) => {
+                        let is_last = !(index + 1..=last_header)
+                            .any(|later| layout.kinds[later] == LineKind::Header(key.clone()));
+                        if is_last {
+                            self.write_line(format!("{}: {}", key, value));
+                        }
+                    }
+                },
+                LineKind::Body if body_range.contains(&index) => {
+                    if index == body_range.start
+                        && let Some(ref body) = request.body
+                    {
+                        for line in body.lines() {
+                            self.write_line(line.to_string());
+                        }
+                    }
+                }
+                _ => self.write_original(index),
+            }
+
+            if index == last_header {
+                for (key, value) in &new_headers {
+                    self.write_line(format!("{}: {}", key, value));
+                }
+                if original.body.is_none()
+                    && let Some(ref body) = request.body
+                {
+                    self.write_line(String::new());
+                    for line in body.lines() {
+                        self.write_line(line.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_separator(&mut self, request: &Request) {
+        self.write_line(match request.name {
+            Some(ref name) => format!("### {}", name),
+            None => "###".to_string(),
+        });
+    }
+
+    fn write_new_request(&mut self, request: &Request) {
+        if self.out.last().is_some_and(|line| !line.trim().is_empty()) {
+            self.write_line(String::new());
+        }
+        self.separate = false;
+        for line in render(request, self.dir).lines() {
+            self.write_line(line.to_string());
+        }
+        self.separate = true;
+        self.requests_written = true;
+    }
+
+    /// Writes variables that were added since parsing, in alphabetical order.
+    fn write_new_variables(&mut self) {
+        let mut variables: Vec<_> = self
+            .file
+            .variables
+            .iter()
+            .filter(|(name, _)| !self.layout.variables.contains_key(*name))
+            .collect();
+        if variables.is_empty() {
+            return;
+        }
+        variables.sort();
+        for (name, value) in variables {
+            self.write_line(format!("@{} = {}", name, value));
+        }
+        if self
+            .layout
+            .kinds
+            .iter()
+            .all(|kind| !matches!(kind, LineKind::Variable(_)))
+        {
+            self.separate = true;
+        }
+    }
+
+    /// Writes a line of the original file, or what replaced it if it defines a
+    /// variable that has been changed or removed.
+    fn write_original(&mut self, index: usize) {
+        let layout = self.layout;
+        let LineKind::Variable(ref name) = layout.kinds[index] else {
+            self.push(layout.lines[index].clone(), false);
+            return;
+        };
+
+        let is_last = self.last_definitions.get(name.as_str()) == Some(&index);
+        match self.file.variables.get(name) {
+            None => {}
+            Some(value) if layout.variables.get(name) == Some(value) => {
+                self.push(layout.lines[index].clone(), false)
+            }
+            Some(value) if is_last => self.write_line(format!("@{} = {}", name, value)),
+            Some(_) => self.push(layout.lines[index].clone(), false),
+        }
+        if self.last_definitions.values().max() == Some(&index) {
+            self.write_new_variables();
+        }
+    }
+
+    /// Writes a generated line, with the line ending the file uses.
+    fn write_line(&mut self, mut text: String) {
+        if self.layout.crlf {
+            text.push('\r');
+        }
+        self.push(text, true);
+    }
+
+    fn push(&mut self, text: String, generated: bool) {
+        if std::mem::take(&mut self.separate)
+            && !text.trim().is_empty()
+            && self.out.last().is_some_and(|line| !line.trim().is_empty())
+        {
+            let blank = if self.layout.crlf { "\r" } else { "" };
+            self.out.push(blank.to_string());
+        }
+        self.out.push(text);
+        self.ends_generated = generated;
+    }
+}
+
+fn directive_lines(request: &Request, dir: &Path) -> Vec<String> {
+    render(request, dir)
+        .lines()
+        .skip(1)
+        .take_while(|line| line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{Error, ParseError};
+    use crate::http::{Method, Parser};
+    use proptest::prelude::*;
+    use std::path::PathBuf;
+
+    const FILE: &str = "\
+@host = https://api.example.com
+# Shared by every request
+
+### List users
+GET {{host}}/users HTTP/1.1
+Accept: application/json
+// not sent
+X-Trace: 1
+
+### Create user
+# @no-cookie-jar
+POST {{host}}/users
+Content-Type: application/json
+
+{
+  \"name\": \"Ann\"
+}
+
+
+### Notes without a request
+";
+
+    fn parse(content: &str) -> HttpFile {
+        Parser::parse_file_content(Path::new("api.http"), content).unwrap()
+    }
+
+    #[test]
+    fn test_unchanged_file_is_written_back_exactly() {
+        assert_eq!(parse(FILE).to_http(), FILE);
+        let crlf = FILE.replace('\n', "\r\n");
+        assert_eq!(parse(&crlf).to_http(), crlf);
+        assert_eq!(parse("GET /a").to_http(), "GET /a");
+        assert_eq!(parse("").to_http(), "");
+    }
+
+    #[test]
+    fn test_edits_change_only_their_lines() {
+        let mut file = parse(FILE);
+        file.variables
+            .insert("host".into(), "http://localhost".into());
+        file.variables.insert("token".into(), "abc".into());
+        let list = &mut file.requests[0];
+        list.url = "{{host}}/people".to_string();
+        list.headers.remove("X-Trace");
+        list.headers.insert("Accept".into(), "text/csv".into());
+        let create = &mut file.requests[1];
+        create.name = Some("Add user".to_string());
+        create.body = Some("{\"name\": \"Bob\"}".to_string());
+        create
+            .headers
+            .insert("Authorization".into(), "Bearer {{token}}".into());
+
+        assert_eq!(
+            file.to_http(),
+            "\
+@host = http://localhost
+@token = abc
+# Shared by every request
+
+### List users
+GET {{host}}/people HTTP/1.1
+Accept: text/csv
+// not sent
+
+### Add user
+# @no-cookie-jar
+POST {{host}}/users
+Content-Type: application/json
+Authorization: Bearer {{token}}
+
+{\"name\": \"Bob\"}
+
+
+### Notes without a request
+"
+        );
+    }
+
+    #[test]
+    fn test_new_and_deleted_requests() {
+        let mut file = parse(FILE);
+        let copy = file.requests[0].clone().with_name("List users (copy)");
+        file.requests.insert(1, copy);
+        file.requests.remove(2);
+        file.requests
+            .push(Request::new(Method::Delete, "{{host}}/users/1"));
+
+        assert_eq!(
+            file.to_http(),
+            "\
+@host = https://api.example.com
+# Shared by every request
+
+### List users
+GET {{host}}/users HTTP/1.1
+Accept: application/json
+// not sent
+X-Trace: 1
+
+### Notes without a request
+
+### List users (copy)
+GET {{host}}/users
+Accept: application/json
+X-Trace: 1
+
+###
+DELETE {{host}}/users/1
+"
+        );
+    }
+
+    #[test]
+    fn test_request_before_first_separator() {
+        let mut file = parse("@id = 1\nGET /a\n\n### B\nGET /b\n");
+        file.requests.insert(0, Request::new(Method::Get, "/new"));
+        file.requests[1].name = Some("A".to_string());
+        let (requests, variables) = Parser::parse_content(&file.to_http()).unwrap();
+        assert_eq!(requests, file.requests);
+        assert_eq!(variables, file.variables);
+    }
+
+    #[test]
+    fn test_proto_paths_stay_relative() {
+        let mut file = Parser::parse_file_content(
+            Path::new("protos/api.http"),
+            "### Hello\n# @proto greeter.proto\nGRPC localhost:50051/Greeter/SayHello\n",
+        )
+        .unwrap();
+        assert_eq!(
+            file.requests[0].proto_files,
+            [PathBuf::from("protos/greeter.proto")]
+        );
+        file.requests[0].no_cookie_jar = true;
+        assert_eq!(
+            file.to_http(),
+            "### Hello\n# @no-cookie-jar\n# @proto greeter.proto\nGRPC localhost:50051/Greeter/SayHello\n"
+        );
+    }
+
+    #[test]
+    fn test_save_keeps_file_variables() {
+        let dir = std::env::temp_dir().join(format!("poke-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("api.http");
+        std::fs::write(&path, FILE).unwrap();
+
+        let mut file = Parser::parse_file(&path).unwrap();
+        file.variables
+            .insert("token".to_string(), "from-environment".to_string());
+        file.requests[0].url = "{{host}}/people".to_string();
+        let copy = file.requests[0].clone();
+        file.requests.push(copy);
+        file.save().unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+
+        file.requests[1].url = "{{host}}/members".to_string();
+        file.save().unwrap();
+        let rewritten = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(!written.contains("from-environment"));
+        assert!(
+            written.starts_with("@host = https://api.example.com\n# Shared by every request\n")
+        );
+        assert!(
+            written
+                .contains("GET {{host}}/people HTTP/1.1\nAccept: application/json\n// not sent\n")
+        );
+        assert_eq!(written.matches("### List users").count(), 2);
+        assert_eq!(
+            rewritten,
+            written.replace("POST {{host}}/users", "POST {{host}}/members")
+        );
+    }
+
+    #[test]
+    fn test_save_refuses_file_changed_on_disk() {
+        let dir = std::env::temp_dir().join(format!("poke-changed-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("api.http");
+        std::fs::write(&path, FILE).unwrap();
+
+        let mut file = Parser::parse_file(&path).unwrap();
+        assert_eq!(file.layout.as_ref().unwrap().text(), FILE);
+        let changed = FILE.replace("/users", "/accounts");
+        std::fs::write(&path, &changed).unwrap();
+        file.requests[0].url = "{{host}}/people".to_string();
+        let err = file.save().unwrap_err();
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(matches!(err, Error::Parse(ParseError::ChangedOnDisk(_))));
+        assert_eq!(on_disk, changed);
+    }
+
+    #[test]
+    fn test_reload_keeps_environment_variables() {
+        let dir = std::env::temp_dir().join(format!("poke-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("api.http");
+        std::fs::write(&path, FILE).unwrap();
+
+        let mut file = Parser::parse_file(&path).unwrap();
+        assert_eq!(file.request_line(0), Some(5));
+        assert_eq!(file.request_line(1), Some(12));
+        assert_eq!(file.request_line(2), None);
+        file.variables
+            .insert("token".to_string(), "from-environment".to_string());
+
+        std::fs::write(
+            &path,
+            FILE.replace(
+                "@host = https://api.example.com",
+                "@host = http://localhost\n",
+            ),
+        )
+        .unwrap();
+        file.reload().unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(file.variables["host"], "http://localhost");
+        assert_eq!(file.variables["token"], "from-environment");
+        assert_eq!(file.request_line(0), Some(6));
+    }
+
+    #[test]
+    fn test_appended_request_can_be_saved() {
+        let dir = std::env::temp_dir().join(format!("poke-append-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("api.http");
+        std::fs::write(&path, FILE).unwrap();
+
+        let mut file = Parser::parse_file(&path).unwrap();
+        file.variables
+            .insert("token".to_string(), "from-environment".to_string());
+        file.append_request(Request::new(Method::Delete, "{{host}}/users/1"))
+            .unwrap();
+        assert_eq!(file.requests.len(), 3);
+        assert_eq!(file.request_line(2), Some(23));
+        assert_eq!(file.variables["token"], "from-environment");
+
+        file.requests[2].url = "{{host}}/users/2".to_string();
+        file.save().unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(written.starts_with(FILE));
+        assert!(written.ends_with("\nDELETE {{host}}/users/2\n"));
+    }
+
+    fn name() -> impl Strategy<Value = String> {
+        "[A-Za-z][A-Za-z0-9 ]{0,10}[A-Za-z0-9]"
+    }
+
+    fn value() -> impl Strategy<Value = String> {
+        "[a-zA-Z0-9{}/:.=;-]{0,12}"
+    }
+
+    fn method() -> impl Strategy<Value = Method> {
+        prop::sample::select(vec![
+            Method::Get,
+            Method::Post,
+            Method::Put,
+            Method::Delete,
+            Method::WebSocket,
+        ])
+    }
+
+    fn body() -> impl Strategy<Value = String> {
+        prop::collection::vec("[a-z0-9{}\":,]([a-z0-9{}\":, ]{0,10}[a-z0-9{}\":,])?", 1..4)
+            .prop_map(|lines| lines.join("\n"))
+    }
+
+    fn header() -> impl Strategy<Value = (String, String)> {
+        ("[A-Z][a-zA-Z-]{0,8}", value())
+    }
+
+    /// A request section as text, with the odd comment, blank line and variable.
+    fn section() -> impl Strategy<Value = String> {
+        (
+            prop::option::of(name()),
+            any::<bool>(),
+            prop::option::of("[a-z][a-z ]{0,10}"),
+            method(),
+            "[a-z{}/:.]{1,20}",
+            any::<bool>(),
+            prop::collection::vec(header(), 0..4),
+            prop::option::of(body()),
+            prop::option::of(("[a-z][a-zA-Z0-9_]{0,6}", value())),
+            0..3usize,
+        )
+            .prop_map(
+                |(
+                    name,
+                    no_cookie_jar,
+                    comment,
+                    method,
+                    url,
+                    version,
+                    headers,
+                    body,
+                    variable,
+                    blanks,
+                )| {
+                    let mut text = match name {
+                        Some(name) => format!("### {}\n", name),
+                        None => "###\n".to_string(),
+                    };
+                    if let Some(comment) = comment {
+                        text.push_str(&format!("# {}\n", comment));
+                    }
+                    if no_cookie_jar {
+                        text.push_str("# @no-cookie-jar\n");
+                    }
+                    text.push_str(&format!("{} {}", method, url));
+                    text.push_str(if version { " HTTP/1.1\n" } else { "\n" });
+                    for (key, value) in headers {
+                        text.push_str(&format!("{}: {}\n", key, value));
+                    }
+                    if let Some(body) = body {
+                        text.push_str(&format!("\n{}\n", body));
+                    }
+                    if let Some((name, value)) = variable {
+                        text.push_str(&format!("@{} = {}\n", name, value));
+                    }
+                    text.push_str(&"\n".repeat(blanks));
+                    text
+                },
+            )
+    }
+
+    fn http_file() -> impl Strategy<Value = String> {
+        (
+            prop::collection::vec(("[a-z][a-zA-Z0-9_]{0,6}", value()), 0..3),
+            prop::option::of((method(), "[a-z/]{1,10}")),
+            prop::collection::vec(section(), 0..5),
+        )
+            .prop_map(|(variables, first, sections)| {
+                let mut text: String = variables
+                    .into_iter()
+                    .map(|(name, value)| format!("@{}={}\n", name, value))
+                    .collect();
+                if let Some((method, url)) = first {
+                    text.push_str(&format!("# before any separator\n{} {}\n\n", method, url));
+                }
+                text + &sections.concat()
+            })
+    }
+
+    #[derive(Debug, Clone)]
+    enum Edit {
+        Name(usize, Option<String>),
+        Method(usize, Method),
+        Url(usize, String),
+        SetHeader(usize, String, String),
+        RemoveHeader(usize, usize),
+        Body(usize, Option<String>),
+        NoCookieJar(usize, bool),
+        Duplicate(usize),
+        Delete(usize),
+        Insert(usize, Method, String),
+        SetVariable(String, String),
+        RemoveVariable(usize),
+    }
+
+    fn edit() -> impl Strategy<Value = Edit> {
+        prop_oneof![
+            (any::<usize>(), prop::option::of(name())).prop_map(|(i, n)| Edit::Name(i, n)),
+            (any::<usize>(), method()).prop_map(|(i, m)| Edit::Method(i, m)),
+            (any::<usize>(), "[a-z{}/:.]{1,20}").prop_map(|(i, u)| Edit::Url(i, u)),
+            (any::<usize>(), header()).prop_map(|(i, (k, v))| Edit::SetHeader(i, k, v)),
+            (any::<usize>(), any::<usize>()).prop_map(|(i, h)| Edit::RemoveHeader(i, h)),
+            (any::<usize>(), prop::option::of(body())).prop_map(|(i, b)| Edit::Body(i, b)),
+            (any::<usize>(), any::<bool>()).prop_map(|(i, b)| Edit::NoCookieJar(i, b)),
+            any::<usize>().prop_map(Edit::Duplicate),
+            any::<usize>().prop_map(Edit::Delete),
+            (any::<usize>(), method(), "[a-z/]{1,10}").prop_map(|(i, m, u)| Edit::Insert(i, m, u)),
+            ("[a-z][a-zA-Z0-9_]{0,6}", value()).prop_map(|(n, v)| Edit::SetVariable(n, v)),
+            any::<usize>().prop_map(Edit::RemoveVariable),
+        ]
+    }
+
+    fn apply(file: &mut HttpFile, edit: Edit) {
+        let len = file.requests.len();
+        if let Edit::Insert(i, method, url) = edit {
+            file.requests
+                .insert(i % (len + 1), Request::new(method, url));
+            return;
+        }
+        if let Edit::SetVariable(name, value) = edit {
+            file.variables.insert(name, value);
+            return;
+        }
+        if let Edit::RemoveVariable(i) = edit {
+            let mut names: Vec<_> = file.variables.keys().cloned().collect();
+            names.sort();
+            if !names.is_empty() {
+                file.variables.remove(&names[i % names.len()]);
+            }
+            return;
+        }
+        if len == 0 {
+            return;
+        }
+        match edit {
+            Edit::Name(i, name) => file.requests[i % len].name = name,
+            Edit::Method(i, method) => file.requests[i % len].method = method,
+            Edit::Url(i, url) => file.requests[i % len].url = url,
+            Edit::SetHeader(i, key, value) => {
+                file.requests[i % len].headers.insert(key, value);
+            }
+            Edit::RemoveHeader(i, h) => {
+                let headers = &mut file.requests[i % len].headers;
+                let mut keys: Vec<_> = headers.keys().cloned().collect();
+                keys.sort();
+                if !keys.is_empty() {
+                    headers.remove(&keys[h % keys.len()]);
+                }
+            }
+            Edit::Body(i, body) => file.requests[i % len].body = body,
+            Edit::NoCookieJar(i, value) => file.requests[i % len].no_cookie_jar = value,
+            Edit::Duplicate(i) => {
+                let copy = file.requests[i % len].clone();
+                file.requests.insert(i % len + 1, copy);
+            }
+            Edit::Delete(i) => {
+                file.requests.remove(i % len);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_any_text_is_written_back_exactly(content in "[\\PC\n\r]{0,300}") {
+            let file = parse(&content);
+            prop_assert_eq!(file.to_http(), content);
+        }
+
+        #[test]
+        fn prop_round_trip(content in http_file()) {
+            let file = parse(&content);
+            let written = file.to_http();
+            prop_assert_eq!(&written, &content);
+            let reparsed = parse(&written);
+            prop_assert_eq!(reparsed.requests, file.requests);
+            prop_assert_eq!(reparsed.variables, file.variables);
+        }
+
+        #[test]
+        fn prop_edits_round_trip(
+            content in http_file(),
+            edits in prop::collection::vec(edit(), 1..6),
+        ) {
+            let mut file = parse(&content);
+            for edit in edits {
+                apply(&mut file, edit);
+            }
+            let reparsed = parse(&file.to_http());
+            prop_assert_eq!(reparsed.requests, file.requests);
+            prop_assert_eq!(reparsed.variables, file.variables);
+        }
+
+        #[test]
+        fn prop_new_files_round_trip(
+            content in http_file(),
+            edits in prop::collection::vec(edit(), 0..6),
+        ) {
+            let mut file = parse(&content);
+            for edit in edits {
+                apply(&mut file, edit);
+            }
+            file.layout = None;
+            let reparsed = parse(&file.to_http());
+            prop_assert_eq!(reparsed.requests, file.requests);
+            prop_assert_eq!(reparsed.variables, file.variables);
+        }
+    }
+}
@@ -0,0 +1,647 @@
+use super::{Auth, Body, Collection, Folder, ImportedEnvironment, Item};
+use crate::error::{ImportError, Result};
+use crate::http::{Method, Request};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+const COLLECTION_FILE: &str = "collection.bru";
+const FOLDER_FILE: &str = "folder.bru";
+const ENVIRONMENTS_DIR: &str = "environments";
+
+/// Blocks holding free text rather than `key: value` lines.
+const TEXT_BLOCKS: &[&str] = &[
+    "body:json",
+    "body:text",
+    "body:xml",
+    "body:sparql",
+    "body:graphql",
+    "body:graphql:vars",
+    "script:pre-request",
+    "script:post-response",
+    "tests",
+    "docs",
+];
+
+#[derive(Debug, Deserialize)]
+struct BrunoConfig {
+    name: String,
+}
+
+/// A parsed `.bru` file: named blocks such as `meta { ... }` or `body:json { ... }`.
+#[derive(Debug, Default)]
+pub struct BruFile {
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug)]
+pub struct Block {
+    pub name: String,
+    /// Lines between the braces, with the two-space indentation removed.
+    pub lines: Vec<String>,
+}
+
+impl BruFile {
+    pub fn parse(content: &str) -> Result<Self> {
+        let header = Regex::new(r"^([\w:-]+)\s*([{\[])\s*$").unwrap();
+        let mut file = BruFile::default();
+        let mut lines = content.lines();
+
+        while let Some(line) = lines.next() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some(captures) = header.captures(line) else {
+                return Err(invalid(format!("unexpected line '{}'", line)));
+            };
+            let close = if &captures[2] == "{" { "}" } else { "]" };
+            let mut block = Block {
+                name: captures[1].to_string(),
+                lines: Vec::new(),
+            };
+            loop {
+                match lines.next() {
+                    Some(line) if line.trim_end() == close => break,
+                    Some(line) => block
+                        .lines
+                        .push(line.strip_prefix("  ").unwrap_or(line).to_string()),
+                    None => return Err(invalid(format!("unterminated block {}", block.name))),
+                }
+            }
+            file.blocks.push(block);
+        }
+        Ok(file)
+    }
+
+    pub fn block(&self, name: &str) -> Option<&Block> {
+        self.blocks.iter().find(|block| block.name == name)
+    }
+
+    /// Enabled `key: value` pairs of a block; disabled ones start with `~`.
+    pub fn pairs(&self, name: &str) -> Vec<(String, String)> {
+        let Some(block) = self.block(name) else {
+            return Vec::new();
+        };
+        block
+            .lines
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('~'))
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect()
+    }
+
+    pub fn value(&self, block: &str, key: &str) -> Option<String> {
+        self.pairs(block)
+            .into_iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// The content of a text block such as `body:json`.
+    pub fn text(&self, name: &str) -> Option<String> {
+        let block = self.block(name)?;
+        let text = block.lines.join("\n");
+        let text = text.trim_matches('\n');
+        (!text.trim().is_empty()).then(|| text.to_string())
+    }
+
+    /// Items of a list block such as `vars:secret [ token, apiKey ]`.
+    pub fn list(&self, name: &str) -> Vec<String> {
+        let Some(block) = self.block(name) else {
+            return Vec::new();
+        };
+        block
+            .lines
+            .iter()
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|item| !item.is_empty() && !item.starts_with('~'))
+            .map(String::from)
+            .collect()
+    }
+
+    fn is_text_block(name: &str) -> bool {
+        TEXT_BLOCKS.contains(&name)
+    }
+}
+
+/// Loads a Bruno collection directory: `bruno.json`, folders of `.bru` requests and
+/// the environments in `environments/`.
+pub fn load(dir: &Path) -> Result<Collection> {
+    let config = fs::read_to_string(dir.join("bruno.json"))
+        .map_err(|err| invalid(format!("{}: {}", dir.join("bruno.json").display(), err)))?;
+    let config: BrunoConfig =
+        serde_json::from_str(&config).map_err(|err| invalid(format!("bruno.json: {}", err)))?;
+
+    let mut collection = Collection::new(config.name);
+    let root = read_optional(&dir.join(COLLECTION_FILE))?;
+    let mut loader = Loader {
+        warnings: Vec::new(),
+    };
+    if let Some(ref root) = root {
+        loader.check_unsupported(&collection.name, root);
+    }
+    collection.items = loader.folder(dir, "", &Inherited::default().with(root.as_ref()))?;
+
+    let environments_dir = dir.join(ENVIRONMENTS_DIR);
+    if environments_dir.is_dir() {
+        for path in sorted_entries(&environments_dir)? {
+            if path.extension().is_some_and(|ext| ext == "bru") {
+                collection.environments.push(loader.environment(&path)?);
+            }
+        }
+    }
+
+    collection.warnings = loader.warnings;
+    Ok(collection)
+}
+
+/// Headers and auth that folders pass down to their requests.
+#[derive(Debug, Default, Clone)]
+struct Inherited {
+    headers: Vec<(String, String)>,
+    auth: Option<(String, Vec<(String, String)>)>,
+}
+
+impl Inherited {
+    /// Adds the headers of a `collection.bru` or `folder.bru`, and its auth unless it
+    /// inherits as well.
+    fn with(&self, file: Option<&BruFile>) -> Self {
+        let mut inherited = self.clone();
+        let Some(file) = file else {
+            return inherited;
+        };
+        for (key, value) in file.pairs("headers") {
+            inherited
+                .headers
+                .retain(|(name, _)| !name.eq_ignore_ascii_case(&key));
+            inherited.headers.push((key, value));
+        }
+        if let Some(mode) = file.value("auth", "mode")
+            && mode != "inherit"
+        {
+            inherited.auth = Some((mode.clone(), file.pairs(&format!("auth:{}", mode))));
+        }
+        inherited
+    }
+}
+
+struct Loader {
+    warnings: Vec<String>,
+}
+
+impl Loader {
+    fn folder(&mut self, dir: &Path, prefix: &str, inherited: &Inherited) -> Result<Vec<Item>> {
+        let mut folders: Vec<(i64, String, Folder)> = Vec::new();
+        let mut requests: Vec<(i64, String, Request)> = Vec::new();
+
+        for path in sorted_entries(dir)? {
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if path.is_dir() {
+                if file_name.starts_with('.')
+                    || file_name == "node_modules"
+                    || (prefix.is_empty() && file_name == ENVIRONMENTS_DIR)
+                {
+                    continue;
+                }
+                let folder_file = read_optional(&path.join(FOLDER_FILE))?;
+                let name = folder_file
+                    .as_ref()
+                    .and_then(|file| file.value("meta", "name"))
+                    .unwrap_or(file_name);
+                let seq = folder_file
+                    .as_ref()
+                    .and_then(|file| file.value("meta", "seq"))
+                    .and_then(|seq| seq.parse().ok())
+                    .unwrap_or(i64::MAX);
+                let folder_path = format!("{}{}", prefix, name);
+                if let Some(ref folder_file) = folder_file {
+                    self.check_unsupported(&folder_path, folder_file);
+                }
+
+                let items = self.folder(
+                    &path,
+                    &format!("{} / ", folder_path),
+                    &inherited.with(folder_file.as_ref()),
+                )?;
+                folders.push((seq, name.clone(), Folder { name, items }));
+            } else if path.extension().is_some_and(|ext| ext == "bru")
+                && file_name != COLLECTION_FILE
+                && file_name != FOLDER_FILE
+            {
+                let content = fs::read_to_string(&path)?;
+                let file = BruFile::parse(&content)
+                    .map_err(|err| invalid(format!("{}: {}", path.display(), err)))?;
+                let seq = file
+                    .value("meta", "seq")
+                    .and_then(|seq| seq.parse().ok())
+                    .unwrap_or(i64::MAX);
+                let name = file
+                    .value("meta", "name")
+                    .unwrap_or_else(|| file_name.trim_end_matches(".bru").to_string());
+                if let Some(request) =
+                    self.request(&file, &name, &format!("{}{}", prefix, name), inherited)
+                {
+                    requests.push((seq, name, request));
+                }
+            }
+        }
+
+        folders.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        requests.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        Ok(folders
+            .into_iter()
+            .map(|(_, _, folder)| Item::Folder(folder))
+            .chain(
+                requests
+                    .into_iter()
+                    .map(|(_, _, request)| Item::Request(Box::new(request))),
+            )
+            .collect())
+    }
+
+    fn request(
+        &mut self,
+        file: &BruFile,
+        name: &str,
+        path: &str,
+        inherited: &Inherited,
+    ) -> Option<Request> {
+        if file.value("meta", "type").as_deref() == Some("grpc") {
+            self.warnings.push(format!("{}: gRPC request", path));
+            return None;
+        }
+        let Some((method, block)) = file.blocks.iter().find_map(|block| {
+            block
+                .name
+                .parse::<Method>()
+                .ok()
+                .filter(|method| !matches!(method, Method::WebSocket | Method::Grpc))
+                .map(|method| (method, block.name.as_str()))
+        }) else {
+            self.warnings.push(format!("{}: no supported method", path));
+            return None;
+        };
+        self.check_unsupported(path, file);
+
+        let url = file.value(block, "url").unwrap_or_default();
+        let url = path_params(&url, &file.pairs("params:path"));
+        let mut request = Request::new(method, url).with_name(name);
+        for (key, value) in inherited
+            .headers
+            .iter()
+            .cloned()
+            .chain(file.pairs("headers"))
+        {
+            request
+                .headers
+                .retain(|existing, _| !existing.eq_ignore_ascii_case(&key));
+            request.headers.insert(key, value);
+        }
+
+        if let Some(body) = file
+            .value(block, "body")
+            .and_then(|mode| self.body(path, file, &mode))
+        {
+            body.apply(&mut request);
+        }
+
+        let mode = file
+            .value(block, "auth")
+            .unwrap_or_else(|| "none".to_string());
+        let auth = if mode == "inherit" {
+            inherited.auth.clone()
+        } else {
+            Some((mode.clone(), file.pairs(&format!("auth:{}", mode))))
+        };
+        if let Some((mode, params)) = auth
+            && let Some(auth) = self.auth(path, &mode, &params)
+        {
+            auth.apply(&mut request);
+        }
+
+        if request.url.contains("{{process.env.") {
+            self.warnings
+                .push(format!("{}: process.env variables", path));
+        }
+        Some(request)
+    }
+
+    fn body(&mut self, path: &str, file: &BruFile, mode: &str) -> Option<Body> {
+        let raw = |block: &str, content_type: &str| {
+            Some(Body::Raw {
+                content: file.text(block)?,
+                content_type: Some(content_type.to_string()),
+            })
+        };
+        match mode {
+            "json" => raw("body:json", "application/json"),
+            "text" => raw("body:text", "text/plain"),
+            "xml" => raw("body:xml", "application/xml"),
+            "sparql" => raw("body:sparql", "application/sparql-query"),
+            "formUrlEncoded" => Some(Body::UrlEncoded(file.pairs("body:form-urlencoded"))),
+            "multipartForm" => {
+                let mut fields = file.pairs("body:multipart-form");
+                fields.retain(|(key, value)| {
+                    let is_file = value.starts_with("@file(");
+                    if is_file {
+                        self.warnings
+                            .push(format!("{}: file form field {}", path, key));
+                    }
+                    !is_file
+                });
+                Some(Body::Multipart(fields))
+            }
+            "graphql" => {
+                let variables = match file.text("body:graphql:vars") {
+                    Some(variables) => match serde_json::from_str(&variables) {
+                        Ok(variables) => Some(variables),
+                        Err(_) => {
+                            self.warnings
+                                .push(format!("{}: GraphQL variables are not valid JSON", path));
+                            None
+                        }
+                    },
+                    None => None,
+                };
+                Some(Body::GraphQl {
+                    query: file.text("body:graphql")?,
+                    variables,
+                })
+            }
+            "none" => None,
+            mode => {
+                self.warnings.push(format!("{}: {} body", path, mode));
+                None
+            }
+        }
+    }
+
+    fn auth(&mut self, path: &str, mode: &str, params: &[(String, String)]) -> Option<Auth> {
+        let param = |key: &str| {
+            params
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.clone())
+                .filter(|value| !value.is_empty())
+        };
+        let required = |key: &str| param(key).unwrap_or_default();
+
+        Some(match mode {
+            "basic" => Auth::Basic {
+                username: required("username"),
+                password: required("password"),
+            },
+            "digest" => Auth::Digest {
+                username: required("username"),
+                password: required("password"),
+            },
+            "bearer" => Auth::Bearer {
+                token: required("token"),
+            },
+            "awsv4" => Auth::Aws {
+                access_key: required("accessKeyId"),
+                secret_key: required("secretAccessKey"),
+                session_token: param("sessionToken"),
+                region: param("region"),
+                service: param("service"),
+            },
+            "apikey" => Auth::ApiKey {
+                name: required("key"),
+                value: required("value"),
+                in_query: param("placement").as_deref() == Some("queryparams"),
+            },
+            "none" => return None,
+            mode => {
+                self.warnings.push(format!("{}: {} auth", path, mode));
+                return None;
+            }
+        })
+    }
+
+    fn environment(&mut self, path: &Path) -> Result<ImportedEnvironment> {
+        let file = BruFile::parse(&fs::read_to_string(path)?)
+            .map_err(|err| invalid(format!("{}: {}", path.display(), err)))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let secrets: Vec<(String, String)> = file
+            .list("vars:secret")
+            .into_iter()
+            .map(|key| (key, String::new()))
+            .collect();
+        if !secrets.is_empty() {
+            let keys: Vec<&str> = secrets.iter().map(|(key, _)| key.as_str()).collect();
+            self.warnings.push(format!(
+                "environment {}: values of secrets {} (added empty to the private env file)",
+                name,
+                keys.join(", ")
+            ));
+        }
+        Ok(ImportedEnvironment {
+            name,
+            variables: file.pairs("vars"),
+            secrets,
+        })
+    }
+
+    /// Reports the blocks that have no .http equivalent.
+    fn check_unsupported(&mut self, path: &str, file: &BruFile) {
+        for block in &file.blocks {
+            let what = match block.name.as_str() {
+                "script:pre-request" => "pre-request script",
+                "script:post-response" => "post-response script",
+                "tests" => "tests",
+                "assert" => "assertions",
+                "vars:pre-request" => "pre-request variables",
+                "vars:post-response" => "post-response variables",
+                _ => continue,
+            };
+            let has_content = if BruFile::is_text_block(&block.name) {
+                file.text(&block.name).is_some()
+            } else {
+                !file.pairs(&block.name).is_empty()
+            };
+            if has_content {
+                self.warnings.push(format!("{}: {}", path, what));
+            }
+        }
+    }
+}
+
+/// Replaces `:name` path segments with the values from `params:path`, or a
+/// `{{name}}` variable without one.
+fn path_params(url: &str, params: &[(String, String)]) -> String {
+    let (path, query) = match url.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (url, None),
+    };
+    let segment = Regex::new(r"/:(\w+)").unwrap();
+    let path = segment.replace_all(path, |captures: &Captures| {
+        let value = params
+            .iter()
+            .find(|(name, _)| *name == captures[1])
+            .map(|(_, value)| value.clone())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| format!("{{{{{}}}}}", &captures[1]));
+        format!("/{}", value)
+    });
+    match query {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.into_owned(),
+    }
+}
+
+fn read_optional(path: &Path) -> Result<Option<BruFile>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    BruFile::parse(&content)
+        .map(Some)
+        .map_err(|err| invalid(format!("{}: {}", path.display(), err)))
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+fn invalid(message: String) -> crate::Error {
+    ImportError::InvalidFile("Bruno", message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUEST: &str = r#"meta {
+  name: Create user
+  type: http
+  seq: 2
+}
+
+post {
+  url: {{baseUrl}}/orgs/:org/users
+  body: json
+  auth: inherit
+}
+
+params:path {
+  org: acme
+}
+
+headers {
+  Accept: application/json
+  ~X-Debug: 1
+}
+
+body:json {
+  {
+    "name": "Ann"
+  }
+}
+
+script:pre-request {
+  bru.setVar("x", 1);
+}
+"#;
+
+    #[test]
+    fn test_parse_bru_file() {
+        let file = BruFile::parse(REQUEST).unwrap();
+        assert_eq!(file.value("meta", "name").as_deref(), Some("Create user"));
+        assert_eq!(file.pairs("headers").len(), 1);
+        assert_eq!(
+            file.text("body:json").as_deref(),
+            Some("{\n  \"name\": \"Ann\"\n}")
+        );
+        assert!(BruFile::parse("meta {\n  name: x\n").is_err());
+    }
+
+    #[test]
+    fn test_load_collection() {
+        let dir = std::env::temp_dir().join(format!("poke-bruno-{}", std::process::id()));
+        let write = |path: &str, content: &str| {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "bruno.json",
+            r#"{ "version": "1", "name": "Accounts", "type": "collection" }"#,
+        );
+        write(
+            "collection.bru",
+            "headers {\n  X-Client: poke\n}\n\nauth {\n  mode: bearer\n}\n\nauth:bearer {\n  token: {{token}}\n}\n",
+        );
+        write(
+            "health.bru",
+            "meta {\n  name: Health\n  seq: 1\n}\n\nget {\n  url: {{baseUrl}}/health\n  body: none\n  auth: none\n}\n",
+        );
+        write("users/create.bru", REQUEST);
+        write(
+            "users/upload.bru",
+            "meta {\n  name: Upload\n  seq: 1\n}\n\nput {\n  url: {{baseUrl}}/avatar\n  body: multipartForm\n  auth: apikey\n}\n\nauth:apikey {\n  key: X-Key\n  value: {{apiKey}}\n  placement: header\n}\n\nbody:multipart-form {\n  caption: me\n  image: @file(me.png)\n}\n",
+        );
+        write(
+            "environments/Local.bru",
+            "vars {\n  baseUrl: http://localhost:3000\n  ~old: 1\n}\nvars:secret [\n  token\n]\n",
+        );
+
+        let collection = load(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(collection.name, "Accounts");
+        let file = collection.http_file(Path::new("accounts.http"));
+        let names: Vec<_> = file
+            .requests
+            .iter()
+            .map(|r| r.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["users / Upload", "users / Create user", "Health"]);
+
+        let upload = &file.requests[0];
+        assert_eq!(upload.method, Method::Put);
+        assert_eq!(upload.headers["X-Key"], "{{apiKey}}");
+        assert_eq!(upload.headers["X-Client"], "poke");
+        assert!(!upload.headers.contains_key("Authorization"));
+
+        let create = &file.requests[1];
+        assert_eq!(create.url, "{{baseUrl}}/orgs/acme/users");
+        assert_eq!(create.headers["Authorization"], "Bearer {{token}}");
+        assert_eq!(create.headers["Content-Type"], "application/json");
+        assert_eq!(create.headers.len(), 4);
+
+        let health = &file.requests[2];
+        assert_eq!(health.headers.len(), 1);
+
+        let local = &collection.environments[0];
+        assert_eq!(local.name, "Local");
+        assert_eq!(
+            local.variables,
+            [("baseUrl".to_string(), "http://localhost:3000".to_string())]
+        );
+        assert_eq!(local.secrets, [("token".to_string(), String::new())]);
+
+        assert_eq!(
+            collection.warnings,
+            [
+                "users / Create user: pre-request script",
+                "users / Upload: file form field image",
+                "environment Local: values of secrets token (added empty to the private env file)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,570 @@
+use super::{Auth, Body, Collection, Folder, ImportedEnvironment, Item};
+use crate::error::{ImportError, Result};
+use crate::http::{Method, Request};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// An Insomnia export in the v4 format, as JSON or YAML: a flat list of resources
+/// linked through `parentId`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InsomniaExport {
+    #[serde(rename = "__export_format")]
+    pub export_format: Option<u32>,
+    #[serde(default)]
+    pub resources: Vec<Resource>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    #[serde(rename = "_id")]
+    pub id: String,
+    #[serde(rename = "_type")]
+    pub kind: String,
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub meta_sort_key: Option<f64>,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<Pair>,
+    /// Query parameters, kept apart from the URL.
+    #[serde(default)]
+    pub parameters: Vec<Pair>,
+    #[serde(default)]
+    pub body: Option<InsomniaBody>,
+    #[serde(default)]
+    pub authentication: Option<Authentication>,
+    /// Variables of an environment.
+    #[serde(default)]
+    pub data: Option<Value>,
+    /// Variables of a folder.
+    #[serde(default)]
+    pub environment: Option<Value>,
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pair {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default)]
+    pub disabled: bool,
+    /// `file` for file form fields.
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsomniaBody {
+    pub mime_type: Option<String>,
+    pub text: Option<String>,
+    #[serde(default)]
+    pub params: Vec<Pair>,
+    /// Set when the body is sent from a file.
+    pub file_name: Option<String>,
+}
+
+/// Auth settings; an empty object means the request inherits its folder's.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authentication {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub disabled: bool,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub token: Option<String>,
+    pub prefix: Option<String>,
+    pub key: Option<String>,
+    pub value: Option<String>,
+    pub add_to: Option<String>,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub session_token: Option<String>,
+    pub region: Option<String>,
+    pub service: Option<String>,
+}
+
+impl InsomniaExport {
+    pub fn parse(content: &str) -> Result<Self> {
+        let invalid = |message: String| ImportError::InvalidFile("Insomnia export", message);
+        let export: Self = if content.trim_start().starts_with('{') {
+            serde_json::from_str(content).map_err(|err| invalid(err.to_string()))?
+        } else {
+            serde_yaml_ng::from_str(content).map_err(|err| invalid(err.to_string()))?
+        };
+
+        match export.export_format {
+            Some(4) => Ok(export),
+            _ => Err(invalid("expected the v4 export format".to_string()).into()),
+        }
+    }
+
+    /// Converts the workspace: requests and folders, the base environment as collection
+    /// variables and its sub-environments as environments.
+    pub fn to_collection(&self) -> Collection {
+        let workspaces: Vec<&Resource> = self
+            .resources
+            .iter()
+            .filter(|resource| resource.kind == "workspace")
+            .collect();
+        let mut collection = Collection::new(
+            workspaces
+                .first()
+                .map(|workspace| workspace.name.as_str())
+                .unwrap_or("insomnia"),
+        );
+
+        let mut converter = Converter {
+            export: self,
+            warnings: Vec::new(),
+            variables: Vec::new(),
+        };
+        for workspace in &workspaces {
+            collection
+                .items
+                .extend(converter.children(&workspace.id, "", None));
+
+            for base in self.children_of(&workspace.id, "environment") {
+                converter.add_variables(&base.name, base.data.as_ref());
+                for environment in self.children_of(&base.id, "environment") {
+                    let mut variables = Vec::new();
+                    flatten_data(environment.data.as_ref(), "", &mut variables);
+                    let variables = variables
+                        .into_iter()
+                        .map(|(key, value)| (key, converter.template(&environment.name, &value)))
+                        .collect();
+                    collection.environments.push(if environment.is_private {
+                        ImportedEnvironment {
+                            name: environment.name.clone(),
+                            secrets: variables,
+                            ..Default::default()
+                        }
+                    } else {
+                        ImportedEnvironment {
+                            name: environment.name.clone(),
+                            variables,
+                            ..Default::default()
+                        }
+                    });
+                }
+            }
+        }
+
+        collection.variables = converter.variables;
+        collection.warnings = converter.warnings;
+        collection
+    }
+
+    /// Resources of `kind` under `parent_id`, in the order Insomnia shows them.
+    fn children_of(&self, parent_id: &str, kind: &str) -> Vec<&Resource> {
+        let mut children: Vec<&Resource> = self
+            .resources
+            .iter()
+            .filter(|resource| {
+                resource.parent_id.as_deref() == Some(parent_id)
+                    && (kind.is_empty() || resource.kind == kind)
+            })
+            .collect();
+        children.sort_by(|a, b| {
+            a.meta_sort_key
+                .unwrap_or_default()
+                .total_cmp(&b.meta_sort_key.unwrap_or_default())
+        });
+        children
+    }
+}
+
+struct Converter<'a> {
+    export: &'a InsomniaExport,
+    warnings: Vec<String>,
+    variables: Vec<(String, String)>,
+}
+
+impl Converter<'_> {
+    fn children(
+        &mut self,
+        parent_id: &str,
+        prefix: &str,
+        inherited_auth: Option<&Authentication>,
+    ) -> Vec<Item> {
+        let mut items = Vec::new();
+        for resource in self.export.children_of(parent_id, "") {
+            let path = format!("{}{}", prefix, resource.name);
+            match resource.kind.as_str() {
+                "request_group" => {
+                    self.add_variables(&path, resource.environment.as_ref());
+                    let auth = effective_auth(resource.authentication.as_ref(), inherited_auth);
+                    items.push(Item::Folder(Folder {
+                        name: resource.name.clone(),
+                        items: self.children(&resource.id, &format!("{} / ", path), auth),
+                    }));
+                }
+                "request" | "websocket_request" => {
+                    let request = self.request(resource, &path, inherited_auth);
+                    items.extend(request.map(|request| Item::Request(Box::new(request))));
+                }
+                "grpc_request" => self.warnings.push(format!("{}: gRPC request", path)),
+                _ => {}
+            }
+        }
+        items
+    }
+
+    fn request(
+        &mut self,
+        resource: &Resource,
+        path: &str,
+        inherited_auth: Option<&Authentication>,
+    ) -> Option<Request> {
+        let method = if resource.kind == "websocket_request" {
+            Method::WebSocket
+        } else {
+            let name = resource.method.as_deref().unwrap_or("GET");
+            match name.parse::<Method>() {
+                Ok(method) if !matches!(method, Method::WebSocket | Method::Grpc) => method,
+                _ => {
+                    self.warnings
+                        .push(format!("{}: unsupported method {}", path, name));
+                    return None;
+                }
+            }
+        };
+
+        let mut url = self.template(path, &resource.url);
+        let query: Vec<String> = resource
+            .parameters
+            .iter()
+            .filter(|param| !param.disabled && !param.name.is_empty())
+            .map(|param| format!("{}={}", param.name, self.template(path, &param.value)))
+            .collect();
+        if !query.is_empty() {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            url = format!("{}{}{}", url, separator, query.join("&"));
+        }
+
+        let mut request = Request::new(method, url).with_name(resource.name.clone());
+        for header in resource
+            .headers
+            .iter()
+            .filter(|h| !h.disabled && !h.name.is_empty())
+        {
+            let value = self.template(path, &header.value);
+            request.headers.insert(header.name.clone(), value);
+        }
+
+        if let Some(ref body) = resource.body
+            && let Some(body) = self.body(path, body)
+        {
+            body.apply(&mut request);
+        }
+
+        let auth = effective_auth(resource.authentication.as_ref(), inherited_auth);
+        if let Some(auth) = auth.and_then(|auth| self.auth(path, auth)) {
+            auth.apply(&mut request);
+        }
+        Some(request)
+    }
+
+    fn body(&mut self, path: &str, body: &InsomniaBody) -> Option<Body> {
+        if body
+            .file_name
+            .as_deref()
+            .is_some_and(|name| !name.is_empty())
+        {
+            self.warnings.push(format!("{}: body from a file", path));
+            return None;
+        }
+
+        let mime_type = body.mime_type.clone().unwrap_or_default();
+        let fields = |converter: &mut Self| -> Vec<(String, String)> {
+            body.params
+                .iter()
+                .filter(|param| !param.disabled)
+                .filter_map(|param| {
+                    if param.kind.as_deref() == Some("file") {
+                        converter
+                            .warnings
+                            .push(format!("{}: file form field {}", path, param.name));
+                        return None;
+                    }
+                    Some((param.name.clone(), converter.template(path, &param.value)))
+                })
+                .collect()
+        };
+
+        Some(match mime_type.as_str() {
+            "application/x-www-form-urlencoded" => Body::UrlEncoded(fields(self)),
+            "multipart/form-data" => Body::Multipart(fields(self)),
+            _ => {
+                let text = body.text.as_deref().filter(|text| !text.is_empty())?;
+                let content_type = match mime_type.as_str() {
+                    // GraphQL bodies are stored as the JSON payload that is sent.
+                    "application/graphql" => Some("application/json".to_string()),
+                    "" => None,
+                    other => Some(other.to_string()),
+                };
+                Body::Raw {
+                    content: self.template(path, text),
+                    content_type,
+                }
+            }
+        })
+    }
+
+    fn auth(&mut self, path: &str, auth: &Authentication) -> Option<Auth> {
+        if auth.disabled {
+            return None;
+        }
+        let mut field = |value: &Option<String>| {
+            let value = value.clone().unwrap_or_default();
+            self.template(path, &value)
+        };
+
+        Some(match auth.kind.as_deref()? {
+            "basic" => Auth::Basic {
+                username: field(&auth.username),
+                password: field(&auth.password),
+            },
+            "digest" => Auth::Digest {
+                username: field(&auth.username),
+                password: field(&auth.password),
+            },
+            "bearer" => {
+                let token = field(&auth.token);
+                match auth.prefix.as_deref().filter(|prefix| !prefix.is_empty()) {
+                    Some(prefix) if prefix != "Bearer" => Auth::ApiKey {
+                        name: "Authorization".to_string(),
+                        value: format!("{} {}", prefix, token),
+                        in_query: false,
+                    },
+                    _ => Auth::Bearer { token },
+                }
+            }
+            "apikey" => Auth::ApiKey {
+                name: field(&auth.key),
+                value: field(&auth.value),
+                in_query: auth.add_to.as_deref() == Some("queryParams"),
+            },
+            "iam" => Auth::Aws {
+                access_key: field(&auth.access_key_id),
+                secret_key: field(&auth.secret_access_key),
+                session_token: Some(field(&auth.session_token)),
+                region: Some(field(&auth.region)),
+                service: Some(field(&auth.service)),
+            },
+            "none" => return None,
+            kind => {
+                self.warnings.push(format!("{}: {} auth", path, kind));
+                return None;
+            }
+        })
+    }
+
+    /// Adds base environment or folder variables to the collection variables. The first
+    /// definition of a name wins, since .http files have a single scope.
+    fn add_variables(&mut self, path: &str, data: Option<&Value>) {
+        let mut variables = Vec::new();
+        flatten_data(data, "", &mut variables);
+        for (key, value) in variables {
+            let value = self.template(path, &value);
+            match self.variables.iter().find(|(name, _)| *name == key) {
+                None => self.variables.push((key, value)),
+                Some((_, existing)) if *existing == value => {}
+                Some(_) => self
+                    .warnings
+                    .push(format!("{}: variable {} is already defined", path, key)),
+            }
+        }
+    }
+
+    /// Rewrites `{{ _.name }}` references as `{{name}}`, with nested names joined by
+    /// underscores, and reports template tags such as `{% uuid %}`.
+    fn template(&mut self, path: &str, text: &str) -> String {
+        let reference = Regex::new(r"\{\{\s*(?:_\.)?([\w.]+)\s*\}\}").unwrap();
+        let converted = reference.replace_all(text, |captures: &Captures| {
+            format!("{{{{{}}}}}", captures[1].replace('.', "_"))
+        });
+
+        let tag = Regex::new(r"\{%\s*(\w+)").unwrap();
+        for captures in tag.captures_iter(&converted) {
+            let warning = format!("{}: template tag {}", path, &captures[1]);
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
+        converted.into_owned()
+    }
+}
+
+/// The auth that applies to a request or folder: its own, unless it has none set.
+fn effective_auth<'a>(
+    own: Option<&'a Authentication>,
+    inherited: Option<&'a Authentication>,
+) -> Option<&'a Authentication> {
+    match own {
+        Some(auth) if auth.kind.is_some() => Some(auth),
+        _ => inherited,
+    }
+}
+
+/// Flattens environment data, so `{ "api": { "url": "..." } }` becomes `api_url`.
+fn flatten_data(data: Option<&Value>, prefix: &str, out: &mut Vec<(String, String)>) {
+    let Some(Value::Object(map)) = data else {
+        return;
+    };
+    for (key, value) in map {
+        let name = format!("{}{}", prefix, key);
+        match value {
+            Value::Object(_) => flatten_data(Some(value), &format!("{}_", name), out),
+            Value::String(s) => out.push((name, s.clone())),
+            Value::Null => out.push((name, String::new())),
+            other => out.push((name, other.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "_type": "export",
+        "__export_format": 4,
+        "__export_source": "insomnia.desktop.app:v2023.5.8",
+        "resources": [
+            { "_id": "wrk_1", "_type": "workspace", "parentId": null, "name": "Shop API" },
+            {
+                "_id": "env_base", "_type": "environment", "parentId": "wrk_1",
+                "name": "Base Environment",
+                "data": { "baseUrl": "http://localhost:8080", "api": { "version": "v2" } }
+            },
+            {
+                "_id": "env_dev", "_type": "environment", "parentId": "env_base",
+                "name": "Dev", "data": { "baseUrl": "https://dev.shop.test" }
+            },
+            {
+                "_id": "env_secret", "_type": "environment", "parentId": "env_base",
+                "name": "Secrets", "isPrivate": true, "data": { "token": "t0k3n" }
+            },
+            {
+                "_id": "req_health", "_type": "request", "parentId": "wrk_1", "name": "Health",
+                "method": "GET", "url": "{{ _.baseUrl }}/health", "metaSortKey": -10,
+                "authentication": {}
+            },
+            {
+                "_id": "fld_orders", "_type": "request_group", "parentId": "wrk_1",
+                "name": "Orders", "metaSortKey": -20,
+                "authentication": { "type": "bearer", "token": "{{ _.token }}" }
+            },
+            {
+                "_id": "req_create", "_type": "request", "parentId": "fld_orders",
+                "name": "Create order", "method": "POST", "metaSortKey": 2,
+                "url": "{{ _.baseUrl }}/{{ _.api.version }}/orders",
+                "body": { "mimeType": "application/json", "text": "{\"id\": \"{% uuid 'v4' %}\"}" },
+                "headers": [
+                    { "name": "X-Trace", "value": "1" },
+                    { "name": "X-Off", "value": "1", "disabled": true }
+                ],
+                "authentication": {}
+            },
+            {
+                "_id": "req_search", "_type": "request", "parentId": "fld_orders",
+                "name": "Search", "method": "GET", "metaSortKey": 1,
+                "url": "{{ _.baseUrl }}/orders",
+                "parameters": [
+                    { "name": "q", "value": "{{ _.query }}" },
+                    { "name": "debug", "value": "1", "disabled": true }
+                ],
+                "authentication": { "type": "apikey", "key": "X-Key", "value": "abc", "addTo": "header" }
+            },
+            {
+                "_id": "req_upload", "_type": "request", "parentId": "fld_orders",
+                "name": "Upload", "method": "POST", "metaSortKey": 3, "url": "{{ _.baseUrl }}/files",
+                "body": {
+                    "mimeType": "multipart/form-data",
+                    "params": [
+                        { "name": "title", "value": "Invoice" },
+                        { "name": "file", "type": "file", "fileName": "/tmp/a.pdf" }
+                    ]
+                },
+                "authentication": { "type": "none" }
+            },
+            { "_id": "grpc_1", "_type": "grpc_request", "parentId": "wrk_1", "name": "Ping" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_convert_export() {
+        let collection = InsomniaExport::parse(EXPORT).unwrap().to_collection();
+        assert_eq!(collection.name, "Shop API");
+        assert_eq!(
+            collection.variables,
+            [
+                ("api_version".to_string(), "v2".to_string()),
+                ("baseUrl".to_string(), "http://localhost:8080".to_string()),
+            ]
+        );
+        assert_eq!(collection.environments[0].name, "Dev");
+        assert_eq!(collection.environments[1].secrets[0].0, "token");
+
+        let file = collection.http_file(std::path::Path::new("shop.http"));
+        let names: Vec<_> = file
+            .requests
+            .iter()
+            .map(|r| r.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "Orders / Search",
+                "Orders / Create order",
+                "Orders / Upload",
+                "Health"
+            ]
+        );
+
+        let search = &file.requests[0];
+        assert_eq!(search.url, "{{baseUrl}}/orders?q={{query}}");
+        assert_eq!(search.headers["X-Key"], "abc");
+        assert!(!search.headers.contains_key("Authorization"));
+
+        let create = &file.requests[1];
+        assert_eq!(create.url, "{{baseUrl}}/{{api_version}}/orders");
+        assert_eq!(create.headers.len(), 3);
+        assert_eq!(create.headers["Authorization"], "Bearer {{token}}");
+        assert_eq!(create.headers["Content-Type"], "application/json");
+
+        let upload = &file.requests[2];
+        assert!(!upload.headers.contains_key("Authorization"));
+        assert!(upload.body.as_deref().unwrap().contains("Invoice"));
+
+        assert_eq!(
+            collection.warnings,
+            [
+                "Orders / Create order: template tag uuid",
+                "Orders / Upload: file form field file",
+                "Ping: gRPC request",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_other_formats() {
+        let err =
+            InsomniaExport::parse(r#"{ "__export_format": 3, "resources": [] }"#).unwrap_err();
+        assert!(err.to_string().contains("v4"));
+    }
+}
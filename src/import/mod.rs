@@ -0,0 +1,506 @@
+//! Conversion of collections from other tools into .http and env files.
+//!
+//! Every importer builds a [`Collection`]: requests already converted to [`Request`]s,
+//! arranged in folders, together with variables and environments. How that is laid out
+//! on disk is decided here, the same way for every source.
+
+pub mod bruno;
+pub mod insomnia;
+pub mod openapi;
+pub mod postman;
+
+use crate::curl::url_encode;
+use crate::environment::{ENV_FILE, Environment, PRIVATE_ENV_FILE, SHARED_ENV};
+use crate::error::{ImportError, Result};
+use crate::http::{HttpFile, Request};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MULTIPART_BOUNDARY: &str = "----PokeFormBoundary";
+
+/// Requests, folders, variables and environments converted from another tool.
+#[derive(Debug, Default)]
+pub struct Collection {
+    pub name: String,
+    /// Collection-wide variables.
+    pub variables: Vec<(String, String)>,
+    pub items: Vec<Item>,
+    pub environments: Vec<ImportedEnvironment>,
+    /// Everything that could not be converted, prefixed with where it was found.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum Item {
+    Request(Box<Request>),
+    Folder(Folder),
+}
+
+#[derive(Debug, Default)]
+pub struct Folder {
+    pub name: String,
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportedEnvironment {
+    pub name: String,
+    pub variables: Vec<(String, String)>,
+    /// Values marked secret, written to the private env file.
+    pub secrets: Vec<(String, String)>,
+}
+
+/// Credentials, mapped onto the `Authorization` shorthands the client understands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Auth {
+    Basic {
+        username: String,
+        password: String,
+    },
+    Digest {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    Aws {
+        access_key: String,
+        secret_key: String,
+        session_token: Option<String>,
+        region: Option<String>,
+        service: Option<String>,
+    },
+    ApiKey {
+        name: String,
+        value: String,
+        in_query: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Body {
+    Raw {
+        content: String,
+        content_type: Option<String>,
+    },
+    UrlEncoded(Vec<(String, String)>),
+    /// Text fields of a `multipart/form-data` body.
+    Multipart(Vec<(String, String)>),
+    GraphQl {
+        query: String,
+        variables: Option<serde_json::Value>,
+    },
+}
+
+impl Collection {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Every request in one file, named with its folder path, e.g. `Admin / Create pet`.
+    pub fn http_file(&self, path: &Path) -> HttpFile {
+        let mut file = HttpFile::new(path);
+        file.variables = self.file_variables();
+        flatten(&self.items, "", &mut file.requests);
+        file
+    }
+
+    /// One file for the requests at the top level and one per top-level folder, named
+    /// after them. Requests in nested folders get the folder path as a name prefix.
+    /// Paths are file names, relative to the output directory.
+    pub fn http_files(&self) -> Vec<HttpFile> {
+        let mut groups: Vec<(&str, Vec<Request>)> = vec![(&self.name, Vec::new())];
+        for item in &self.items {
+            match item {
+                Item::Request(request) => groups[0].1.push(request.as_ref().clone()),
+                Item::Folder(folder) => {
+                    let mut requests = Vec::new();
+                    flatten(&folder.items, "", &mut requests);
+                    groups.push((&folder.name, requests));
+                }
+            }
+        }
+        if groups[0].1.is_empty() && groups.len() > 1 {
+            groups.remove(0);
+        }
+
+        let variables = self.file_variables();
+        let mut used_stems = HashSet::new();
+        groups
+            .into_iter()
+            .map(|(name, requests)| {
+                let mut stem = file_stem(name);
+                let mut counter = 2;
+                while !used_stems.insert(stem.clone()) {
+                    stem = format!("{}-{}", file_stem(name), counter);
+                    counter += 1;
+                }
+
+                let mut file = HttpFile::new(&PathBuf::from(format!("{}.http", stem)));
+                file.variables = variables.clone();
+                file.requests = requests;
+                file
+            })
+            .collect()
+    }
+
+    /// Collection variables that an environment also defines. File variables would take
+    /// precedence over the environment, so these belong in `$shared` instead.
+    pub fn shared_variables(&self) -> Vec<(String, String)> {
+        let overridden = self.environment_keys();
+        self.variables
+            .iter()
+            .filter(|(key, _)| overridden.contains(key.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Writes [`Collection::http_files`] to `dir` and adds the environments to its env
+    /// files. Nothing is written if one of the .http files already exists.
+    pub fn write(&self, dir: &Path) -> Result<Vec<HttpFile>> {
+        let mut files = self.http_files();
+        for file in &mut files {
+            file.path = dir.join(&file.path);
+            if file.path.exists() {
+                return Err(ImportError::FileExists(file.path.clone()).into());
+            }
+        }
+        fs::create_dir_all(dir)?;
+
+        let shared = self.shared_variables();
+        if !shared.is_empty() {
+            Environment::add_variables(&dir.join(ENV_FILE), SHARED_ENV, &shared)?;
+        }
+        for environment in &self.environments {
+            Environment::add_variables(
+                &dir.join(ENV_FILE),
+                &environment.name,
+                &environment.variables,
+            )?;
+            if !environment.secrets.is_empty() {
+                Environment::add_variables(
+                    &dir.join(PRIVATE_ENV_FILE),
+                    &environment.name,
+                    &environment.secrets,
+                )?;
+            }
+        }
+
+        for file in &files {
+            fs::write(&file.path, file.to_http())?;
+        }
+        Ok(files)
+    }
+
+    fn environment_keys(&self) -> HashSet<&str> {
+        self.environments
+            .iter()
+            .flat_map(|environment| environment.variables.iter().chain(&environment.secrets))
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
+
+    fn file_variables(&self) -> HashMap<String, String> {
+        let overridden = self.environment_keys();
+        self.variables
+            .iter()
+            .filter(|(key, _)| !overridden.contains(key.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Auth {
+    /// Adds the credentials to `request`, unless it already sets them itself.
+    pub fn apply(&self, request: &mut Request) {
+        let authorization = match self {
+            Auth::Basic { username, password } => format!("Basic {}:{}", username, password),
+            Auth::Digest { username, password } => format!("Digest {} {}", username, password),
+            Auth::Bearer { token } => format!("Bearer {}", token),
+            Auth::Aws {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            } => {
+                let mut value = format!("AWS {} {}", access_key, secret_key);
+                for (label, param) in [
+                    ("token", session_token),
+                    ("region", region),
+                    ("service", service),
+                ] {
+                    if let Some(param) = param.as_ref().filter(|param| !param.is_empty()) {
+                        value.push_str(&format!(" {}:{}", label, param));
+                    }
+                }
+                value
+            }
+            Auth::ApiKey {
+                name,
+                value,
+                in_query: true,
+            } => {
+                let separator = if request.url.contains('?') { '&' } else { '?' };
+                request.url = format!(
+                    "{}{}{}={}",
+                    request.url,
+                    separator,
+                    encode_outside_variables(name),
+                    encode_outside_variables(value)
+                );
+                return;
+            }
+            Auth::ApiKey { name, value, .. } => {
+                if !has_header(request, name) {
+                    request.headers.insert(name.clone(), value.clone());
+                }
+                return;
+            }
+        };
+
+        if !has_header(request, "authorization") {
+            request
+                .headers
+                .insert("Authorization".to_string(), authorization);
+        }
+    }
+}
+
+impl Body {
+    /// Sets the body of `request`, with a `Content-Type` unless it already has one.
+    pub fn apply(self, request: &mut Request) {
+        let (content, content_type) = match self {
+            Body::Raw {
+                content,
+                content_type,
+            } => (content, content_type),
+            Body::UrlEncoded(fields) => (
+                fields
+                    .iter()
+                    .map(|(key, value)| {
+                        format!(
+                            "{}={}",
+                            encode_outside_variables(key),
+                            encode_outside_variables(value)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("&"),
+                Some("application/x-www-form-urlencoded".to_string()),
+            ),
+            Body::Multipart(fields) => {
+                if fields.is_empty() {
+                    return;
+                }
+                let mut content = String::new();
+                for (name, value) in fields {
+                    content.push_str(&format!(
+                        "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                        MULTIPART_BOUNDARY, name, value
+                    ));
+                }
+                content.push_str(&format!("--{}--", MULTIPART_BOUNDARY));
+                (
+                    content,
+                    Some(format!(
+                        "multipart/form-data; boundary={}",
+                        MULTIPART_BOUNDARY
+                    )),
+                )
+            }
+            Body::GraphQl { query, variables } => {
+                let mut payload = serde_json::json!({ "query": query });
+                if let Some(variables) = variables {
+                    payload["variables"] = variables;
+                }
+                (
+                    serde_json::to_string_pretty(&payload).expect("JSON value serializes"),
+                    Some("application/json".to_string()),
+                )
+            }
+        };
+
+        if content.is_empty() {
+            return;
+        }
+        if let Some(content_type) = content_type
+            && !has_header(request, "content-type")
+        {
+            request
+                .headers
+                .insert("Content-Type".to_string(), content_type);
+        }
+        request.body = Some(content);
+    }
+}
+
+fn flatten(items: &[Item], prefix: &str, requests: &mut Vec<Request>) {
+    for item in items {
+        match item {
+            Item::Request(request) => {
+                let mut request = request.as_ref().clone();
+                if !prefix.is_empty() {
+                    request.name = Some(format!("{}{}", prefix, request.display_name()));
+                }
+                requests.push(request);
+            }
+            Item::Folder(folder) => {
+                flatten(
+                    &folder.items,
+                    &format!("{}{} / ", prefix, folder.name),
+                    requests,
+                );
+            }
+        }
+    }
+}
+
+/// URL-encodes `text`, leaving `{{variable}}` references intact.
+fn encode_outside_variables(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&url_encode(&rest[..start]));
+        out.push_str(&rest[start..start + end + 2]);
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(&url_encode(rest));
+    out
+}
+
+fn has_header(request: &Request, name: &str) -> bool {
+    request
+        .headers
+        .keys()
+        .any(|key| key.eq_ignore_ascii_case(name))
+}
+
+/// A file name for a collection or folder name: lowercase words joined by dashes.
+fn file_stem(name: &str) -> String {
+    let stem = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    if stem.is_empty() {
+        "collection".to_string()
+    } else {
+        stem
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Method;
+
+    fn request(name: &str) -> Item {
+        Item::Request(Box::new(
+            Request::new(Method::Get, "{{baseUrl}}/").with_name(name),
+        ))
+    }
+
+    #[test]
+    fn test_layout_files_and_variables() {
+        let mut collection = Collection::new("Pet Store");
+        collection.variables = vec![
+            ("baseUrl".to_string(), "https://petstore.test".to_string()),
+            ("token".to_string(), "dev".to_string()),
+        ];
+        collection.items = vec![
+            request("Health"),
+            Item::Folder(Folder {
+                name: "Pets".to_string(),
+                items: vec![
+                    request("List"),
+                    Item::Folder(Folder {
+                        name: "Admin".to_string(),
+                        items: vec![request("Create")],
+                    }),
+                ],
+            }),
+        ];
+        collection.environments = vec![ImportedEnvironment {
+            name: "staging".to_string(),
+            variables: vec![("baseUrl".to_string(), "https://staging".to_string())],
+            secrets: Vec::new(),
+        }];
+
+        let files = collection.http_files();
+        let paths: Vec<_> = files.iter().map(|f| f.path.to_str().unwrap()).collect();
+        assert_eq!(paths, ["pet-store.http", "pets.http"]);
+        let names: Vec<_> = files[1]
+            .requests
+            .iter()
+            .map(|r| r.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["List", "Admin / Create"]);
+        assert_eq!(files[1].variables.len(), 1);
+        assert_eq!(files[1].variables["token"], "dev");
+        assert_eq!(
+            collection.shared_variables(),
+            [("baseUrl".to_string(), "https://petstore.test".to_string())]
+        );
+
+        let single = collection.http_file(Path::new("api.http"));
+        assert_eq!(
+            single.requests[2].name.as_deref(),
+            Some("Pets / Admin / Create")
+        );
+    }
+
+    #[test]
+    fn test_apply_auth_and_body() {
+        let mut request = Request::new(Method::Post, "https://x.io/a?b=1");
+        Auth::ApiKey {
+            name: "api key".to_string(),
+            value: "{{apiKey}}&x=1".to_string(),
+            in_query: true,
+        }
+        .apply(&mut request);
+        Auth::Aws {
+            access_key: "AK".to_string(),
+            secret_key: "SK".to_string(),
+            session_token: None,
+            region: Some("eu-west-1".to_string()),
+            service: Some(String::new()),
+        }
+        .apply(&mut request);
+        Body::UrlEncoded(vec![
+            ("user".to_string(), "{{user}}".to_string()),
+            ("note".to_string(), "a b&c".to_string()),
+        ])
+        .apply(&mut request);
+
+        assert_eq!(request.url, "https://x.io/a?b=1&api+key={{apiKey}}%26x%3D1");
+        assert_eq!(
+            request.headers["Authorization"],
+            "AWS AK SK region:eu-west-1"
+        );
+        assert_eq!(request.body.as_deref(), Some("user={{user}}&note=a+b%26c"));
+        assert_eq!(
+            request.headers["Content-Type"],
+            "application/x-www-form-urlencoded"
+        );
+
+        let mut request = Request::new(Method::Post, "https://x.io/upload");
+        Body::Multipart(vec![("caption".to_string(), "hi".to_string())]).apply(&mut request);
+        assert_eq!(
+            request.body.as_deref(),
+            Some(
+                "------PokeFormBoundary\r\nContent-Disposition: form-data; name=\"caption\"\r\n\r\n\
+                 hi\r\n------PokeFormBoundary--"
+            )
+        );
+    }
+}
@@ -0,0 +1,706 @@
+use super::{Auth, Collection, Item};
+use crate::error::{ImportError, Result};
+use crate::http::{Method, Request};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde_yaml_ng::{Mapping, Value};
+use std::collections::HashSet;
+
+/// How deep example bodies are synthesized before nested objects are left empty.
+const MAX_EXAMPLE_DEPTH: usize = 8;
+
+/// Methods in the order their operations are written for each path.
+const METHODS: [(&str, Method); 7] = [
+    ("get", Method::Get),
+    ("post", Method::Post),
+    ("put", Method::Put),
+    ("patch", Method::Patch),
+    ("delete", Method::Delete),
+    ("head", Method::Head),
+    ("options", Method::Options),
+];
+
+/// An OpenAPI 3.0 or 3.1 specification, reduced to what is needed to write requests.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenApi {
+    pub openapi: Option<String>,
+    #[serde(default)]
+    pub info: Info,
+    #[serde(default)]
+    pub servers: Vec<Server>,
+    #[serde(default)]
+    pub paths: IndexMap<String, PathItem>,
+    #[serde(default)]
+    pub components: Components,
+    #[serde(default)]
+    pub security: Vec<IndexMap<String, Vec<String>>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Info {
+    #[serde(default)]
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Server {
+    pub url: String,
+    #[serde(default)]
+    pub variables: IndexMap<String, ServerVariable>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerVariable {
+    pub default: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Components {
+    #[serde(default)]
+    pub schemas: IndexMap<String, Schema>,
+    #[serde(default)]
+    pub parameters: IndexMap<String, Parameter>,
+    #[serde(default)]
+    pub request_bodies: IndexMap<String, RequestBody>,
+    #[serde(default)]
+    pub security_schemes: IndexMap<String, SecurityScheme>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PathItem {
+    #[serde(default)]
+    pub parameters: Vec<MaybeRef<Parameter>>,
+    #[serde(flatten)]
+    pub operations: IndexMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    pub operation_id: Option<String>,
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub parameters: Vec<MaybeRef<Parameter>>,
+    pub request_body: Option<MaybeRef<RequestBody>>,
+    pub security: Option<Vec<IndexMap<String, Vec<String>>>>,
+}
+
+/// An object, or a `$ref` pointing to one under `components`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MaybeRef<T> {
+    Ref {
+        #[serde(rename = "$ref")]
+        reference: String,
+    },
+    Item(T),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    #[serde(rename = "in")]
+    pub location: String,
+    pub schema: Option<Schema>,
+    pub example: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RequestBody {
+    #[serde(default)]
+    pub content: IndexMap<String, MediaType>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MediaType {
+    pub schema: Option<Schema>,
+    pub example: Option<Value>,
+    #[serde(default)]
+    pub examples: IndexMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    #[serde(rename = "$ref")]
+    pub reference: Option<String>,
+    /// A type name, or in OpenAPI 3.1 a list such as `[string, "null"]`.
+    #[serde(rename = "type")]
+    pub kind: Option<Value>,
+    pub format: Option<String>,
+    #[serde(default)]
+    pub properties: IndexMap<String, Schema>,
+    pub items: Option<Box<Schema>>,
+    #[serde(default)]
+    pub all_of: Vec<Schema>,
+    #[serde(default)]
+    pub one_of: Vec<Schema>,
+    #[serde(default)]
+    pub any_of: Vec<Schema>,
+    pub example: Option<Value>,
+    pub default: Option<Value>,
+    #[serde(default, rename = "enum")]
+    pub enumeration: Vec<Value>,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SecurityScheme {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub scheme: Option<String>,
+    pub name: Option<String>,
+    #[serde(rename = "in")]
+    pub location: Option<String>,
+}
+
+impl OpenApi {
+    /// Parses a specification in JSON or YAML.
+    pub fn parse(content: &str) -> Result<Self> {
+        let invalid = |message: String| ImportError::InvalidFile("OpenAPI", message);
+        let spec: Self = if content.trim_start().starts_with('{') {
+            serde_json::from_str(content).map_err(|err| invalid(err.to_string()))?
+        } else {
+            serde_yaml_ng::from_str(content).map_err(|err| invalid(err.to_string()))?
+        };
+
+        match spec.openapi {
+            Some(ref version) if version.starts_with("3.") => Ok(spec),
+            _ => Err(invalid("expected an OpenAPI 3 specification".to_string()).into()),
+        }
+    }
+
+    /// Converts every operation into a request.
+    ///
+    /// Requests start from `{{baseUrl}}`, set from the first server. Path, query and
+    /// header parameters become variables, declared with their example values.
+    pub fn to_collection(&self) -> Collection {
+        let mut collection = Collection::new(&self.info.title);
+        collection
+            .variables
+            .push(("baseUrl".to_string(), self.base_url()));
+
+        for (route, item) in &self.paths {
+            for (key, operation) in &item.operations {
+                let Some((_, method)) = METHODS.iter().find(|(name, _)| name == key) else {
+                    if key == "trace" {
+                        collection
+                            .warnings
+                            .push(format!("TRACE {}: unsupported method", route));
+                    }
+                    continue;
+                };
+                let operation: Operation = match serde_yaml_ng::from_value(operation.clone()) {
+                    Ok(operation) => operation,
+                    Err(err) => {
+                        collection
+                            .warnings
+                            .push(format!("{} {}: {}", method, route, err));
+                        continue;
+                    }
+                };
+                let request = self.request(
+                    route,
+                    method.clone(),
+                    item,
+                    &operation,
+                    &mut collection.variables,
+                    &mut collection.warnings,
+                );
+                collection.items.push(Item::Request(Box::new(request)));
+            }
+        }
+        collection
+    }
+
+    fn base_url(&self) -> String {
+        let Some(server) = self.servers.first() else {
+            return "http://localhost".to_string();
+        };
+        let mut url = server.url.clone();
+        for (name, variable) in &server.variables {
+            url = url.replace(&format!("{{{}}}", name), &variable.default);
+        }
+        url.trim_end_matches('/').to_string()
+    }
+
+    fn request(
+        &self,
+        route: &str,
+        method: Method,
+        item: &PathItem,
+        operation: &Operation,
+        variables: &mut Vec<(String, String)>,
+        warnings: &mut Vec<String>,
+    ) -> Request {
+        let name = operation
+            .operation_id
+            .clone()
+            .or_else(|| operation.summary.clone())
+            .unwrap_or_else(|| format!("{} {}", method, route));
+
+        // Operation parameters override path-level ones with the same name and location.
+        let mut parameters: Vec<&Parameter> = Vec::new();
+        for parameter in item.parameters.iter().chain(&operation.parameters) {
+            let Some(parameter) = self.resolve(parameter, &self.components.parameters) else {
+                warnings.push(format!("{}: unresolved parameter reference", name));
+                continue;
+            };
+            parameters.retain(|existing| {
+                existing.name != parameter.name || existing.location != parameter.location
+            });
+            parameters.push(parameter);
+        }
+
+        let mut url = format!("{{{{baseUrl}}}}{}", route);
+        let mut query = Vec::new();
+        let mut headers = Vec::new();
+        for parameter in parameters {
+            let variable = variable_name(&parameter.name);
+            let reference = format!("{{{{{}}}}}", variable);
+            match parameter.location.as_str() {
+                "path" => url = url.replace(&format!("{{{}}}", parameter.name), &reference),
+                "query" => query.push(format!("{}={}", parameter.name, reference)),
+                "header" => headers.push((parameter.name.clone(), reference)),
+                _ => {
+                    warnings.push(format!(
+                        "{}: {} parameter {}",
+                        name, parameter.location, parameter.name
+                    ));
+                    continue;
+                }
+            }
+            if !variables.iter().any(|(name, _)| *name == variable) {
+                variables.push((variable, scalar(&self.parameter_example(parameter))));
+            }
+        }
+
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query.join("&"));
+        }
+        let mut request = Request::new(method, url).with_name(name.clone());
+        request.headers.extend(headers);
+
+        if let Some(ref body) = operation.request_body {
+            match self.resolve(body, &self.components.request_bodies) {
+                Some(body) => self.body(&name, body, &mut request, warnings),
+                None => warnings.push(format!("{}: unresolved request body reference", name)),
+            }
+        }
+
+        let security = operation.security.as_ref().unwrap_or(&self.security);
+        if let Some(requirement) = security.first()
+            && let Some(scheme_name) = requirement.keys().next()
+        {
+            match self.components.security_schemes.get(scheme_name) {
+                Some(scheme) => apply_security(&name, scheme, &mut request, warnings),
+                None => warnings.push(format!("{}: unknown security scheme {}", name, scheme_name)),
+            }
+        }
+
+        request
+    }
+
+    fn body(
+        &self,
+        name: &str,
+        body: &RequestBody,
+        request: &mut Request,
+        warnings: &mut Vec<String>,
+    ) {
+        let Some((content_type, media)) = body
+            .content
+            .iter()
+            .find(|(content_type, _)| is_json(content_type))
+            .or_else(|| body.content.first())
+        else {
+            return;
+        };
+        request
+            .headers
+            .insert("Content-Type".to_string(), content_type.clone());
+
+        let example = media
+            .example
+            .clone()
+            .or_else(|| {
+                media
+                    .examples
+                    .values()
+                    .find_map(|example| example.get("value").cloned())
+            })
+            .or_else(|| {
+                media
+                    .schema
+                    .as_ref()
+                    .map(|schema| self.example(schema, 0, &mut HashSet::new()))
+            });
+        let Some(example) = example else {
+            return;
+        };
+
+        let content = if is_json(content_type) {
+            serde_json::to_string_pretty(&example).ok()
+        } else if content_type == "application/x-www-form-urlencoded" {
+            example.as_mapping().map(|fields| {
+                fields
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", scalar(key), scalar(value)))
+                    .collect::<Vec<_>>()
+                    .join("&")
+            })
+        } else if let Value::String(text) = example {
+            Some(text)
+        } else {
+            None
+        };
+        match content {
+            Some(content) => request.body = Some(content),
+            None => warnings.push(format!("{}: {} body", name, content_type)),
+        }
+    }
+
+    fn parameter_example(&self, parameter: &Parameter) -> Value {
+        parameter
+            .example
+            .clone()
+            .unwrap_or_else(|| match parameter.schema {
+                Some(ref schema) => self.example(schema, 0, &mut HashSet::new()),
+                None => Value::String(String::new()),
+            })
+    }
+
+    /// Builds an example value from a schema, preferring the values it documents.
+    /// `seen` holds the references being expanded, so recursive schemas terminate.
+    fn example(&self, schema: &Schema, depth: usize, seen: &mut HashSet<String>) -> Value {
+        if let Some(ref reference) = schema.reference {
+            let Some(target) = reference
+                .strip_prefix("#/components/schemas/")
+                .and_then(|name| self.components.schemas.get(name))
+            else {
+                return Value::Null;
+            };
+            if depth >= MAX_EXAMPLE_DEPTH || !seen.insert(reference.clone()) {
+                return Value::Null;
+            }
+            let example = self.example(target, depth + 1, seen);
+            seen.remove(reference);
+            return example;
+        }
+
+        if let Some(example) = schema
+            .example
+            .clone()
+            .or_else(|| schema.default.clone())
+            .or_else(|| schema.enumeration.first().cloned())
+        {
+            return example;
+        }
+
+        if !schema.all_of.is_empty() {
+            let mut merged = Mapping::new();
+            for part in &schema.all_of {
+                match self.example(part, depth + 1, seen) {
+                    Value::Mapping(fields) => merged.extend(fields),
+                    other if schema.all_of.len() == 1 => return other,
+                    _ => {}
+                }
+            }
+            return Value::Mapping(merged);
+        }
+        if let Some(first) = schema.one_of.first().or_else(|| schema.any_of.first()) {
+            return self.example(first, depth + 1, seen);
+        }
+
+        match schema_type(schema) {
+            "object" => {
+                let mut fields = Mapping::new();
+                if depth < MAX_EXAMPLE_DEPTH {
+                    for (name, property) in &schema.properties {
+                        if property.read_only {
+                            continue;
+                        }
+                        fields.insert(
+                            Value::String(name.clone()),
+                            self.example(property, depth + 1, seen),
+                        );
+                    }
+                }
+                Value::Mapping(fields)
+            }
+            "array" => match schema.items {
+                Some(ref items) if depth < MAX_EXAMPLE_DEPTH => {
+                    match self.example(items, depth + 1, seen) {
+                        Value::Null => Value::Sequence(Vec::new()),
+                        item => Value::Sequence(vec![item]),
+                    }
+                }
+                _ => Value::Sequence(Vec::new()),
+            },
+            "integer" => Value::Number(0.into()),
+            "number" => Value::Number(0.0.into()),
+            "boolean" => Value::Bool(false),
+            _ => Value::String(
+                match schema.format.as_deref() {
+                    Some("date-time") => "2024-01-01T00:00:00Z",
+                    Some("date") => "2024-01-01",
+                    Some("email") => "user@example.com",
+                    Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                    Some("uri") | Some("url") => "https://example.com",
+                    _ => "string",
+                }
+                .to_string(),
+            ),
+        }
+    }
+
+    fn resolve<'a, T>(
+        &'a self,
+        item: &'a MaybeRef<T>,
+        components: &'a IndexMap<String, T>,
+    ) -> Option<&'a T> {
+        match item {
+            MaybeRef::Item(item) => Some(item),
+            MaybeRef::Ref { reference } => components.get(reference.rsplit('/').next()?),
+        }
+    }
+}
+
+/// Adds the credentials a security scheme asks for, as variables left to the environment.
+fn apply_security(
+    name: &str,
+    scheme: &SecurityScheme,
+    request: &mut Request,
+    warnings: &mut Vec<String>,
+) {
+    let key_name = scheme.name.clone().unwrap_or_default();
+    let auth = match (scheme.kind.as_str(), scheme.location.as_deref()) {
+        ("http", _) => {
+            let username = "{{username}}".to_string();
+            let password = "{{password}}".to_string();
+            match scheme.scheme.as_deref().map(str::to_lowercase).as_deref() {
+                Some("basic") => Auth::Basic { username, password },
+                Some("digest") => Auth::Digest { username, password },
+                _ => Auth::Bearer {
+                    token: "{{token}}".to_string(),
+                },
+            }
+        }
+        ("oauth2" | "openIdConnect", _) => Auth::Bearer {
+            token: "{{token}}".to_string(),
+        },
+        ("apiKey", Some(location @ ("header" | "query"))) => Auth::ApiKey {
+            name: key_name,
+            value: "{{apiKey}}".to_string(),
+            in_query: location == "query",
+        },
+        ("apiKey", Some("cookie")) => {
+            request
+                .headers
+                .insert("Cookie".to_string(), format!("{}={{{{apiKey}}}}", key_name));
+            return;
+        }
+        (kind, _) => {
+            warnings.push(format!("{}: {} security scheme", name, kind));
+            return;
+        }
+    };
+    auth.apply(request);
+}
+
+fn schema_type(schema: &Schema) -> &str {
+    match schema.kind {
+        Some(Value::String(ref kind)) => kind,
+        Some(Value::Sequence(ref kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|kind| *kind != "null")
+            .unwrap_or("string"),
+        _ if !schema.properties.is_empty() => "object",
+        _ if schema.items.is_some() => "array",
+        _ => "string",
+    }
+}
+
+fn is_json(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+/// A value as written in a URL or variable declaration.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    }
+}
+
+/// Variable names may only hold word characters, so `page[size]` becomes `page_size_`.
+fn variable_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const SPEC: &str = r#"
+openapi: 3.0.3
+info:
+  title: Pet Store
+  version: 1.0.0
+servers:
+  - url: https://{env}.petstore.test/v1/
+    variables:
+      env:
+        default: api
+security:
+  - bearerAuth: []
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      parameters:
+        - name: limit
+          in: query
+          schema:
+            type: integer
+            default: 20
+        - $ref: '#/components/parameters/RequestId'
+    post:
+      summary: Create a pet
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        required: true
+        schema:
+          type: string
+          format: uuid
+    delete:
+      security: []
+      responses:
+        '204':
+          description: Deleted
+components:
+  parameters:
+    RequestId:
+      name: X-Request-Id
+      in: header
+      example: abc
+  securitySchemes:
+    bearerAuth:
+      type: http
+      scheme: bearer
+  schemas:
+    Pet:
+      type: object
+      properties:
+        id:
+          type: integer
+          readOnly: true
+        name:
+          type: string
+          example: Rex
+        tags:
+          type: array
+          items:
+            type: string
+        owner:
+          $ref: '#/components/schemas/Owner'
+    Owner:
+      type: object
+      properties:
+        email:
+          type: string
+          format: email
+        pets:
+          type: array
+          items:
+            $ref: '#/components/schemas/Pet'
+"#;
+
+    #[test]
+    fn test_convert_operations() {
+        let spec = OpenApi::parse(SPEC).unwrap();
+        let collection = spec.to_collection();
+        let file = collection.http_file(Path::new("petstore.http"));
+
+        assert!(collection.warnings.is_empty(), "{:?}", collection.warnings);
+        assert_eq!(collection.name, "Pet Store");
+        assert_eq!(file.variables["baseUrl"], "https://api.petstore.test/v1");
+        assert_eq!(file.variables["limit"], "20");
+        assert_eq!(file.variables["X_Request_Id"], "abc");
+        assert_eq!(
+            file.variables["petId"],
+            "00000000-0000-0000-0000-000000000000"
+        );
+
+        let names: Vec<_> = file
+            .requests
+            .iter()
+            .map(|r| r.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["listPets", "Create a pet", "DELETE /pets/{petId}"]);
+
+        let list = &file.requests[0];
+        assert_eq!(list.url, "{{baseUrl}}/pets?limit={{limit}}");
+        assert_eq!(list.headers["X-Request-Id"], "{{X_Request_Id}}");
+        assert_eq!(list.headers["Authorization"], "Bearer {{token}}");
+
+        let create = &file.requests[1];
+        assert_eq!(create.method, Method::Post);
+        assert_eq!(create.headers["Content-Type"], "application/json");
+        assert_eq!(
+            create.body.as_deref(),
+            Some(
+                r#"{
+  "name": "Rex",
+  "tags": [
+    "string"
+  ],
+  "owner": {
+    "email": "user@example.com",
+    "pets": []
+  }
+}"#
+            )
+        );
+
+        let delete = &file.requests[2];
+        assert_eq!(delete.url, "{{baseUrl}}/pets/{{petId}}");
+        assert!(!delete.headers.contains_key("Authorization"));
+    }
+
+    #[test]
+    fn test_parse_json_and_reject_swagger() {
+        let spec =
+            OpenApi::parse(r#"{ "openapi": "3.1.0", "paths": { "/health": { "head": {} } } }"#)
+                .unwrap();
+        let file = spec.to_collection().http_file(Path::new("api.http"));
+        assert_eq!(file.variables["baseUrl"], "http://localhost");
+        assert_eq!(file.requests[0].method, Method::Head);
+
+        let err = OpenApi::parse(r#"{ "swagger": "2.0", "paths": {} }"#).unwrap_err();
+        assert!(err.to_string().contains("OpenAPI 3"));
+    }
+}
@@ -0,0 +1,799 @@
+use super::{Auth, Body, Collection, Folder, ImportedEnvironment, Item};
+use crate::error::{ImportError, Result};
+use crate::http::{Method, Request};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// A Postman collection in the v2.0 or v2.1 format.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostmanCollection {
+    pub info: Info,
+    #[serde(default)]
+    pub item: Vec<PostmanItem>,
+    #[serde(default)]
+    pub variable: Vec<KeyValue>,
+    #[serde(default)]
+    pub auth: Option<PostmanAuth>,
+    #[serde(default)]
+    pub event: Vec<Event>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Info {
+    pub name: String,
+}
+
+/// A folder when `item` is set, otherwise a request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostmanItem {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub item: Option<Vec<PostmanItem>>,
+    #[serde(default)]
+    pub request: Option<ItemRequest>,
+    #[serde(default)]
+    pub auth: Option<PostmanAuth>,
+    #[serde(default)]
+    pub event: Vec<Event>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ItemRequest {
+    Url(String),
+    Full(Box<RequestDefinition>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RequestDefinition {
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub url: Option<Url>,
+    #[serde(default)]
+    pub header: Vec<KeyValue>,
+    #[serde(default)]
+    pub body: Option<PostmanBody>,
+    #[serde(default)]
+    pub auth: Option<PostmanAuth>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Url {
+    Raw(String),
+    Parts(Box<UrlParts>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UrlParts {
+    #[serde(default)]
+    pub raw: Option<String>,
+    #[serde(default)]
+    pub protocol: Option<String>,
+    #[serde(default)]
+    pub host: Option<Segments>,
+    #[serde(default)]
+    pub path: Option<Segments>,
+    #[serde(default)]
+    pub query: Vec<KeyValue>,
+    /// Values of `:name` path variables.
+    #[serde(default)]
+    pub variable: Vec<KeyValue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Segments {
+    Joined(String),
+    Split(Vec<Value>),
+}
+
+/// A header, query parameter, form field or variable.
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyValue {
+    #[serde(default)]
+    pub key: String,
+    #[serde(default)]
+    pub value: Value,
+    #[serde(default)]
+    pub disabled: bool,
+    /// Environment values use `enabled` instead of `disabled`.
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// `text` or `file` for form fields, `secret` for environment values.
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostmanBody {
+    #[serde(default)]
+    pub mode: String,
+    #[serde(default)]
+    pub raw: Option<String>,
+    #[serde(default)]
+    pub urlencoded: Vec<KeyValue>,
+    #[serde(default)]
+    pub formdata: Vec<KeyValue>,
+    #[serde(default)]
+    pub graphql: Option<GraphQl>,
+    #[serde(default)]
+    pub options: Option<Value>,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphQl {
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub variables: Option<String>,
+}
+
+/// Auth settings: `type` names the scheme, and its parameters are stored under a key of
+/// the same name, as `[{ "key", "value" }]` in v2.1 or as an object in v2.0.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostmanAuth {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(flatten)]
+    pub params: serde_json::Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Event {
+    pub listen: String,
+    #[serde(default)]
+    pub script: Option<Script>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Script {
+    #[serde(default)]
+    pub exec: Value,
+}
+
+/// A Postman environment export.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PostmanEnvironment {
+    pub name: String,
+    #[serde(default)]
+    pub values: Vec<KeyValue>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl PostmanCollection {
+    pub fn parse(content: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(content)
+            .map_err(|err| ImportError::InvalidFile("Postman collection", err.to_string()))?;
+        if value.get("info").is_none() {
+            return Err(ImportError::InvalidFile(
+                "Postman collection",
+                "expected a v2.0 or v2.1 collection".to_string(),
+            )
+            .into());
+        }
+        serde_json::from_value(value)
+            .map_err(|err| ImportError::InvalidFile("Postman collection", err.to_string()).into())
+    }
+
+    /// Converts the collection together with the environments exported next to it.
+    pub fn to_collection(&self, environments: &[PostmanEnvironment]) -> Collection {
+        let mut collection = Collection::new(&self.info.name);
+        collection.variables = self
+            .variable
+            .iter()
+            .filter(|variable| variable.is_enabled())
+            .map(|variable| (variable.key.clone(), variable.value_string()))
+            .collect();
+        collection.environments = environments
+            .iter()
+            .map(PostmanEnvironment::to_environment)
+            .collect();
+        collection
+            .warnings
+            .extend(script_warnings(&self.info.name, &self.event));
+        collection.items =
+            convert_items(&self.item, "", self.auth.as_ref(), &mut collection.warnings);
+        collection
+    }
+}
+
+impl PostmanEnvironment {
+    pub fn parse(content: &str) -> Result<Self> {
+        serde_json::from_str(content)
+            .map_err(|err| ImportError::InvalidFile("Postman environment", err.to_string()).into())
+    }
+
+    fn to_environment(&self) -> ImportedEnvironment {
+        let mut environment = ImportedEnvironment {
+            name: self.name.clone(),
+            ..Default::default()
+        };
+        for value in self.values.iter().filter(|v| v.is_enabled()) {
+            let entry = (value.key.clone(), value.value_string());
+            if value.kind.as_deref() == Some("secret") {
+                environment.secrets.push(entry);
+            } else {
+                environment.variables.push(entry);
+            }
+        }
+        environment
+    }
+}
+
+impl KeyValue {
+    fn is_enabled(&self) -> bool {
+        !self.disabled && self.enabled
+    }
+
+    fn value_string(&self) -> String {
+        match self.value {
+            Value::String(ref s) => s.clone(),
+            Value::Null => String::new(),
+            ref other => other.to_string(),
+        }
+    }
+}
+
+impl PostmanAuth {
+    /// Looks up parameter `name` of the auth scheme in either storage format.
+    fn param(&self, name: &str) -> Option<String> {
+        let value = match self.params.get(&self.kind)? {
+            Value::Array(params) => params
+                .iter()
+                .find(|param| param.get("key").and_then(Value::as_str) == Some(name))?
+                .get("value")?,
+            Value::Object(params) => params.get(name)?,
+            _ => return None,
+        };
+        let value = match value {
+            Value::String(s) => s.clone(),
+            Value::Null => return None,
+            other => other.to_string(),
+        };
+        (!value.is_empty()).then_some(value)
+    }
+
+    fn to_auth(&self, name: &str, warnings: &mut Vec<String>) -> Option<Auth> {
+        let param = |key: &str| self.param(key).unwrap_or_default();
+        Some(match self.kind.as_str() {
+            "basic" => Auth::Basic {
+                username: param("username"),
+                password: param("password"),
+            },
+            "digest" => Auth::Digest {
+                username: param("username"),
+                password: param("password"),
+            },
+            "bearer" => Auth::Bearer {
+                token: param("token"),
+            },
+            "awsv4" => Auth::Aws {
+                access_key: param("accessKey"),
+                secret_key: param("secretKey"),
+                session_token: self.param("sessionToken"),
+                region: self.param("region"),
+                service: self.param("service"),
+            },
+            "apikey" => Auth::ApiKey {
+                name: param("key"),
+                value: param("value"),
+                in_query: self.param("in").as_deref() == Some("query"),
+            },
+            "oauth2" => {
+                warnings.push(format!(
+                    "{}: OAuth2 settings (configure them under Security.Auth in the env file)",
+                    name
+                ));
+                Auth::Bearer {
+                    token: self.param("accessToken")?,
+                }
+            }
+            "noauth" => return None,
+            kind => {
+                warnings.push(format!("{}: {} auth", name, kind));
+                return None;
+            }
+        })
+    }
+}
+
+/// Converts folders and requests, with `prefix` naming the folders they are in.
+fn convert_items(
+    items: &[PostmanItem],
+    prefix: &str,
+    inherited_auth: Option<&PostmanAuth>,
+    warnings: &mut Vec<String>,
+) -> Vec<Item> {
+    let mut converted = Vec::new();
+    for item in items {
+        let path = format!("{}{}", prefix, item.name);
+        warnings.extend(script_warnings(&path, &item.event));
+        let auth = effective_auth(item.auth.as_ref(), inherited_auth);
+
+        match item.item {
+            Some(ref children) => converted.push(Item::Folder(Folder {
+                name: item.name.clone(),
+                items: convert_items(children, &format!("{} / ", path), auth, warnings),
+            })),
+            None => {
+                if let Some(request) = convert_request(item, &path, inherited_auth, warnings) {
+                    converted.push(Item::Request(Box::new(request)));
+                }
+            }
+        }
+    }
+    converted
+}
+
+fn convert_request(
+    item: &PostmanItem,
+    path: &str,
+    inherited_auth: Option<&PostmanAuth>,
+    warnings: &mut Vec<String>,
+) -> Option<Request> {
+    let definition = match item.request {
+        Some(ItemRequest::Full(ref definition)) => definition.as_ref().clone(),
+        Some(ItemRequest::Url(ref url)) => RequestDefinition {
+            method: None,
+            url: Some(Url::Raw(url.clone())),
+            header: Vec::new(),
+            body: None,
+            auth: None,
+        },
+        None => {
+            warnings.push(format!("{}: no request", path));
+            return None;
+        }
+    };
+
+    let method_name = definition.method.as_deref().unwrap_or("GET");
+    let method = match method_name.parse::<Method>() {
+        Ok(method) if !matches!(method, Method::WebSocket | Method::Grpc) => method,
+        _ => {
+            warnings.push(format!("{}: unsupported method {}", path, method_name));
+            return None;
+        }
+    };
+
+    let url = definition.url.as_ref().map(url_string).unwrap_or_default();
+    let mut request = Request::new(method, url).with_name(item.name.clone());
+    for header in definition.header.iter().filter(|h| h.is_enabled()) {
+        request
+            .headers
+            .insert(header.key.clone(), header.value_string());
+    }
+
+    if let Some(ref body) = definition.body
+        && !body.disabled
+        && let Some(body) = convert_body(path, body, warnings)
+    {
+        body.apply(&mut request);
+    }
+
+    let own_auth = definition.auth.as_ref().or(item.auth.as_ref());
+    let auth = effective_auth(own_auth, inherited_auth);
+    if let Some(auth) = auth.and_then(|auth| auth.to_auth(path, warnings)) {
+        auth.apply(&mut request);
+    }
+
+    let dynamic = Regex::new(r"\{\{\$(\w+)\}\}").unwrap();
+    let mut texts: Vec<&str> = vec![&request.url];
+    texts.extend(request.headers.values().map(String::as_str));
+    texts.extend(request.body.as_deref());
+    let mut seen = HashSet::new();
+    for captures in texts.iter().flat_map(|text| dynamic.captures_iter(text)) {
+        if seen.insert(captures[1].to_string()) {
+            warnings.push(format!(
+                "{}: dynamic variable {{{{${}}}}}",
+                path, &captures[1]
+            ));
+        }
+    }
+
+    Some(request)
+}
+
+fn convert_body(path: &str, body: &PostmanBody, warnings: &mut Vec<String>) -> Option<Body> {
+    let mut fields = |fields: &[KeyValue]| -> Vec<(String, String)> {
+        fields
+            .iter()
+            .filter(|field| field.is_enabled())
+            .filter(|field| {
+                let is_file = field.kind.as_deref() == Some("file");
+                if is_file {
+                    warnings.push(format!("{}: file form field {}", path, field.key));
+                }
+                !is_file
+            })
+            .map(|field| (field.key.clone(), field.value_string()))
+            .collect()
+    };
+
+    Some(match body.mode.as_str() {
+        "raw" => {
+            let language = body
+                .options
+                .as_ref()
+                .and_then(|options| options.pointer("/raw/language"))
+                .and_then(Value::as_str);
+            let content_type = match language {
+                Some("json") => Some("application/json"),
+                Some("xml") => Some("application/xml"),
+                Some("html") => Some("text/html"),
+                Some("text") => Some("text/plain"),
+                _ => None,
+            };
+            Body::Raw {
+                content: body.raw.clone().unwrap_or_default(),
+                content_type: content_type.map(String::from),
+            }
+        }
+        "urlencoded" => Body::UrlEncoded(fields(&body.urlencoded)),
+        "formdata" => Body::Multipart(fields(&body.formdata)),
+        "graphql" => {
+            let graphql = body.graphql.as_ref()?;
+            let variables = match graphql.variables.as_deref().map(str::trim) {
+                None | Some("") => None,
+                Some(variables) => match serde_json::from_str(variables) {
+                    Ok(variables) => Some(variables),
+                    Err(_) => {
+                        warnings.push(format!("{}: GraphQL variables are not valid JSON", path));
+                        None
+                    }
+                },
+            };
+            Body::GraphQl {
+                query: graphql.query.clone(),
+                variables,
+            }
+        }
+        "" => return None,
+        mode => {
+            warnings.push(format!("{}: {} body", path, mode));
+            return None;
+        }
+    })
+}
+
+/// The auth that applies to an item: its own, unless it inherits from its parent.
+fn effective_auth<'a>(
+    own: Option<&'a PostmanAuth>,
+    inherited: Option<&'a PostmanAuth>,
+) -> Option<&'a PostmanAuth> {
+    match own {
+        Some(auth) if auth.kind == "inherit" => inherited,
+        Some(auth) => Some(auth),
+        None => inherited,
+    }
+}
+
+fn script_warnings(name: &str, events: &[Event]) -> Vec<String> {
+    events
+        .iter()
+        .filter(|event| {
+            event
+                .script
+                .as_ref()
+                .is_some_and(|script| match script.exec {
+                    Value::String(ref line) => !line.trim().is_empty(),
+                    Value::Array(ref lines) => lines
+                        .iter()
+                        .any(|line| line.as_str().is_some_and(|line| !line.trim().is_empty())),
+                    _ => false,
+                })
+        })
+        .map(|event| {
+            let kind = match event.listen.as_str() {
+                "prerequest" => "pre-request script",
+                "test" => "test script",
+                other => other,
+            };
+            format!("{}: {}", name, kind)
+        })
+        .collect()
+}
+
+fn url_string(url: &Url) -> String {
+    let (raw, variables) = match url {
+        Url::Raw(raw) => (raw.clone(), &[][..]),
+        Url::Parts(parts) => {
+            let raw = parts.raw.clone().unwrap_or_else(|| {
+                let join = |segments: &Option<Segments>, separator: &str| match segments {
+                    Some(Segments::Joined(s)) => s.clone(),
+                    Some(Segments::Split(segments)) => segments
+                        .iter()
+                        .map(|segment| match segment {
+                            Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(separator),
+                    None => String::new(),
+                };
+                let mut raw = String::new();
+                if let Some(ref protocol) = parts.protocol {
+                    raw.push_str(&format!("{}://", protocol));
+                }
+                raw.push_str(&join(&parts.host, "."));
+                let path = join(&parts.path, "/");
+                if !path.is_empty() {
+                    raw.push('/');
+                    raw.push_str(&path);
+                }
+                let query: Vec<_> = parts
+                    .query
+                    .iter()
+                    .filter(|param| param.is_enabled())
+                    .map(|param| format!("{}={}", param.key, param.value_string()))
+                    .collect();
+                if !query.is_empty() {
+                    raw.push('?');
+                    raw.push_str(&query.join("&"));
+                }
+                raw
+            });
+            (raw, &parts.variable[..])
+        }
+    };
+
+    // `:name` path segments become their value, or a `{{name}}` variable without one.
+    let (path, query) = match raw.split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query)),
+        None => (raw.clone(), None),
+    };
+    let segment = Regex::new(r"/:(\w+)").unwrap();
+    let path = segment.replace_all(&path, |captures: &regex::Captures| {
+        let value = variables
+            .iter()
+            .find(|variable| variable.key == captures[1])
+            .map(KeyValue::value_string)
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| format!("{{{{{}}}}}", &captures[1]));
+        format!("/{}", value)
+    });
+    match query {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLLECTION: &str = r#"{
+        "info": {
+            "name": "Pet Store",
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        },
+        "auth": {
+            "type": "bearer",
+            "bearer": [{ "key": "token", "value": "{{token}}", "type": "string" }]
+        },
+        "variable": [
+            { "key": "baseUrl", "value": "https://petstore.test" },
+            { "key": "token", "value": "dev-token" }
+        ],
+        "item": [
+            {
+                "name": "Health",
+                "request": { "method": "GET", "url": "{{baseUrl}}/health" }
+            },
+            {
+                "name": "Pets",
+                "auth": {
+                    "type": "basic",
+                    "basic": { "username": "ann", "password": "{{password}}" }
+                },
+                "event": [
+                    { "listen": "prerequest", "script": { "exec": ["pm.variables.set('x', 1)"] } }
+                ],
+                "item": [
+                    {
+                        "name": "Get pet",
+                        "request": {
+                            "method": "GET",
+                            "header": [
+                                { "key": "Accept", "value": "application/json" },
+                                { "key": "X-Debug", "value": "1", "disabled": true }
+                            ],
+                            "url": {
+                                "raw": "{{baseUrl}}/pets/:id?verbose=true",
+                                "variable": [{ "key": "id", "value": "42" }]
+                            }
+                        }
+                    },
+                    {
+                        "name": "Admin",
+                        "item": [
+                            {
+                                "name": "Create pet",
+                                "request": {
+                                    "method": "POST",
+                                    "auth": { "type": "inherit" },
+                                    "url": "{{baseUrl}}/pets",
+                                    "body": {
+                                        "mode": "raw",
+                                        "raw": "{\"name\": \"Rex\", \"id\": \"{{$guid}}\"}",
+                                        "options": { "raw": { "language": "json" } }
+                                    }
+                                }
+                            },
+                            {
+                                "name": "Upload photo",
+                                "request": {
+                                    "method": "POST",
+                                    "auth": { "type": "noauth" },
+                                    "url": "{{baseUrl}}/pets/:petId/photo",
+                                    "body": {
+                                        "mode": "formdata",
+                                        "formdata": [
+                                            { "key": "caption", "value": "at the beach", "type": "text" },
+                                            { "key": "photo", "src": "rex.jpg", "type": "file" }
+                                        ]
+                                    }
+                                }
+                            }
+                        ]
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_convert_collection() {
+        let environment = PostmanEnvironment::parse(
+            r#"{
+                "name": "Staging",
+                "values": [
+                    { "key": "baseUrl", "value": "https://staging.petstore.test", "enabled": true },
+                    { "key": "password", "value": "hunter2", "type": "secret", "enabled": true },
+                    { "key": "old", "value": "x", "enabled": false }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let collection = PostmanCollection::parse(COLLECTION)
+            .unwrap()
+            .to_collection(&[environment]);
+        let files = collection.http_files();
+
+        let names: Vec<_> = files
+            .iter()
+            .map(|file| file.path.to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["pet-store.http", "pets.http"]);
+
+        let root = &files[0];
+        assert_eq!(root.variables.len(), 1);
+        assert_eq!(root.variables["token"], "dev-token");
+        assert_eq!(
+            root.requests[0].headers["Authorization"],
+            "Bearer {{token}}"
+        );
+
+        let pets = &files[1].requests;
+        let names: Vec<_> = pets.iter().map(|r| r.name.as_deref().unwrap()).collect();
+        assert_eq!(
+            names,
+            ["Get pet", "Admin / Create pet", "Admin / Upload photo"]
+        );
+        assert_eq!(pets[0].url, "{{baseUrl}}/pets/42?verbose=true");
+        assert_eq!(pets[0].headers.len(), 2);
+        assert_eq!(pets[0].headers["Authorization"], "Basic ann:{{password}}");
+        assert_eq!(pets[1].method, Method::Post);
+        assert_eq!(pets[1].headers["Authorization"], "Basic ann:{{password}}");
+        assert_eq!(pets[1].headers["Content-Type"], "application/json");
+        assert_eq!(pets[2].url, "{{baseUrl}}/pets/{{petId}}/photo");
+        assert!(!pets[2].headers.contains_key("Authorization"));
+        assert_eq!(
+            pets[2].body.as_deref(),
+            Some(
+                "------PokeFormBoundary\r\nContent-Disposition: form-data; name=\"caption\"\r\n\r\n\
+                 at the beach\r\n------PokeFormBoundary--"
+            )
+        );
+
+        assert_eq!(
+            collection.shared_variables(),
+            [("baseUrl".to_string(), "https://petstore.test".to_string())]
+        );
+        let staging = &collection.environments[0];
+        assert_eq!(staging.name, "Staging");
+        assert_eq!(staging.variables.len(), 1);
+        assert_eq!(staging.secrets[0].0, "password");
+
+        assert_eq!(
+            collection.warnings,
+            [
+                "Pets: pre-request script",
+                "Pets / Admin / Create pet: dynamic variable {{$guid}}",
+                "Pets / Admin / Upload photo: file form field photo",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_bodies() {
+        let collection = PostmanCollection::parse(
+            r#"{
+                "info": { "name": "Bodies" },
+                "item": [
+                    {
+                        "name": "Login",
+                        "request": {
+                            "method": "POST",
+                            "url": { "protocol": "https", "host": ["api", "test"], "path": ["login"] },
+                            "body": {
+                                "mode": "urlencoded",
+                                "urlencoded": [
+                                    { "key": "user", "value": "{{user}}" },
+                                    { "key": "password", "value": "a b&c" }
+                                ]
+                            }
+                        }
+                    },
+                    {
+                        "name": "Query",
+                        "request": {
+                            "method": "POST",
+                            "url": "https://api.test/graphql",
+                            "auth": {
+                                "type": "apikey",
+                                "apikey": [
+                                    { "key": "key", "value": "api_key" },
+                                    { "key": "value", "value": "{{apiKey}}" },
+                                    { "key": "in", "value": "query" }
+                                ]
+                            },
+                            "body": {
+                                "mode": "graphql",
+                                "graphql": { "query": "{ me { id } }", "variables": "{\"a\": 1}" }
+                            }
+                        }
+                    },
+                    { "name": "Copy", "request": { "method": "COPY", "url": "https://api.test" } }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let collection = collection.to_collection(&[]);
+        let requests = &collection.http_files()[0].requests;
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].url, "https://api.test/login");
+        assert_eq!(
+            requests[0].body.as_deref(),
+            Some("user={{user}}&password=a+b%26c")
+        );
+        assert_eq!(
+            requests[0].headers["Content-Type"],
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(
+            requests[1].url,
+            "https://api.test/graphql?api_key={{apiKey}}"
+        );
+        let body: Value = serde_json::from_str(requests[1].body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["variables"]["a"], 1);
+        assert_eq!(collection.warnings, ["Copy: unsupported method COPY"]);
+    }
+
+    #[test]
+    fn test_parse_v1_collection() {
+        let err = PostmanCollection::parse(r#"{ "id": "x", "name": "Old", "requests": [] }"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("v2.0 or v2.1"));
+    }
+}
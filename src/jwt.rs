@@ -0,0 +1,207 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, TimeDelta, Utc};
+use regex::Regex;
+use serde_json::Value;
+
+/// A decoded JSON Web Token. The signature is not verified.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jwt {
+    pub raw: String,
+    pub header: Value,
+    pub payload: Value,
+}
+
+impl Jwt {
+    /// Decodes the header and payload of a compact JWT, or returns `None` if `token`
+    /// is not one.
+    pub fn decode(token: &str) -> Option<Self> {
+        let token = token.trim();
+        let mut parts = token.split('.');
+        let (header, payload, _signature) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            raw: token.to_string(),
+            header: decode_part(header)?,
+            payload: decode_part(payload)?,
+        })
+    }
+
+    /// Finds every JWT in `text`, such as a header value or a response body.
+    pub fn find(text: &str) -> Vec<Self> {
+        let re = Regex::new(r"eyJ[A-Za-z0-9_-]*\.eyJ[A-Za-z0-9_-]*\.[A-Za-z0-9_-]*").unwrap();
+        re.find_iter(text)
+            .filter_map(|m| Self::decode(m.as_str()))
+            .collect()
+    }
+
+    /// Finds JWTs in the string values of a JSON document, labelled with their field
+    /// path, e.g. `access_token` or `data.tokens[0]`.
+    pub fn find_in_json(value: &Value) -> Vec<(String, Self)> {
+        let mut found = Vec::new();
+        collect_json(value, String::new(), &mut found);
+        found
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.timestamp("exp")
+    }
+
+    pub fn issued_at(&self) -> Option<DateTime<Utc>> {
+        self.timestamp("iat")
+    }
+
+    pub fn not_before(&self) -> Option<DateTime<Utc>> {
+        self.timestamp("nbf")
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at().is_some_and(|exp| exp <= Utc::now())
+    }
+
+    fn timestamp(&self, claim: &str) -> Option<DateTime<Utc>> {
+        let seconds = self.payload.get(claim)?.as_f64()?;
+        DateTime::from_timestamp(seconds as i64, 0)
+    }
+}
+
+/// Describes `time` relative to `now`, e.g. `in 4m 10s` or `3h 5m ago`.
+pub fn format_relative(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let delta = time - now;
+    let span = format_span(delta.abs());
+    if delta >= TimeDelta::zero() {
+        format!("in {}", span)
+    } else {
+        format!("{} ago", span)
+    }
+}
+
+fn format_span(delta: TimeDelta) -> String {
+    let seconds = delta.num_seconds();
+    if seconds >= 86400 {
+        format!("{}d {}h", seconds / 86400, seconds % 86400 / 3600)
+    } else if seconds >= 3600 {
+        format!("{}h {}m", seconds / 3600, seconds % 3600 / 60)
+    } else if seconds >= 60 {
+        format!("{}m {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+fn decode_part(part: &str) -> Option<Value> {
+    let bytes = URL_SAFE_NO_PAD.decode(part.trim_end_matches('=')).ok()?;
+    match serde_json::from_slice(&bytes).ok()? {
+        value @ Value::Object(_) => Some(value),
+        _ => None,
+    }
+}
+
+fn collect_json(value: &Value, path: String, found: &mut Vec<(String, Jwt)>) {
+    match value {
+        Value::String(s) => {
+            if let Some(jwt) = Jwt::decode(s) {
+                found.push((path, jwt));
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect_json(item, format!("{}[{}]", path, i), found);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, item) in fields {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                collect_json(item, path, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn token(payload: Value) -> String {
+        format!(
+            "{}.{}.c2lnbmF0dXJl",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#),
+            URL_SAFE_NO_PAD.encode(payload.to_string())
+        )
+    }
+
+    #[test]
+    fn test_decode() {
+        let jwt = Jwt::decode(&token(json!({"sub": "alice", "iat": 1516239022}))).unwrap();
+
+        assert_eq!(jwt.header["alg"], "HS256");
+        assert_eq!(jwt.payload["sub"], "alice");
+        assert_eq!(
+            jwt.issued_at().unwrap().to_rfc3339(),
+            "2018-01-18T01:30:22+00:00"
+        );
+        assert_eq!(jwt.expires_at(), None);
+        assert!(!jwt.is_expired());
+        assert_eq!(Jwt::decode("not.a.jwt"), None);
+        assert_eq!(Jwt::decode("abc"), None);
+    }
+
+    #[test]
+    fn test_expired() {
+        let past = Utc::now().timestamp() - 60;
+        let future = Utc::now().timestamp() + 3600;
+
+        assert!(
+            Jwt::decode(&token(json!({ "exp": past })))
+                .unwrap()
+                .is_expired()
+        );
+        assert!(
+            !Jwt::decode(&token(json!({ "exp": future })))
+                .unwrap()
+                .is_expired()
+        );
+    }
+
+    #[test]
+    fn test_find_in_header_and_json() {
+        let jwt = token(json!({"sub": "alice"}));
+
+        let found = Jwt::find(&format!("Bearer {}", jwt));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].raw, jwt);
+
+        let body = json!({"access_token": jwt, "token_type": "Bearer", "nested": {"ids": [jwt]}});
+        let labels: Vec<String> = Jwt::find_in_json(&body)
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        assert_eq!(labels, ["access_token", "nested.ids[0]"]);
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let now = Utc::now();
+        assert_eq!(
+            format_relative(now + TimeDelta::seconds(250), now),
+            "in 4m 10s"
+        );
+        assert_eq!(
+            format_relative(now - TimeDelta::seconds(11100), now),
+            "3h 5m ago"
+        );
+        assert_eq!(
+            format_relative(now + TimeDelta::seconds(90000), now),
+            "in 1d 1h"
+        );
+    }
+}
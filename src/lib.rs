@@ -1,10 +1,22 @@
 pub mod client;
+pub mod codegen;
+pub mod cookie;
+pub mod curl;
+pub mod diff;
+pub mod environment;
 pub mod error;
+pub mod har;
+pub mod history;
 pub mod http;
+pub mod import;
+pub mod jwt;
+pub mod oauth;
 pub mod tui;
 pub mod variable;
 
 pub use client::Client;
+pub use cookie::CookieJar;
+pub use environment::Environment;
 pub use error::{Error, Result};
 pub use http::{HttpFile, Parser, Request};
 pub use variable::substitute;
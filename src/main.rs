@@ -1,25 +1,455 @@
-use clap::Parser as ClapParser;
-use poke_http::{http::Parser, tui};
-use std::path::PathBuf;
+use clap::{Parser as ClapParser, Subcommand};
+use poke_http::client::DEFAULT_MAX_BODY_SIZE;
+use poke_http::codegen::{self, Language};
+use poke_http::environment::ENV_FILE;
+use poke_http::error::HistoryError;
+use poke_http::har::Har;
+use poke_http::history::{DEFAULT_HISTORY_LIMIT, HistoryStore};
+use poke_http::import::insomnia::InsomniaExport;
+use poke_http::import::openapi::OpenApi;
+use poke_http::import::postman::{PostmanCollection, PostmanEnvironment};
+use poke_http::import::{Collection, bruno};
+use poke_http::{Client, CookieJar, Environment, http::Parser, tui};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(ClapParser)]
 #[command(name = "poke")]
 #[command(author, version, about = "Interactive HTTP client for .http files")]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 struct Args {
-    #[arg(value_name = "FILE", help = "Path to the .http file")]
-    file: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(value_name = "FILE", required = true, help = "Path to the .http file")]
+    file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Persist cookies to a <name>.cookies.json file next to the .http file"
+    )]
+    persist_cookies: bool,
+
+    #[arg(
+        long,
+        help = "Persist history to a <name>.history.jsonl file next to the .http file"
+    )]
+    persist_history: bool,
+
+    #[arg(
+        short,
+        long,
+        value_name = "NAME",
+        help = "Environment from http-client.env.json next to the .http file"
+    )]
+    env: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = DEFAULT_MAX_BODY_SIZE,
+        help = "Response body bytes kept in memory; the rest is written to a temp file"
+    )]
+    max_body_size: usize,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = DEFAULT_HISTORY_LIMIT,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        help = "History entries kept in the file written by --persist-history"
+    )]
+    history_limit: usize,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Inspect the request history stored for an .http file
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+    /// Print a request as code: curl, rust, python, javascript, go or httpie
+    Codegen {
+        #[arg(value_name = "FILE", help = "Path to the .http file")]
+        file: PathBuf,
+
+        #[arg(
+            value_name = "REQUEST",
+            help = "Request name or number, counting from 1"
+        )]
+        request: String,
+
+        #[arg(
+            short,
+            long,
+            value_name = "LANG",
+            default_value = "curl",
+            help = "curl, rust, python, javascript, go or httpie"
+        )]
+        lang: Language,
+
+        #[arg(
+            short,
+            long,
+            value_name = "NAME",
+            help = "Environment from http-client.env.json next to the .http file"
+        )]
+        env: Option<String>,
+    },
+    /// Convert requests recorded by other tools into an .http file
+    Import {
+        #[command(subcommand)]
+        command: ImportCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// List stored entries, oldest first
+    List {
+        #[arg(value_name = "FILE", help = "Path to the .http file")]
+        file: PathBuf,
+
+        #[arg(short = 'n', long, help = "Only list the newest N entries")]
+        last: Option<usize>,
+    },
+    /// Show the request and response of one entry
+    Show {
+        #[arg(value_name = "FILE", help = "Path to the .http file")]
+        file: PathBuf,
+
+        #[arg(
+            value_name = "ID",
+            help = "Entry number as printed by `poke history list`"
+        )]
+        id: usize,
+    },
+    /// Export entries as a HAR 1.2 archive
+    Export {
+        #[arg(value_name = "FILE", help = "Path to the .http file")]
+        file: PathBuf,
+
+        #[arg(
+            value_name = "ID",
+            help = "Entry numbers as printed by `poke history list` (all entries if omitted)"
+        )]
+        ids: Vec<usize>,
+
+        #[arg(
+            short,
+            long,
+            value_name = "PATH",
+            help = "Write to PATH instead of standard output"
+        )]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportCommand {
+    /// Convert a HAR archive, e.g. saved from browser devtools
+    Har {
+        #[arg(value_name = "HAR", help = "Path to the .har file")]
+        file: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            value_name = "PATH",
+            help = "Path of the .http file to create (defaults to <name>.http next to the HAR)"
+        )]
+        output: Option<PathBuf>,
+    },
+    /// Write one request per operation of an OpenAPI 3 specification (JSON or YAML)
+    Openapi {
+        #[arg(value_name = "SPEC", help = "Path to the specification")]
+        file: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            value_name = "PATH",
+            help = "Path of the .http file to create (defaults to <name>.http next to the spec)"
+        )]
+        output: Option<PathBuf>,
+    },
+    /// Convert a Postman collection (v2.0 or v2.1) and its environments
+    Postman {
+        #[arg(value_name = "COLLECTION", help = "Path to the exported collection")]
+        file: PathBuf,
+
+        #[arg(
+            short,
+            long = "env",
+            value_name = "ENV",
+            help = "Exported Postman environment to add to http-client.env.json (repeatable)"
+        )]
+        environments: Vec<PathBuf>,
+
+        #[arg(
+            short,
+            long,
+            value_name = "DIR",
+            help = "Directory for the .http and env files (defaults to the collection's)"
+        )]
+        output: Option<PathBuf>,
+    },
+    /// Convert an Insomnia v4 export (JSON or YAML) and its environments
+    Insomnia {
+        #[arg(value_name = "EXPORT", help = "Path to the exported file")]
+        file: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            value_name = "DIR",
+            help = "Directory for the .http and env files (defaults to the export's)"
+        )]
+        output: Option<PathBuf>,
+    },
+    /// Convert a Bruno collection directory and its environments
+    Bruno {
+        #[arg(
+            value_name = "DIR",
+            help = "Collection directory containing bruno.json"
+        )]
+        dir: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            value_name = "DIR",
+            help = "Directory for the .http and env files (defaults to the collection's parent)"
+        )]
+        output: Option<PathBuf>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
-    let http_file = Parser::parse_file(&args.file)?;
+    match args.command {
+        Some(Command::History { command }) => return run_history_command(command),
+        Some(Command::Import { command }) => return run_import_command(command),
+        Some(Command::Codegen {
+            file,
+            request,
+            lang,
+            env,
+        }) => return run_codegen_command(&file, &request, lang, env.as_deref()),
+        None => {}
+    }
+    let file = args.file.expect("FILE is required without a subcommand");
+
+    let mut http_file = Parser::parse_file(&file)?;
 
     if http_file.requests.is_empty() {
-        eprintln!("No requests found in {:?}", args.file);
+        eprintln!("No requests found in {:?}", file);
         std::process::exit(1);
     }
 
-    tui::run(http_file).await
+    let cookie_jar = if args.persist_cookies {
+        CookieJar::load(&CookieJar::path_for(&file))?
+    } else {
+        CookieJar::new()
+    };
+    let mut builder = Client::builder()
+        .cookie_jar(Arc::new(cookie_jar))
+        .max_body_size(args.max_body_size);
+
+    if let Some(ref name) = args.env {
+        let dir = file.parent().unwrap_or(Path::new(""));
+        let environment = Environment::load(dir, name)?;
+        for (key, value) in environment.variables {
+            http_file.variables.entry(key).or_insert(value);
+        }
+        builder = builder.ssl(environment.ssl).auth(environment.auth);
+    }
+
+    let client = builder.build()?;
+
+    let (history_store, history) = if args.persist_history {
+        let (store, entries) =
+            HistoryStore::open(&HistoryStore::path_for(&file), args.history_limit)?;
+        (Some(store), entries)
+    } else {
+        (None, Vec::new())
+    };
+
+    tui::run(http_file, client, history_store, history).await
+}
+
+fn run_history_command(command: HistoryCommand) -> anyhow::Result<()> {
+    match command {
+        HistoryCommand::List { file, last } => {
+            let entries = HistoryStore::read(&HistoryStore::path_for(&file))?;
+            let skip = last.map_or(0, |last| entries.len().saturating_sub(last));
+            for (index, entry) in entries.iter().enumerate().skip(skip) {
+                println!("{:>4}  {}", index + 1, entry.summary());
+            }
+        }
+        HistoryCommand::Show { file, id } => {
+            let entries = HistoryStore::read(&HistoryStore::path_for(&file))?;
+            let entry = id
+                .checked_sub(1)
+                .and_then(|index| entries.get(index))
+                .ok_or(HistoryError::EntryNotFound(id, entries.len()))?;
+            print!("{}", entry.details());
+        }
+        HistoryCommand::Export { file, ids, output } => {
+            let entries = HistoryStore::read(&HistoryStore::path_for(&file))?;
+            let selected = if ids.is_empty() {
+                entries.iter().collect()
+            } else {
+                ids.iter()
+                    .map(|&id| {
+                        id.checked_sub(1)
+                            .and_then(|index| entries.get(index))
+                            .ok_or(HistoryError::EntryNotFound(id, entries.len()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+            let json = Har::from_entries(selected).to_json();
+            match output {
+                Some(path) => std::fs::write(path, json)?,
+                None => println!("{}", json),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_codegen_command(
+    file: &Path,
+    request: &str,
+    language: Language,
+    env: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut http_file = Parser::parse_file(file)?;
+    if let Some(name) = env {
+        let environment = Environment::load(file.parent().unwrap_or(Path::new("")), name)?;
+        for (key, value) in environment.variables {
+            http_file.variables.entry(key).or_insert(value);
+        }
+    }
+
+    let selected = http_file
+        .requests
+        .iter()
+        .find(|candidate| candidate.name.as_deref() == Some(request))
+        .or_else(|| {
+            request
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| number.checked_sub(1))
+                .and_then(|index| http_file.requests.get(index))
+        });
+    let Some(selected) = selected else {
+        anyhow::bail!(
+            "No request named or numbered '{}' in {}",
+            request,
+            file.display()
+        );
+    };
+
+    println!(
+        "{}",
+        codegen::generate(language, selected, &http_file.variables)?
+    );
+    Ok(())
+}
+
+fn run_import_command(command: ImportCommand) -> anyhow::Result<()> {
+    let parent = |path: &Path| -> PathBuf { path.parent().unwrap_or(Path::new("")).into() };
+    match command {
+        ImportCommand::Har { file, output } => {
+            let har = Har::parse(&std::fs::read_to_string(&file)?)?;
+            let output = output.unwrap_or_else(|| file.with_extension("http"));
+            let name = file_stem(&file);
+            write_http_file(&har.to_collection(&name), &output)?;
+        }
+        ImportCommand::Openapi { file, output } => {
+            let spec = OpenApi::parse(&std::fs::read_to_string(&file)?)?;
+            let output = output.unwrap_or_else(|| file.with_extension("http"));
+            write_http_file(&spec.to_collection(), &output)?;
+        }
+        ImportCommand::Postman {
+            file,
+            environments,
+            output,
+        } => {
+            let collection = PostmanCollection::parse(&std::fs::read_to_string(&file)?)?;
+            let environments = environments
+                .iter()
+                .map(|path| PostmanEnvironment::parse(&std::fs::read_to_string(path)?))
+                .collect::<poke_http::Result<Vec<_>>>()?;
+            let dir = output.unwrap_or_else(|| parent(&file));
+            write_collection(&collection.to_collection(&environments), &dir)?;
+        }
+        ImportCommand::Insomnia { file, output } => {
+            let export = InsomniaExport::parse(&std::fs::read_to_string(&file)?)?;
+            let dir = output.unwrap_or_else(|| parent(&file));
+            write_collection(&export.to_collection(), &dir)?;
+        }
+        ImportCommand::Bruno { dir, output } => {
+            let collection = bruno::load(&dir)?;
+            let output = output.unwrap_or_else(|| parent(&dir.canonicalize().unwrap_or(dir)));
+            write_collection(&collection, &output)?;
+        }
+    }
+    Ok(())
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Writes all requests of `collection` to the single file `output`.
+fn write_http_file(collection: &Collection, output: &Path) -> anyhow::Result<()> {
+    if output.exists() {
+        anyhow::bail!("{} already exists", output.display());
+    }
+    let http_file = collection.http_file(output);
+    std::fs::write(output, http_file.to_http())?;
+    println!(
+        "Imported {} requests into {}",
+        http_file.requests.len(),
+        output.display()
+    );
+    print_import_warnings(&collection.warnings);
+    Ok(())
+}
+
+/// Writes `collection` as .http and env files into `dir`.
+fn write_collection(collection: &Collection, dir: &Path) -> anyhow::Result<()> {
+    let files = collection.write(dir)?;
+    for environment in &collection.environments {
+        println!(
+            "Added environment '{}' to {}",
+            environment.name,
+            dir.join(ENV_FILE).display()
+        );
+    }
+    for http_file in &files {
+        println!(
+            "Imported {} requests into {}",
+            http_file.requests.len(),
+            http_file.path.display()
+        );
+    }
+    print_import_warnings(&collection.warnings);
+    Ok(())
+}
+
+fn print_import_warnings(warnings: &[String]) {
+    if !warnings.is_empty() {
+        eprintln!("Not converted:");
+        for warning in warnings {
+            eprintln!("  {}", warning);
+        }
+    }
 }
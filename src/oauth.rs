@@ -0,0 +1,441 @@
+use crate::error::{AuthError, Result};
+use crate::variable;
+use chrono::{DateTime, TimeDelta, Utc};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Tokens this close to expiry are refreshed before use.
+const EXPIRY_MARGIN: TimeDelta = TimeDelta::seconds(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum GrantType {
+    #[serde(rename = "Client Credentials")]
+    ClientCredentials,
+    #[serde(rename = "Password")]
+    Password,
+    #[serde(rename = "Refresh Token")]
+    RefreshToken,
+}
+
+/// How the client id and secret are sent to the token endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum ClientAuthentication {
+    #[default]
+    #[serde(rename = "basic")]
+    Basic,
+    #[serde(rename = "in body")]
+    InBody,
+    #[serde(rename = "none")]
+    None,
+}
+
+/// An OAuth2 configuration from the `Security.Auth` section of an environment file,
+/// using the same keys as the IntelliJ HTTP client.
+///
+/// ```json
+/// "Security": {
+///   "Auth": {
+///     "my-auth": {
+///       "Type": "OAuth2",
+///       "Grant Type": "Client Credentials",
+///       "Token URL": "https://auth.example.com/token",
+///       "Client ID": "poke",
+///       "Client Secret": "{{clientSecret}}",
+///       "Scope": "read write"
+///     }
+///   }
+/// }
+/// ```
+///
+/// String values may reference variables, which are substituted when a token is fetched.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct OAuth2Config {
+    #[serde(rename = "Type")]
+    pub kind: String,
+    #[serde(rename = "Grant Type")]
+    pub grant_type: GrantType,
+    #[serde(rename = "Token URL")]
+    pub token_url: String,
+    #[serde(rename = "Client ID")]
+    pub client_id: String,
+    #[serde(rename = "Client Secret")]
+    pub client_secret: Option<String>,
+    #[serde(rename = "Client Credentials", default)]
+    pub client_authentication: ClientAuthentication,
+    #[serde(rename = "Scope")]
+    pub scope: Option<String>,
+    #[serde(rename = "Username")]
+    pub username: Option<String>,
+    #[serde(rename = "Password")]
+    pub password: Option<String>,
+    #[serde(rename = "Refresh Token")]
+    pub refresh_token: Option<String>,
+}
+
+/// A cached access token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Token {
+    fn is_fresh(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| expires_at - EXPIRY_MARGIN > Utc::now())
+    }
+}
+
+/// Expiry of a cached token, used for display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenInfo {
+    pub name: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+/// Fetches, caches and refreshes the tokens referenced as `{{$auth.token("name")}}`.
+#[derive(Debug, Default)]
+pub struct OAuth2 {
+    configs: HashMap<String, OAuth2Config>,
+    tokens: RwLock<HashMap<String, Token>>,
+}
+
+impl OAuth2 {
+    pub fn new(configs: HashMap<String, OAuth2Config>) -> Self {
+        Self {
+            configs,
+            tokens: RwLock::default(),
+        }
+    }
+
+    /// Returns the names of the tokens referenced in `text`.
+    pub fn references(text: &str) -> Vec<String> {
+        token_pattern()
+            .captures_iter(text)
+            .map(|cap| cap[1].to_string())
+            .collect()
+    }
+
+    /// Replaces every `{{$auth.token("name")}}` in `text` with a valid access token.
+    pub async fn substitute(
+        &self,
+        http: &reqwest::Client,
+        text: &str,
+        variables: &HashMap<String, String>,
+    ) -> Result<String> {
+        let mut result = text.to_string();
+        for cap in token_pattern().captures_iter(text) {
+            let token = self.token(http, &cap[1], variables).await?;
+            result = result.replace(&cap[0], &token.access_token);
+        }
+        Ok(result)
+    }
+
+    /// Returns the cached token for `name`, refreshing or fetching it when needed.
+    pub async fn token(
+        &self,
+        http: &reqwest::Client,
+        name: &str,
+        variables: &HashMap<String, String>,
+    ) -> Result<Token> {
+        let config = self
+            .configs
+            .get(name)
+            .ok_or_else(|| AuthError::UnknownConfig(name.to_string()))?;
+
+        let cached = self.tokens.read().unwrap().get(name).cloned();
+        if let Some(ref token) = cached
+            && token.is_fresh()
+        {
+            return Ok(token.clone());
+        }
+
+        let refreshed = match cached.and_then(|token| token.refresh_token) {
+            Some(refresh_token) => {
+                let params = vec![
+                    ("grant_type", "refresh_token".to_string()),
+                    ("refresh_token", refresh_token),
+                ];
+                self.request_token(http, config, params, variables)
+                    .await
+                    .ok()
+            }
+            None => None,
+        };
+
+        let token = match refreshed {
+            Some(token) => token,
+            None => {
+                let params = grant_params(name, config, variables)?;
+                self.request_token(http, config, params, variables).await?
+            }
+        };
+
+        self.tokens
+            .write()
+            .unwrap()
+            .insert(name.to_string(), token.clone());
+        Ok(token)
+    }
+
+    /// Lists cached tokens with their expiry, sorted by name.
+    pub fn tokens(&self) -> Vec<TokenInfo> {
+        let mut tokens: Vec<TokenInfo> = self
+            .tokens
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, token)| TokenInfo {
+                name: name.clone(),
+                expires_at: token.expires_at,
+            })
+            .collect();
+        tokens.sort_by(|a, b| a.name.cmp(&b.name));
+        tokens
+    }
+
+    async fn request_token(
+        &self,
+        http: &reqwest::Client,
+        config: &OAuth2Config,
+        mut params: Vec<(&'static str, String)>,
+        variables: &HashMap<String, String>,
+    ) -> Result<Token> {
+        let token_url = variable::substitute(&config.token_url, variables)?;
+        let client_id = variable::substitute(&config.client_id, variables)?;
+        let client_secret = match config.client_secret {
+            Some(ref secret) => Some(variable::substitute(secret, variables)?),
+            None => None,
+        };
+
+        let mut request = http.post(&token_url);
+        match config.client_authentication {
+            ClientAuthentication::Basic => {
+                request = request.basic_auth(&client_id, client_secret.as_ref());
+            }
+            ClientAuthentication::InBody => {
+                params.push(("client_id", client_id));
+                if let Some(secret) = client_secret {
+                    params.push(("client_secret", secret));
+                }
+            }
+            ClientAuthentication::None => params.push(("client_id", client_id)),
+        }
+
+        let response = request
+            .form(&params)
+            .send()
+            .await
+            .map_err(|err| AuthError::TokenRequestFailed(err.to_string()))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|err| AuthError::TokenRequestFailed(err.to_string()))?;
+        if !status.is_success() {
+            return Err(AuthError::TokenRequestFailed(format!("{}: {}", status, body)).into());
+        }
+
+        let response: TokenResponse = serde_json::from_str(&body)
+            .map_err(|err| AuthError::TokenRequestFailed(err.to_string()))?;
+        Ok(Token {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            expires_at: response
+                .expires_in
+                .map(|seconds| Utc::now() + TimeDelta::seconds(seconds)),
+        })
+    }
+}
+
+fn grant_params(
+    name: &str,
+    config: &OAuth2Config,
+    variables: &HashMap<String, String>,
+) -> Result<Vec<(&'static str, String)>> {
+    let required = |value: &Option<String>, field: &str| -> Result<String> {
+        let value = value.as_ref().ok_or_else(|| {
+            AuthError::InvalidConfig(name.to_string(), format!("missing {}", field))
+        })?;
+        variable::substitute(value, variables)
+    };
+
+    let mut params = match config.grant_type {
+        GrantType::ClientCredentials => vec![("grant_type", "client_credentials".to_string())],
+        GrantType::Password => vec![
+            ("grant_type", "password".to_string()),
+            ("username", required(&config.username, "Username")?),
+            ("password", required(&config.password, "Password")?),
+        ],
+        GrantType::RefreshToken => vec![
+            ("grant_type", "refresh_token".to_string()),
+            (
+                "refresh_token",
+                required(&config.refresh_token, "Refresh Token")?,
+            ),
+        ],
+    };
+    if let Some(ref scope) = config.scope {
+        params.push(("scope", variable::substitute(scope, variables)?));
+    }
+    Ok(params)
+}
+
+fn token_pattern() -> Regex {
+    Regex::new(r#"\{\{\s*\$auth\.token\(\s*"([^"]+)"\s*\)\s*\}\}"#).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serves `replies` in order and records the request bodies it received.
+    async fn token_server(replies: Vec<&'static str>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/token", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+
+        tokio::spawn(async move {
+            for reply in replies {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                received.lock().unwrap().push(request);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.len(),
+                    reply
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    fn config(token_url: &str, grant_type: GrantType) -> OAuth2Config {
+        OAuth2Config {
+            kind: "OAuth2".to_string(),
+            grant_type,
+            token_url: token_url.to_string(),
+            client_id: "poke".to_string(),
+            client_secret: Some("{{secret}}".to_string()),
+            client_authentication: ClientAuthentication::InBody,
+            scope: Some("read".to_string()),
+            username: Some("alice".to_string()),
+            password: Some("wonderland".to_string()),
+            refresh_token: None,
+        }
+    }
+
+    fn variables() -> HashMap<String, String> {
+        HashMap::from([("secret".to_string(), "s3cret".to_string())])
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config: OAuth2Config = serde_json::from_str(
+            r#"{
+                "Type": "OAuth2",
+                "Grant Type": "Password",
+                "Token URL": "https://auth.example.com/token",
+                "Client ID": "poke",
+                "Username": "alice",
+                "Password": "{{password}}"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.grant_type, GrantType::Password);
+        assert_eq!(config.client_authentication, ClientAuthentication::Basic);
+        assert_eq!(config.password.as_deref(), Some("{{password}}"));
+    }
+
+    #[test]
+    fn test_references() {
+        assert_eq!(
+            OAuth2::references(r#"Bearer {{$auth.token("my-auth")}} {{ $auth.token("other") }}"#),
+            vec!["my-auth", "other"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_token_is_cached() {
+        let (url, requests) = token_server(vec![
+            r#"{"access_token": "abc", "token_type": "Bearer", "expires_in": 3600}"#,
+        ])
+        .await;
+        let oauth = OAuth2::new(HashMap::from([(
+            "my-auth".to_string(),
+            config(&url, GrantType::ClientCredentials),
+        )]));
+        let http = reqwest::Client::new();
+
+        let header = oauth
+            .substitute(&http, r#"Bearer {{$auth.token("my-auth")}}"#, &variables())
+            .await
+            .unwrap();
+        let again = oauth.token(&http, "my-auth", &variables()).await.unwrap();
+
+        assert_eq!(header, "Bearer abc");
+        assert_eq!(again.access_token, "abc");
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("grant_type=client_credentials"));
+        assert!(requests[0].contains("client_secret=s3cret"));
+        assert!(requests[0].contains("scope=read"));
+        assert_eq!(oauth.tokens()[0].name, "my-auth");
+        assert!(oauth.tokens()[0].expires_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_refreshed() {
+        let (url, requests) = token_server(vec![
+            r#"{"access_token": "first", "refresh_token": "r1", "expires_in": 0}"#,
+            r#"{"access_token": "second", "expires_in": 3600}"#,
+        ])
+        .await;
+        let oauth = OAuth2::new(HashMap::from([(
+            "my-auth".to_string(),
+            config(&url, GrantType::Password),
+        )]));
+        let http = reqwest::Client::new();
+
+        let first = oauth.token(&http, "my-auth", &variables()).await.unwrap();
+        let second = oauth.token(&http, "my-auth", &variables()).await.unwrap();
+
+        assert_eq!(first.access_token, "first");
+        assert_eq!(second.access_token, "second");
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("grant_type=password"));
+        assert!(requests[0].contains("username=alice"));
+        assert!(requests[1].contains("grant_type=refresh_token"));
+        assert!(requests[1].contains("refresh_token=r1"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_config() {
+        let oauth = OAuth2::default();
+        let err = oauth
+            .token(&reqwest::Client::new(), "missing", &HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("missing"));
+    }
+}
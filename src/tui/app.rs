@@ -1,15 +1,17 @@
-use crate::client::Response;
-use crate::http::{HttpFile, Request};
+use super::editor::RequestEditor;
+use super::ui::format_response_body;
+use crate::client::{Authorization, Progress, Response, SseEvent, WebSocketMessage};
+use crate::codegen::{self, Language};
+use crate::cookie::CookieInfo;
+use crate::diff::ResponseDiff;
+pub use crate::history::HistoryEntry;
+use crate::history::{HistoryFilter, HistorySort};
+use crate::http::{HttpFile, Method, Request};
+use crate::jwt::{self, Jwt};
+use crate::oauth::{OAuth2, TokenInfo};
+use crate::variable;
 use regex::Regex;
 use std::collections::HashSet;
-use std::time::SystemTime;
-
-#[derive(Debug, Clone)]
-pub struct HistoryEntry {
-    pub request: Request,
-    pub response: Response,
-    pub timestamp: SystemTime,
-}
 
 pub struct App {
     pub http_file: HttpFile,
@@ -24,12 +26,44 @@ pub struct App {
     pub request_details_scroll: u16,
     pub request_details_visible_height: u16,
     pub loading: bool,
+    pub progress: Option<Progress>,
+    pub stream_events: Vec<SseEvent>,
+    pub websocket_session: bool,
+    pub websocket_messages: Vec<WebSocketMessage>,
+    /// Message being typed into the open WebSocket session, if any.
+    pub websocket_input: Option<String>,
+    pub notification: Option<String>,
+    pub formatted_body: String,
+    pub history_formatted_body: Option<(usize, String)>,
     pub filter_text: String,
     pub filter_active: bool,
     pub history: Vec<HistoryEntry>,
     pub history_view_active: bool,
+    /// Position of the selected entry in `filtered_history`.
     pub selected_history: usize,
     pub history_detail_scroll: u16,
+    pub history_filter_text: String,
+    pub history_filter_active: bool,
+    pub history_sort: HistorySort,
+    /// Indices in `history` of the entries matching the filter, in display order.
+    filtered_history: Vec<usize>,
+    /// Indices in `history` of the entries marked for comparison, in marking order.
+    pub history_marks: Vec<usize>,
+    /// Diff of the two marked entries while the diff view is open.
+    pub history_diff: Option<ResponseDiff>,
+    pub history_diff_scroll: u16,
+    pub cookies: Vec<CookieInfo>,
+    pub cookies_view_active: bool,
+    pub selected_cookie: usize,
+    pub auth_tokens: Vec<TokenInfo>,
+    pub jwt_popup_active: bool,
+    pub jwt_popup_scroll: u16,
+    pub codegen_language: Option<Language>,
+    pub codegen_scroll: u16,
+    /// The request being edited while the editor is open.
+    pub editor: Option<RequestEditor>,
+    /// Whether the selected request is waiting for its deletion to be confirmed.
+    pub confirm_delete: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -40,6 +74,7 @@ pub enum Focus {
     VariablesList,
     HistoryList,
     HistoryDetail,
+    CookieList,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -64,25 +99,82 @@ impl App {
             request_details_scroll: 0,
             request_details_visible_height: 0,
             loading: false,
+            progress: None,
+            stream_events: Vec::new(),
+            websocket_session: false,
+            websocket_messages: Vec::new(),
+            websocket_input: None,
+            notification: None,
+            formatted_body: String::new(),
+            history_formatted_body: None,
             filter_text: String::new(),
             filter_active: false,
             history: Vec::new(),
             history_view_active: false,
             selected_history: 0,
             history_detail_scroll: 0,
+            history_filter_text: String::new(),
+            history_filter_active: false,
+            history_sort: HistorySort::default(),
+            filtered_history: Vec::new(),
+            history_marks: Vec::new(),
+            history_diff: None,
+            history_diff_scroll: 0,
+            cookies: Vec::new(),
+            cookies_view_active: false,
+            selected_cookie: 0,
+            auth_tokens: Vec::new(),
+            jwt_popup_active: false,
+            jwt_popup_scroll: 0,
+            codegen_language: None,
+            codegen_scroll: 0,
+            editor: None,
+            confirm_delete: false,
         }
     }
 
     pub fn selected_request(&self) -> Option<&Request> {
+        self.selected_index()
+            .map(|index| &self.http_file.requests[index])
+    }
+
+    /// Index in `http_file.requests` of the selected request.
+    pub fn selected_index(&self) -> Option<usize> {
         if self.filter_active {
             self.filtered_requests()
                 .get(self.selected)
-                .map(|(idx, _)| &self.http_file.requests[*idx])
+                .map(|(idx, _)| *idx)
         } else {
-            self.http_file.requests.get(self.selected)
+            (self.selected < self.http_file.requests.len()).then_some(self.selected)
+        }
+    }
+
+    /// Selects the request at `index` in `http_file.requests`, clearing the filter.
+    pub fn select_index(&mut self, index: usize) {
+        if self.filter_active {
+            self.exit_filter_mode();
+        }
+        self.selected = index.min(self.http_file.requests.len().saturating_sub(1));
+        self.selected_variable = 0;
+        self.request_details_scroll = 0;
+    }
+
+    pub fn open_editor(&mut self) {
+        if let Some(index) = self.selected_index() {
+            let request = self.http_file.requests[index].clone();
+            self.editor = Some(RequestEditor::new(request, index, false));
         }
     }
 
+    /// Opens the editor on a new request, to be added after the selected one.
+    pub fn open_new_request_editor(&mut self) {
+        let index = self
+            .selected_index()
+            .map_or(self.http_file.requests.len(), |index| index + 1);
+        let request = Request::new(Method::Get, "");
+        self.editor = Some(RequestEditor::new(request, index, true));
+    }
+
     pub fn filtered_requests(&self) -> Vec<(usize, &Request)> {
         if !self.filter_active || self.filter_text.is_empty() {
             return self.http_file.requests.iter().enumerate().collect();
@@ -154,7 +246,8 @@ impl App {
     }
 
     pub fn select_next_variable(&mut self) {
-        let max = self.get_used_variables().len().saturating_sub(1);
+        let max =
+            (self.get_used_variables().len() + self.get_used_auth_tokens().len()).saturating_sub(1);
         if self.selected_variable < max {
             self.selected_variable += 1;
         }
@@ -167,6 +260,7 @@ impl App {
             Focus::RequestDetails => Focus::VariablesList,
             Focus::VariablesList => Focus::RequestList,
             Focus::HistoryList | Focus::HistoryDetail => Focus::HistoryList,
+            Focus::CookieList => Focus::CookieList,
         };
     }
 
@@ -218,6 +312,16 @@ impl App {
 
         let mut lines = 1;
         lines += request.headers.len();
+        lines += request
+            .headers
+            .iter()
+            .filter(|(key, value)| self.authorization_hint(key, value).is_some())
+            .count();
+        lines += request
+            .headers
+            .iter()
+            .filter(|(_, value)| self.jwt_hint(value).is_some())
+            .count();
 
         if let Some(ref body) = request.body {
             if !request.headers.is_empty() {
@@ -229,6 +333,133 @@ impl App {
         lines
     }
 
+    /// Describes how a shorthand `Authorization` header is actually sent, masking the
+    /// encoded credentials.
+    pub fn authorization_hint(&self, key: &str, value: &str) -> Option<String> {
+        if !key.eq_ignore_ascii_case("authorization") {
+            return None;
+        }
+
+        let value = variable::substitute(value, &self.http_file.variables)
+            .unwrap_or_else(|_| value.to_string());
+        match Authorization::parse(&value)? {
+            Authorization::Digest { username, .. } => Some(format!(
+                "sent as Digest credentials for {} once the server sends a challenge",
+                username
+            )),
+            Authorization::Aws(credentials) => Some(format!(
+                "signed with AWS Signature Version 4 using {}",
+                mask_credentials(&format!("key {}", credentials.access_key))
+            )),
+            auth => auth
+                .encode()
+                .map(|encoded| format!("sent as {}", mask_credentials(&encoded))),
+        }
+    }
+
+    /// Describes when the JWT in a header value expires, and whether it already has.
+    pub fn jwt_hint(&self, value: &str) -> Option<(String, bool)> {
+        let value = variable::substitute(value, &self.http_file.variables)
+            .unwrap_or_else(|_| value.to_string());
+        let token = Jwt::find(&value).into_iter().next()?;
+        let expires_at = token.expires_at()?;
+        let relative = jwt::format_relative(expires_at, chrono::Utc::now());
+        if token.is_expired() {
+            Some((format!("JWT expired {}", relative), true))
+        } else {
+            Some((format!("JWT expires {}", relative), false))
+        }
+    }
+
+    /// Collects the JWTs in the selected request and the last response, labelled with
+    /// where they were found.
+    pub fn jwts(&self) -> Vec<(String, Jwt)> {
+        let mut found = Vec::new();
+        let substitute = |text: &str| {
+            variable::substitute(text, &self.http_file.variables)
+                .unwrap_or_else(|_| text.to_string())
+        };
+
+        if let Some(request) = self.selected_request() {
+            for (key, value) in &request.headers {
+                for token in Jwt::find(&substitute(value)) {
+                    found.push((format!("Request header {}", key), token));
+                }
+            }
+            if let Some(ref body) = request.body {
+                for token in Jwt::find(&substitute(body)) {
+                    found.push(("Request body".to_string(), token));
+                }
+            }
+        }
+
+        if let Some(ref response) = self.last_response {
+            for (key, value) in &response.headers {
+                for token in Jwt::find(value) {
+                    found.push((format!("Response header {}", key), token));
+                }
+            }
+            if let Some(ref text) = response.text {
+                match serde_json::from_str(text) {
+                    Ok(json) => found.extend(
+                        Jwt::find_in_json(&json)
+                            .into_iter()
+                            .map(|(path, token)| (format!("Response body {}", path), token)),
+                    ),
+                    Err(_) => found.extend(
+                        Jwt::find(text)
+                            .into_iter()
+                            .map(|token| ("Response body".to_string(), token)),
+                    ),
+                }
+            }
+        }
+
+        found
+    }
+
+    pub fn toggle_jwt_popup(&mut self) {
+        self.jwt_popup_active = !self.jwt_popup_active;
+        self.jwt_popup_scroll = 0;
+    }
+
+    pub fn toggle_codegen_popup(&mut self) {
+        self.codegen_language = match self.codegen_language {
+            Some(_) => None,
+            None => Some(Language::Curl),
+        };
+        self.codegen_scroll = 0;
+    }
+
+    pub fn cycle_codegen_language(&mut self, forward: bool) {
+        let Some(current) = self.codegen_language else {
+            return;
+        };
+        let count = Language::ALL.len();
+        let index = Language::ALL
+            .iter()
+            .position(|language| *language == current)
+            .unwrap_or(0);
+        let next = if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        };
+        self.codegen_language = Some(Language::ALL[next]);
+        self.codegen_scroll = 0;
+    }
+
+    /// The selected request as code in the popup's language.
+    pub fn codegen_output(&self) -> Option<crate::error::Result<String>> {
+        let language = self.codegen_language?;
+        let request = self.selected_request()?;
+        Some(codegen::generate(
+            language,
+            request,
+            &self.http_file.variables,
+        ))
+    }
+
     pub fn get_used_variables(&self) -> Vec<(String, String)> {
         let Some(request) = self.selected_request() else {
             return Vec::new();
@@ -267,25 +498,112 @@ impl App {
             .collect()
     }
 
+    /// Lists the `{{$auth.token("name")}}` references of the selected request, with the
+    /// cached token if one was fetched already.
+    pub fn get_used_auth_tokens(&self) -> Vec<(String, Option<&TokenInfo>)> {
+        let Some(request) = self.selected_request() else {
+            return Vec::new();
+        };
+
+        let mut names = OAuth2::references(&request.url);
+        for value in request.headers.values() {
+            names.extend(OAuth2::references(value));
+        }
+        if let Some(ref body) = request.body {
+            names.extend(OAuth2::references(body));
+        }
+
+        let mut seen = HashSet::new();
+        names.retain(|name| seen.insert(name.clone()));
+
+        names
+            .into_iter()
+            .map(|name| {
+                let token = self.auth_tokens.iter().find(|token| token.name == name);
+                (name, token)
+            })
+            .collect()
+    }
+
+    /// Shows `response` in the response panel, formatting its body once up front.
+    pub fn set_response(&mut self, response: Response) {
+        self.formatted_body = format_response_body(&response);
+        self.last_response = Some(response);
+    }
+
+    /// Makes sure `history_formatted_body` holds the body of the selected history entry.
+    pub fn update_history_formatted_body(&mut self) {
+        let selected = self.selected_history_index();
+        if matches!(self.history_formatted_body, Some((index, _)) if Some(index) == selected) {
+            return;
+        }
+
+        self.history_formatted_body =
+            selected.map(|index| (index, format_response_body(&self.history[index].response)));
+    }
+
+    pub fn set_history(&mut self, history: Vec<HistoryEntry>) {
+        self.history = history;
+        self.refresh_filtered_history();
+    }
+
     pub fn add_history_entry(&mut self, entry: HistoryEntry) {
         self.history.push(entry);
+        self.refresh_filtered_history();
     }
 
     pub fn toggle_history_view(&mut self) {
         self.history_view_active = !self.history_view_active;
         if self.history_view_active {
             self.focus = Focus::HistoryList;
-            if !self.history.is_empty() {
-                self.selected_history = self.history.len() - 1;
-            }
+            self.selected_history = 0;
         } else {
             self.focus = Focus::RequestList;
         }
         self.history_detail_scroll = 0;
     }
 
+    /// History entries matching the filter, in display order, with their index in `history`.
+    pub fn filtered_history(&self) -> Vec<(usize, &HistoryEntry)> {
+        self.filtered_history
+            .iter()
+            .map(|&index| (index, &self.history[index]))
+            .collect()
+    }
+
+    /// Filters and sorts the history again; needed whenever the history, the filter text
+    /// or the sort order changes.
+    fn refresh_filtered_history(&mut self) {
+        let filter = HistoryFilter::parse(&self.history_filter_text);
+        let mut entries: Vec<(usize, &HistoryEntry)> = self
+            .history
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| filter.matches(entry))
+            .collect();
+        self.history_sort.sort(&mut entries);
+        self.filtered_history = entries.into_iter().map(|(index, _)| index).collect();
+    }
+
+    fn selected_history_index(&self) -> Option<usize> {
+        self.filtered_history.get(self.selected_history).copied()
+    }
+
     pub fn selected_history_entry(&self) -> Option<&HistoryEntry> {
-        self.history.get(self.selected_history)
+        self.selected_history_index()
+            .map(|index| &self.history[index])
+    }
+
+    /// Selects the entry at `index` in `history`, if it is shown.
+    pub fn select_history_index(&mut self, index: usize) {
+        if let Some(position) = self
+            .filtered_history
+            .iter()
+            .position(|&entry_index| entry_index == index)
+        {
+            self.selected_history = position;
+            self.history_detail_scroll = 0;
+        }
     }
 
     pub fn select_previous_history(&mut self) {
@@ -296,12 +614,109 @@ impl App {
     }
 
     pub fn select_next_history(&mut self) {
-        if self.selected_history < self.history.len().saturating_sub(1) {
+        if self.selected_history < self.filtered_history.len().saturating_sub(1) {
             self.selected_history += 1;
             self.history_detail_scroll = 0;
         }
     }
 
+    pub fn enter_history_filter_mode(&mut self) {
+        self.history_filter_active = true;
+    }
+
+    /// Stops editing the filter; `keep` leaves it applied, otherwise it is cleared.
+    pub fn exit_history_filter_mode(&mut self, keep: bool) {
+        self.history_filter_active = false;
+        if !keep {
+            self.history_filter_text.clear();
+            self.selected_history = 0;
+            self.refresh_filtered_history();
+        }
+    }
+
+    pub fn history_filter_append_char(&mut self, c: char) {
+        self.history_filter_push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn history_filter_push_str(&mut self, text: &str) {
+        self.history_filter_text.push_str(text);
+        self.selected_history = 0;
+        self.history_detail_scroll = 0;
+        self.refresh_filtered_history();
+    }
+
+    pub fn history_filter_backspace(&mut self) {
+        self.history_filter_text.pop();
+        self.selected_history = 0;
+        self.history_detail_scroll = 0;
+        self.refresh_filtered_history();
+    }
+
+    pub fn cycle_history_sort(&mut self) {
+        self.history_sort = self.history_sort.next();
+        self.selected_history = 0;
+        self.history_detail_scroll = 0;
+        self.refresh_filtered_history();
+    }
+
+    /// Marks or unmarks the selected entry; marking a third entry drops the oldest mark.
+    pub fn toggle_history_mark(&mut self) {
+        let Some(index) = self.selected_history_index() else {
+            return;
+        };
+        if let Some(position) = self.history_marks.iter().position(|&mark| mark == index) {
+            self.history_marks.remove(position);
+        } else {
+            if self.history_marks.len() == 2 {
+                self.history_marks.remove(0);
+            }
+            self.history_marks.push(index);
+        }
+    }
+
+    /// Label of the entry at `index` in `history` if it is marked: `A` for the first mark,
+    /// `B` for the second.
+    pub fn history_mark_label(&self, index: usize) -> Option<char> {
+        self.history_marks
+            .iter()
+            .position(|&mark| mark == index)
+            .map(|position| if position == 0 { 'A' } else { 'B' })
+    }
+
+    /// Opens the diff view comparing the two marked entries, A against B. Does nothing
+    /// until two entries are marked.
+    pub fn open_history_diff(&mut self) {
+        let [a, b] = self.history_marks[..] else {
+            return;
+        };
+        self.history_diff = Some(ResponseDiff::new(
+            &self.history[a].response,
+            &self.history[b].response,
+        ));
+        self.history_diff_scroll = 0;
+    }
+
+    /// Entries to export: every shown entry with `all`, otherwise the marked entries or,
+    /// with none marked, the selected one. Oldest first.
+    pub fn history_export_entries(&self, all: bool) -> Vec<&HistoryEntry> {
+        let mut indices: Vec<usize> = if all {
+            self.filtered_history.clone()
+        } else if !self.history_marks.is_empty() {
+            self.history_marks.clone()
+        } else {
+            self.selected_history_index().into_iter().collect()
+        };
+        indices.sort_unstable();
+        indices
+            .into_iter()
+            .map(|index| &self.history[index])
+            .collect()
+    }
+
+    pub fn close_history_diff(&mut self) {
+        self.history_diff = None;
+    }
+
     pub fn scroll_history_detail_up(&mut self) {
         self.history_detail_scroll = self.history_detail_scroll.saturating_sub(1);
     }
@@ -309,4 +724,44 @@ impl App {
     pub fn scroll_history_detail_down(&mut self) {
         self.history_detail_scroll = self.history_detail_scroll.saturating_add(1);
     }
+
+    pub fn toggle_cookies_view(&mut self) {
+        self.cookies_view_active = !self.cookies_view_active;
+        self.focus = if self.cookies_view_active {
+            Focus::CookieList
+        } else {
+            Focus::RequestList
+        };
+        self.selected_cookie = 0;
+    }
+
+    pub fn set_cookies(&mut self, cookies: Vec<CookieInfo>) {
+        self.cookies = cookies;
+        self.selected_cookie = self
+            .selected_cookie
+            .min(self.cookies.len().saturating_sub(1));
+    }
+
+    pub fn selected_cookie(&self) -> Option<&CookieInfo> {
+        self.cookies.get(self.selected_cookie)
+    }
+
+    pub fn select_previous_cookie(&mut self) {
+        if self.selected_cookie > 0 {
+            self.selected_cookie -= 1;
+        }
+    }
+
+    pub fn select_next_cookie(&mut self) {
+        if self.selected_cookie < self.cookies.len().saturating_sub(1) {
+            self.selected_cookie += 1;
+        }
+    }
+}
+
+/// Keeps the scheme and the first few characters of an encoded credential.
+fn mask_credentials(value: &str) -> String {
+    let (scheme, credentials) = value.split_once(' ').unwrap_or(("", value));
+    let visible: String = credentials.chars().take(4).collect();
+    format!("{} {}••••••", scheme, visible)
 }
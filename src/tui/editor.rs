@@ -0,0 +1,536 @@
+use crate::http::{Method, Request};
+
+const METHODS: [Method; 9] = [
+    Method::Get,
+    Method::Post,
+    Method::Put,
+    Method::Patch,
+    Method::Delete,
+    Method::Head,
+    Method::Options,
+    Method::WebSocket,
+    Method::Grpc,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    Method,
+    Url,
+    HeaderName(usize),
+    HeaderValue(usize),
+    Body,
+}
+
+/// A request being edited in the editor popup. Nothing changes in the file until
+/// it is saved.
+#[derive(Debug, Clone)]
+pub struct RequestEditor {
+    /// Index of the request in the file, or where a new request will be inserted.
+    pub index: usize,
+    pub is_new: bool,
+    /// The request as it was, for the parts the editor does not show.
+    original: Request,
+    pub name: String,
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<String>,
+    pub field: Field,
+    /// Character position of the cursor in the focused text.
+    pub column: usize,
+    /// Line of the cursor in the body.
+    pub body_line: usize,
+    pub body_scroll: u16,
+    pub error: Option<String>,
+}
+
+impl RequestEditor {
+    pub fn new(request: Request, index: usize, is_new: bool) -> Self {
+        let mut headers: Vec<_> = request
+            .headers
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        headers.sort();
+        let body = match request.body {
+            Some(ref body) => body.lines().map(String::from).collect(),
+            None => vec![String::new()],
+        };
+        let mut editor = Self {
+            index,
+            is_new,
+            name: request.name.clone().unwrap_or_default(),
+            method: request.method.clone(),
+            url: request.url.clone(),
+            original: request,
+            headers,
+            body,
+            field: if is_new { Field::Name } else { Field::Url },
+            column: 0,
+            body_line: 0,
+            body_scroll: 0,
+            error: None,
+        };
+        editor.column = editor.text().map_or(0, |text| text.chars().count());
+        editor
+    }
+
+    /// The request with the edits applied.
+    pub fn to_request(&self) -> Request {
+        let mut request = self.original.clone();
+        let name = self.name.trim();
+        request.name = (!name.is_empty()).then(|| name.to_string());
+        request.method = self.method.clone();
+        request.url = self.url.trim().to_string();
+        request.headers = self
+            .headers
+            .iter()
+            .filter(|(key, _)| !key.trim().is_empty())
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        let body = self.body.join("\n");
+        let body = body.trim();
+        request.body = (!body.is_empty()).then(|| body.to_string());
+        request
+    }
+
+    /// Checks that the request can be written to a .http file and read back the same.
+    pub fn validate(&self) -> Result<(), String> {
+        let url = self.url.trim();
+        if url.is_empty() {
+            return Err("The URL is required".to_string());
+        }
+        if url.contains(char::is_whitespace) {
+            return Err("The URL cannot contain spaces".to_string());
+        }
+        if self.name.trim().starts_with('#') {
+            return Err("The name cannot start with #".to_string());
+        }
+        for (row, (key, _)) in self.headers.iter().enumerate() {
+            let key = key.trim();
+            if key.contains(char::is_whitespace) || key.contains(':') {
+                return Err(format!("Invalid header name '{}'", key));
+            }
+            // The file keeps one value per header name.
+            if !key.is_empty()
+                && self.headers[..row]
+                    .iter()
+                    .any(|(other, _)| other.trim().eq_ignore_ascii_case(key))
+            {
+                return Err(format!("Header '{}' is set more than once", key));
+            }
+        }
+        for line in &self.body {
+            let line = line.trim_end();
+            if line.starts_with('#') || line.starts_with("//") {
+                return Err(format!("Body line '{}' would be read as a comment", line));
+            }
+            if line.starts_with('@') && line.contains('=') {
+                return Err(format!("Body line '{}' would be read as a variable", line));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn cycle_method(&mut self, forward: bool) {
+        let count = METHODS.len();
+        let index = METHODS
+            .iter()
+            .position(|method| *method == self.method)
+            .unwrap_or(0);
+        let next = if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        };
+        self.method = METHODS[next].clone();
+    }
+
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![Field::Name, Field::Method, Field::Url];
+        for row in 0..self.headers.len() {
+            fields.push(Field::HeaderName(row));
+            fields.push(Field::HeaderValue(row));
+        }
+        fields.push(Field::Body);
+        fields
+    }
+
+    fn focus(&mut self, field: Field) {
+        self.field = field;
+        self.column = self.text().map_or(0, |text| text.chars().count());
+    }
+
+    pub fn next_field(&mut self) {
+        let fields = self.fields();
+        let position = fields.iter().position(|field| *field == self.field);
+        let next = position.map_or(0, |position| (position + 1) % fields.len());
+        self.focus(fields[next]);
+    }
+
+    pub fn previous_field(&mut self) {
+        let fields = self.fields();
+        let position = fields.iter().position(|field| *field == self.field);
+        let previous = position.map_or(0, |position| (position + fields.len() - 1) % fields.len());
+        self.focus(fields[previous]);
+    }
+
+    /// Moves to the line above: the previous body line, header row or field.
+    pub fn up(&mut self) {
+        let field = match self.field {
+            Field::Body if self.body_line > 0 => {
+                self.body_line -= 1;
+                self.clamp_column();
+                return;
+            }
+            Field::Body => match self.headers.len() {
+                0 => Field::Url,
+                rows => Field::HeaderValue(rows - 1),
+            },
+            Field::HeaderName(0) | Field::HeaderValue(0) => Field::Url,
+            Field::HeaderName(row) => Field::HeaderName(row - 1),
+            Field::HeaderValue(row) => Field::HeaderValue(row - 1),
+            Field::Url => Field::Method,
+            Field::Method | Field::Name => Field::Name,
+        };
+        self.field = field;
+        self.clamp_column();
+    }
+
+    /// Moves to the line below: the next header row, body line or field.
+    pub fn down(&mut self) {
+        let rows = self.headers.len();
+        let field = match self.field {
+            Field::Name => Field::Method,
+            Field::Method => Field::Url,
+            Field::Url if rows > 0 => Field::HeaderName(0),
+            Field::HeaderName(row) if row + 1 < rows => Field::HeaderName(row + 1),
+            Field::HeaderValue(row) if row + 1 < rows => Field::HeaderValue(row + 1),
+            Field::Body if self.body_line + 1 < self.body.len() => {
+                self.body_line += 1;
+                self.clamp_column();
+                return;
+            }
+            Field::Body => return,
+            _ => {
+                self.body_line = 0;
+                Field::Body
+            }
+        };
+        self.field = field;
+        self.clamp_column();
+    }
+
+    /// Adds an empty header row below the focused one, or at the end.
+    pub fn add_header(&mut self) {
+        let row = match self.field {
+            Field::HeaderName(row) | Field::HeaderValue(row) => row + 1,
+            _ => self.headers.len(),
+        };
+        self.headers.insert(row, (String::new(), String::new()));
+        self.focus(Field::HeaderName(row));
+    }
+
+    pub fn remove_header(&mut self) {
+        let (Field::HeaderName(row) | Field::HeaderValue(row)) = self.field else {
+            return;
+        };
+        self.headers.remove(row);
+        if self.headers.is_empty() {
+            self.focus(Field::Url);
+        } else {
+            self.focus(Field::HeaderName(row.min(self.headers.len() - 1)));
+        }
+    }
+
+    /// Handles Enter: a new line in the body, a new row in the headers, otherwise
+    /// the next field.
+    pub fn enter(&mut self) {
+        match self.field {
+            Field::Body => {
+                let line = &mut self.body[self.body_line];
+                let split = byte_index(line, self.column);
+                let rest = line.split_off(split);
+                self.body_line += 1;
+                self.body.insert(self.body_line, rest);
+                self.column = 0;
+            }
+            Field::HeaderName(_) | Field::HeaderValue(_) => self.add_header(),
+            _ => self.next_field(),
+        }
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if self.field == Field::Method {
+            return;
+        }
+        let column = self.column;
+        if let Some(text) = self.text_mut() {
+            text.insert(byte_index(text, column), c);
+            self.column += 1;
+        }
+    }
+
+    /// Inserts pasted text; only the body keeps line breaks.
+    pub fn insert_str(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n");
+        if self.field != Field::Body {
+            for c in text.trim().chars().filter(|c| *c != '\n') {
+                self.insert_char(c);
+            }
+            return;
+        }
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.enter();
+            }
+            for c in line.chars() {
+                self.insert_char(c);
+            }
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.column > 0 {
+            let column = self.column;
+            if let Some(text) = self.text_mut() {
+                text.remove(byte_index(text, column - 1));
+                self.column -= 1;
+            }
+        } else if self.field == Field::Body && self.body_line > 0 {
+            let line = self.body.remove(self.body_line);
+            self.body_line -= 1;
+            self.column = self.body[self.body_line].chars().count();
+            self.body[self.body_line].push_str(&line);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        let column = self.column;
+        let is_body = self.field == Field::Body;
+        let Some(text) = self.text_mut() else {
+            return;
+        };
+        if column < text.chars().count() {
+            text.remove(byte_index(text, column));
+        } else if is_body && self.body_line + 1 < self.body.len() {
+            let next = self.body.remove(self.body_line + 1);
+            self.body[self.body_line].push_str(&next);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if self.field == Field::Method {
+            self.cycle_method(false);
+        } else if self.column > 0 {
+            self.column -= 1;
+        } else if self.field == Field::Body && self.body_line > 0 {
+            self.body_line -= 1;
+            self.column = self.body[self.body_line].chars().count();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if self.field == Field::Method {
+            self.cycle_method(true);
+            return;
+        }
+        let length = self.text().map_or(0, |text| text.chars().count());
+        if self.column < length {
+            self.column += 1;
+        } else if self.field == Field::Body && self.body_line + 1 < self.body.len() {
+            self.body_line += 1;
+            self.column = 0;
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.column = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.column = self.text().map_or(0, |text| text.chars().count());
+    }
+
+    fn clamp_column(&mut self) {
+        let length = self.text().map_or(0, |text| text.chars().count());
+        self.column = self.column.min(length);
+    }
+
+    /// The focused text, if the focused field holds text.
+    pub fn text(&self) -> Option<&String> {
+        match self.field {
+            Field::Name => Some(&self.name),
+            Field::Method => None,
+            Field::Url => Some(&self.url),
+            Field::HeaderName(row) => self.headers.get(row).map(|(key, _)| key),
+            Field::HeaderValue(row) => self.headers.get(row).map(|(_, value)| value),
+            Field::Body => self.body.get(self.body_line),
+        }
+    }
+
+    fn text_mut(&mut self) -> Option<&mut String> {
+        match self.field {
+            Field::Name => Some(&mut self.name),
+            Field::Method => None,
+            Field::Url => Some(&mut self.url),
+            Field::HeaderName(row) => self.headers.get_mut(row).map(|(key, _)| key),
+            Field::HeaderValue(row) => self.headers.get_mut(row).map(|(_, value)| value),
+            Field::Body => self.body.get_mut(self.body_line),
+        }
+    }
+}
+
+fn byte_index(text: &str, column: usize) -> usize {
+    text.char_indices()
+        .nth(column)
+        .map_or(text.len(), |(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor() -> RequestEditor {
+        let request = Request::new(Method::Post, "https://api.test/items")
+            .with_header("Content-Type", "application/json")
+            .with_header("Accept", "*/*")
+            .with_body("{\n  \"a\": 1\n}");
+        RequestEditor::new(request, 0, false)
+    }
+
+    #[test]
+    fn test_byte_index_counts_characters() {
+        assert_eq!(byte_index("héllo", 0), 0);
+        assert_eq!(byte_index("héllo", 2), 3);
+        assert_eq!(byte_index("héllo", 5), 6);
+        assert_eq!(byte_index("héllo", 9), 6);
+    }
+
+    #[test]
+    fn test_multibyte_editing() {
+        let mut editor = editor();
+        editor.field = Field::Name;
+        editor.name = "cafe".to_string();
+        editor.column = 3;
+        editor.insert_char('é');
+        editor.backspace();
+        editor.backspace();
+        editor.insert_char('f');
+        editor.insert_char('é');
+        editor.delete();
+
+        assert_eq!(editor.name, "café");
+        assert_eq!(editor.column, 4);
+    }
+
+    #[test]
+    fn test_line_joins() {
+        let mut editor = editor();
+        editor.focus(Field::Body);
+        editor.body_line = 1;
+        editor.column = 2;
+        editor.enter();
+        assert_eq!(editor.body, ["{", "  ", "\"a\": 1", "}"]);
+        assert_eq!((editor.body_line, editor.column), (2, 0));
+
+        editor.backspace();
+        assert_eq!(editor.body, ["{", "  \"a\": 1", "}"]);
+        assert_eq!((editor.body_line, editor.column), (1, 2));
+
+        editor.end();
+        editor.delete();
+        assert_eq!(editor.body, ["{", "  \"a\": 1}"]);
+
+        // Nothing to join at the start of the first line or the end of the last.
+        editor.body_line = 0;
+        editor.home();
+        editor.backspace();
+        editor.body_line = 1;
+        editor.end();
+        editor.delete();
+        assert_eq!(editor.body, ["{", "  \"a\": 1}"]);
+    }
+
+    #[test]
+    fn test_remove_header_refocuses() {
+        let mut editor = editor();
+        editor.focus(Field::HeaderValue(1));
+        editor.remove_header();
+        assert_eq!(editor.field, Field::HeaderName(0));
+        assert_eq!(editor.headers, [("Accept".to_string(), "*/*".to_string())]);
+
+        editor.remove_header();
+        assert_eq!(editor.field, Field::Url);
+        assert_eq!(editor.column, "https://api.test/items".len());
+        assert!(editor.headers.is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let editor = editor();
+        assert_eq!(editor.validate(), Ok(()));
+
+        let invalid = |change: fn(&mut RequestEditor)| {
+            let mut editor = editor.clone();
+            change(&mut editor);
+            editor.validate().unwrap_err()
+        };
+        assert_eq!(
+            invalid(|editor| editor.url = " ".to_string()),
+            "The URL is required"
+        );
+        assert_eq!(
+            invalid(|editor| editor.url = "https://a.test/b c".to_string()),
+            "The URL cannot contain spaces"
+        );
+        assert_eq!(
+            invalid(|editor| editor.name = "# note".to_string()),
+            "The name cannot start with #"
+        );
+        assert_eq!(
+            invalid(|editor| editor.headers[0].0 = "X Key".to_string()),
+            "Invalid header name 'X Key'"
+        );
+        assert_eq!(
+            invalid(|editor| editor.headers[1].0 = "accept ".to_string()),
+            "Header 'accept' is set more than once"
+        );
+        assert_eq!(
+            invalid(|editor| editor.body.push("// done".to_string())),
+            "Body line '// done' would be read as a comment"
+        );
+        assert_eq!(
+            invalid(|editor| editor.body.push("@id = 1".to_string())),
+            "Body line '@id = 1' would be read as a variable"
+        );
+    }
+
+    #[test]
+    fn test_to_request() {
+        let mut editor = editor();
+        editor.name = "  Create item ".to_string();
+        editor.cycle_method(true);
+        editor.url = " https://api.test/items/1 ".to_string();
+        editor.headers[0].1 = " text/plain ".to_string();
+        editor.add_header();
+        editor.body = vec![String::new(), "{}".to_string(), String::new()];
+
+        let request = editor.to_request();
+
+        assert_eq!(request.name.as_deref(), Some("Create item"));
+        assert_eq!(request.method, Method::Put);
+        assert_eq!(request.url, "https://api.test/items/1");
+        assert_eq!(request.headers.len(), 2);
+        assert_eq!(request.headers["Accept"], "text/plain");
+        assert_eq!(request.headers["Content-Type"], "application/json");
+        assert_eq!(request.body.as_deref(), Some("{}"));
+
+        editor.name.clear();
+        editor.body = vec![String::new()];
+        let request = editor.to_request();
+        assert_eq!(request.name, None);
+        assert_eq!(request.body, None);
+    }
+}
@@ -1,4 +1,5 @@
 use super::app::{App, Focus, ResponseTab};
+use super::editor::Field;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use std::time::Duration;
 
@@ -6,6 +7,20 @@ pub enum EventResult {
     Continue,
     ExecuteRequest,
     ExecuteHistoryEntry,
+    StopRequest,
+    SendWebSocketMessage(String),
+    SaveResponseBody,
+    ExportHar { all: bool },
+    CopyAsCurl,
+    CopyCodegen,
+    ImportCurl(String),
+    SaveRequest,
+    DuplicateRequest,
+    DeleteRequest,
+    OpenInEditor,
+    RefreshCookies,
+    DeleteCookie,
+    DeleteCookieDomain,
     Quit,
 }
 
@@ -18,11 +33,57 @@ pub fn poll_event(timeout: Duration) -> std::io::Result<Option<Event>> {
 }
 
 pub fn handle_key_event(app: &mut App, key: KeyEvent) -> EventResult {
+    if app.editor.is_some() {
+        return handle_editor_keys(app, key);
+    }
+
+    if app.confirm_delete {
+        app.confirm_delete = false;
+        app.notification = None;
+        if matches!(key.code, KeyCode::Char('d') | KeyCode::Char('y')) {
+            return EventResult::DeleteRequest;
+        }
+        return EventResult::Continue;
+    }
+
+    if app.websocket_input.is_some() {
+        return handle_websocket_input_keys(app, key);
+    }
+
+    if app.jwt_popup_active {
+        return handle_jwt_popup_keys(app, key);
+    }
+
+    if app.codegen_language.is_some() {
+        return handle_codegen_popup_keys(app, key);
+    }
+
+    if app.history_diff.is_some() {
+        return handle_history_diff_keys(app, key);
+    }
+
+    if app.history_filter_active {
+        return handle_history_filter_keys(app, key);
+    }
+
     match key.code {
         KeyCode::Char('q') => return EventResult::Quit,
         KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
             return EventResult::Quit;
         }
+        KeyCode::Char('x') if app.loading && !app.filter_active => {
+            return EventResult::StopRequest;
+        }
+        KeyCode::Char('i') if app.loading && app.websocket_session && !app.filter_active => {
+            app.websocket_input = Some(String::new());
+            return EventResult::Continue;
+        }
+        KeyCode::Char('J')
+            if !app.filter_active && !app.history_view_active && !app.cookies_view_active =>
+        {
+            app.toggle_jwt_popup();
+            return EventResult::Continue;
+        }
         KeyCode::Char('H') if !app.filter_active => {
             app.toggle_history_view();
             return EventResult::Continue;
@@ -31,6 +92,17 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) -> EventResult {
             app.toggle_history_view();
             return EventResult::Continue;
         }
+        KeyCode::Char('C') if !app.filter_active && !app.history_view_active => {
+            app.toggle_cookies_view();
+            return EventResult::RefreshCookies;
+        }
+        KeyCode::Esc if app.cookies_view_active => {
+            app.toggle_cookies_view();
+            return EventResult::Continue;
+        }
+        KeyCode::Tab if app.cookies_view_active => {
+            return EventResult::Continue;
+        }
         KeyCode::Tab => {
             if app.history_view_active {
                 app.focus = match app.focus {
@@ -46,7 +118,9 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) -> EventResult {
         _ => {}
     }
 
-    if app.history_view_active {
+    if app.cookies_view_active {
+        handle_cookie_list_keys(app, key)
+    } else if app.history_view_active {
         match app.focus {
             Focus::HistoryList => handle_history_list_keys(app, key),
             Focus::HistoryDetail => handle_history_detail_keys(app, key),
@@ -63,6 +137,131 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) -> EventResult {
     }
 }
 
+/// Handles text pasted into the terminal: it goes into the input being edited, and a
+/// `curl` command pasted over the main view becomes a new request.
+pub fn handle_paste(app: &mut App, text: String) -> EventResult {
+    if let Some(ref mut editor) = app.editor {
+        editor.insert_str(&text);
+    } else if let Some(ref mut input) = app.websocket_input {
+        input.push_str(&text);
+    } else if app.history_filter_active {
+        app.history_filter_push_str(text.trim());
+    } else if app.filter_active {
+        app.filter_text.push_str(text.trim());
+        app.selected = 0;
+    } else if !app.history_view_active
+        && !app.cookies_view_active
+        && !app.jwt_popup_active
+        && app.codegen_language.is_none()
+        && text.trim_start().starts_with("curl ")
+    {
+        return EventResult::ImportCurl(text);
+    }
+    EventResult::Continue
+}
+
+fn handle_websocket_input_keys(app: &mut App, key: KeyEvent) -> EventResult {
+    let Some(ref mut input) = app.websocket_input else {
+        return EventResult::Continue;
+    };
+
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return EventResult::Quit;
+        }
+        KeyCode::Esc => app.websocket_input = None,
+        KeyCode::Enter => {
+            let message = std::mem::take(input);
+            app.websocket_input = None;
+            if !message.is_empty() && app.websocket_session {
+                return EventResult::SendWebSocketMessage(message);
+            }
+        }
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Char(c) => input.push(c),
+        _ => {}
+    }
+    EventResult::Continue
+}
+
+fn handle_editor_keys(app: &mut App, key: KeyEvent) -> EventResult {
+    let Some(ref mut editor) = app.editor else {
+        return EventResult::Continue;
+    };
+
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        match key.code {
+            KeyCode::Char('c') => return EventResult::Quit,
+            KeyCode::Char('s') => return EventResult::SaveRequest,
+            KeyCode::Char('n') => editor.add_header(),
+            KeyCode::Char('d') => editor.remove_header(),
+            _ => {}
+        }
+        return EventResult::Continue;
+    }
+
+    editor.error = None;
+    match key.code {
+        KeyCode::Esc => app.editor = None,
+        KeyCode::Tab => editor.next_field(),
+        KeyCode::BackTab => editor.previous_field(),
+        KeyCode::Enter => editor.enter(),
+        KeyCode::Up => editor.up(),
+        KeyCode::Down => editor.down(),
+        KeyCode::Left => editor.left(),
+        KeyCode::Right => editor.right(),
+        KeyCode::Home => editor.home(),
+        KeyCode::End => editor.end(),
+        KeyCode::Backspace => editor.backspace(),
+        KeyCode::Delete => editor.delete(),
+        KeyCode::Char(' ') if editor.field == Field::Method => editor.cycle_method(true),
+        KeyCode::Char(c) => editor.insert_char(c),
+        _ => {}
+    }
+    EventResult::Continue
+}
+
+fn handle_jwt_popup_keys(app: &mut App, key: KeyEvent) -> EventResult {
+    match key.code {
+        KeyCode::Char('q') => return EventResult::Quit,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return EventResult::Quit;
+        }
+        KeyCode::Esc | KeyCode::Char('J') => app.toggle_jwt_popup(),
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.jwt_popup_scroll = app.jwt_popup_scroll.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.jwt_popup_scroll = app.jwt_popup_scroll.saturating_add(1);
+        }
+        _ => {}
+    }
+    EventResult::Continue
+}
+
+fn handle_codegen_popup_keys(app: &mut App, key: KeyEvent) -> EventResult {
+    match key.code {
+        KeyCode::Char('q') => return EventResult::Quit,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return EventResult::Quit;
+        }
+        KeyCode::Esc | KeyCode::Char('g') => app.toggle_codegen_popup(),
+        KeyCode::Enter | KeyCode::Char('y') => return EventResult::CopyCodegen,
+        KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => app.cycle_codegen_language(true),
+        KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => app.cycle_codegen_language(false),
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.codegen_scroll = app.codegen_scroll.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.codegen_scroll = app.codegen_scroll.saturating_add(1);
+        }
+        _ => {}
+    }
+    EventResult::Continue
+}
+
 fn handle_request_list_keys(app: &mut App, key: KeyEvent) -> EventResult {
     if app.filter_active {
         return handle_filter_keys(app, key);
@@ -82,6 +281,31 @@ fn handle_request_list_keys(app: &mut App, key: KeyEvent) -> EventResult {
             app.enter_filter_mode();
             EventResult::Continue
         }
+        KeyCode::Char('y') => EventResult::CopyAsCurl,
+        KeyCode::Char('g') => {
+            app.toggle_codegen_popup();
+            EventResult::Continue
+        }
+        KeyCode::Char('e') => {
+            app.open_editor();
+            EventResult::Continue
+        }
+        KeyCode::Char('a') => {
+            app.open_new_request_editor();
+            EventResult::Continue
+        }
+        KeyCode::Char('c') => EventResult::DuplicateRequest,
+        KeyCode::Char('E') => EventResult::OpenInEditor,
+        KeyCode::Char('d') => {
+            if let Some(request) = app.selected_request() {
+                app.notification = Some(format!(
+                    "Delete {}? Press d to confirm, any other key to cancel",
+                    request.display_name()
+                ));
+                app.confirm_delete = true;
+            }
+            EventResult::Continue
+        }
         _ => EventResult::Continue,
     }
 }
@@ -149,6 +373,7 @@ fn handle_response_keys(app: &mut App, key: KeyEvent) -> EventResult {
             app.switch_to_body_tab();
             EventResult::Continue
         }
+        KeyCode::Char('s') => EventResult::SaveResponseBody,
         KeyCode::Up | KeyCode::Char('k') => {
             match app.response_tab {
                 ResponseTab::Body => app.scroll_up(),
@@ -222,6 +447,83 @@ fn handle_history_list_keys(app: &mut App, key: KeyEvent) -> EventResult {
             EventResult::Continue
         }
         KeyCode::Enter => EventResult::ExecuteHistoryEntry,
+        KeyCode::Char('/') => {
+            app.enter_history_filter_mode();
+            EventResult::Continue
+        }
+        KeyCode::Char('s') => {
+            app.cycle_history_sort();
+            EventResult::Continue
+        }
+        KeyCode::Char('m') => {
+            app.toggle_history_mark();
+            EventResult::Continue
+        }
+        KeyCode::Char('d') => {
+            app.open_history_diff();
+            EventResult::Continue
+        }
+        KeyCode::Char('e') => EventResult::ExportHar { all: false },
+        KeyCode::Char('E') => EventResult::ExportHar { all: true },
+        _ => EventResult::Continue,
+    }
+}
+
+fn handle_history_diff_keys(app: &mut App, key: KeyEvent) -> EventResult {
+    match key.code {
+        KeyCode::Char('q') => return EventResult::Quit,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return EventResult::Quit;
+        }
+        KeyCode::Esc | KeyCode::Char('d') => app.close_history_diff(),
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.history_diff_scroll = app.history_diff_scroll.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.history_diff_scroll = app.history_diff_scroll.saturating_add(1);
+        }
+        KeyCode::PageUp => {
+            app.history_diff_scroll = app.history_diff_scroll.saturating_sub(10);
+        }
+        KeyCode::PageDown => {
+            app.history_diff_scroll = app.history_diff_scroll.saturating_add(10);
+        }
+        _ => {}
+    }
+    EventResult::Continue
+}
+
+fn handle_history_filter_keys(app: &mut App, key: KeyEvent) -> EventResult {
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => EventResult::Quit,
+        KeyCode::Esc => {
+            app.exit_history_filter_mode(false);
+            EventResult::Continue
+        }
+        KeyCode::Enter => {
+            app.exit_history_filter_mode(true);
+            EventResult::Continue
+        }
+        KeyCode::Backspace => {
+            if app.history_filter_text.is_empty() {
+                app.exit_history_filter_mode(false);
+            } else {
+                app.history_filter_backspace();
+            }
+            EventResult::Continue
+        }
+        KeyCode::Up => {
+            app.select_previous_history();
+            EventResult::Continue
+        }
+        KeyCode::Down => {
+            app.select_next_history();
+            EventResult::Continue
+        }
+        KeyCode::Char(c) => {
+            app.history_filter_append_char(c);
+            EventResult::Continue
+        }
         _ => EventResult::Continue,
     }
 }
@@ -251,3 +553,20 @@ fn handle_history_detail_keys(app: &mut App, key: KeyEvent) -> EventResult {
         _ => EventResult::Continue,
     }
 }
+
+fn handle_cookie_list_keys(app: &mut App, key: KeyEvent) -> EventResult {
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.select_previous_cookie();
+            EventResult::Continue
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.select_next_cookie();
+            EventResult::Continue
+        }
+        KeyCode::Char('d') | KeyCode::Delete => EventResult::DeleteCookie,
+        KeyCode::Char('D') => EventResult::DeleteCookieDomain,
+        KeyCode::Char('r') => EventResult::RefreshCookies,
+        _ => EventResult::Continue,
+    }
+}
@@ -1,19 +1,30 @@
 pub mod app;
+pub mod editor;
 pub mod events;
 pub mod ui;
 
-use crate::client::Client;
-use crate::http::{HttpFile, Request};
+use crate::client::{Client, Progress, Response, ResponseEvent, SseEvent, WebSocketMessage};
+use crate::curl;
+use crate::har::Har;
+use crate::history::HistoryStore;
+use crate::http::{HttpFile, Method, Request};
 pub use app::{App, HistoryEntry};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Local};
 use crossterm::{
-    event::Event,
+    event::{DisableBracketedPaste, EnableBracketedPaste, Event},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-pub use events::{EventResult, handle_key_event, poll_event};
+pub use events::{EventResult, handle_key_event, handle_paste, poll_event};
 use ratatui::{Terminal, backend::CrosstermBackend};
-use std::io::{self, Stdout};
+use std::io::{self, Stdout, Write};
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
+use tokio::process::Command;
+use tokio::sync::{mpsc, oneshot};
 pub use ui::render;
 
 pub type Tui = Terminal<CrosstermBackend<Stdout>>;
@@ -21,41 +32,135 @@ pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 pub fn init_terminal() -> io::Result<Tui> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     Terminal::new(backend)
 }
 
 pub fn restore_terminal(terminal: &mut Tui) -> io::Result<()> {
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    )?;
     Ok(())
 }
 
-pub async fn run(http_file: HttpFile) -> anyhow::Result<()> {
+/// Handles for controlling the request currently running in the background.
+struct RunningRequest {
+    stop: oneshot::Sender<()>,
+    /// Sends typed messages into an open WebSocket session.
+    messages: Option<mpsc::UnboundedSender<String>>,
+}
+
+/// Messages sent from background request tasks to the UI loop.
+enum TaskEvent {
+    Progress(Progress),
+    ServerSent(SseEvent),
+    WebSocket(WebSocketMessage),
+    Finished(Box<HistoryEntry>),
+    HistoryFailed(String),
+}
+
+/// Runs the TUI. `history` holds entries from earlier sessions; new entries are also
+/// appended to `history_store` when one is given.
+pub async fn run(
+    http_file: HttpFile,
+    client: Client,
+    history_store: Option<HistoryStore>,
+    history: Vec<HistoryEntry>,
+) -> anyhow::Result<()> {
     let mut terminal = init_terminal()?;
     let mut app = App::new(http_file);
-    let client = Client::new();
+    app.set_history(history);
+    let client = Arc::new(client);
+    let (task_tx, mut task_rx) = mpsc::unbounded_channel();
+    let mut running: Option<RunningRequest> = None;
+    // Set when the terminal could not be set up again after running the editor.
+    let mut terminal_error = None;
+    let history_writer = history_store.map(|store| spawn_history_writer(store, task_tx.clone()));
 
     loop {
+        while let Ok(event) = task_rx.try_recv() {
+            let finished = matches!(event, TaskEvent::Finished(_));
+            if let TaskEvent::Finished(ref entry) = event
+                && let Some((ref entries, _)) = history_writer
+            {
+                let _ = entries.send(HistoryEntry::clone(entry));
+            }
+            handle_task_event(&mut app, event);
+            if finished {
+                app.auth_tokens = client.auth_tokens();
+            }
+        }
+
         terminal.draw(|frame| render(frame, &mut app))?;
 
-        if let Some(event) = poll_event(Duration::from_millis(100))?
-            && let Event::Key(key) = event
-        {
-            match handle_key_event(&mut app, key) {
+        let result = match poll_event(Duration::from_millis(50))? {
+            Some(Event::Key(key)) => Some(handle_key_event(&mut app, key)),
+            Some(Event::Paste(text)) => Some(handle_paste(&mut app, text)),
+            _ => None,
+        };
+        if let Some(result) = result {
+            match result {
                 EventResult::Quit => break,
                 EventResult::ExecuteRequest => {
                     if let Some(request) = app.selected_request().cloned() {
-                        execute_request(&mut app, &client, &mut terminal, request).await;
+                        running = start_request(&mut app, &client, &task_tx, request).or(running);
                     }
                 }
                 EventResult::ExecuteHistoryEntry => {
                     if let Some(entry) = app.selected_history_entry() {
                         let request = entry.request.clone();
-                        execute_request(&mut app, &client, &mut terminal, request).await;
+                        running = start_request(&mut app, &client, &task_tx, request).or(running);
+                    }
+                }
+                EventResult::StopRequest => {
+                    if let Some(running) = running.take() {
+                        let _ = running.stop.send(());
+                    }
+                }
+                EventResult::SendWebSocketMessage(message) => {
+                    if let Some(RunningRequest {
+                        messages: Some(ref messages),
+                        ..
+                    }) = running
+                    {
+                        let _ = messages.send(message);
                     }
                 }
+                EventResult::SaveResponseBody => save_response_body(&mut app),
+                EventResult::ExportHar { all } => export_har(&mut app, all),
+                EventResult::CopyAsCurl => copy_as_curl(&mut app),
+                EventResult::CopyCodegen => copy_codegen(&mut app),
+                EventResult::ImportCurl(command) => import_curl(&mut app, &command),
+                EventResult::SaveRequest => save_editor(&mut app),
+                EventResult::DuplicateRequest => duplicate_request(&mut app),
+                EventResult::DeleteRequest => delete_request(&mut app),
+                EventResult::OpenInEditor => {
+                    if let Err(e) = open_in_editor(&mut terminal, &mut app).await {
+                        terminal_error = Some(e);
+                        break;
+                    }
+                }
+                EventResult::RefreshCookies => {
+                    app.set_cookies(client.cookie_jar().entries());
+                }
+                EventResult::DeleteCookie => {
+                    if let Some(cookie) = app.selected_cookie() {
+                        client
+                            .cookie_jar()
+                            .remove(&cookie.domain, &cookie.path, &cookie.name);
+                    }
+                    app.set_cookies(client.cookie_jar().entries());
+                }
+                EventResult::DeleteCookieDomain => {
+                    if let Some(cookie) = app.selected_cookie() {
+                        client.cookie_jar().remove_domain(&cookie.domain);
+                    }
+                    app.set_cookies(client.cookie_jar().entries());
+                }
                 EventResult::Continue => {}
             }
         }
@@ -65,50 +170,425 @@ pub async fn run(http_file: HttpFile) -> anyhow::Result<()> {
         }
     }
 
-    restore_terminal(&mut terminal)?;
-    Ok(())
+    match terminal_error {
+        None => restore_terminal(&mut terminal)?,
+        // Setting up may have got as far as raw mode.
+        Some(_) => {
+            let _ = disable_raw_mode();
+        }
+    }
+    if let Some((entries, writer)) = history_writer {
+        // Let the writer store what is still queued.
+        drop(entries);
+        let _ = writer.await;
+    }
+    client.cookie_jar().save()?;
+    remove_body_files(&app);
+    match terminal_error {
+        Some(e) => Err(anyhow::anyhow!(
+            "Could not set up the terminal again after running the editor: {}",
+            e
+        )),
+        None => Ok(()),
+    }
 }
 
-async fn execute_request(app: &mut App, client: &Client, terminal: &mut Tui, request: Request) {
+/// Appends finished entries to `store` on a blocking thread, as appending may rewrite
+/// the whole file. Failures are reported back to the UI loop.
+fn spawn_history_writer(
+    mut store: HistoryStore,
+    task_tx: mpsc::UnboundedSender<TaskEvent>,
+) -> (
+    mpsc::UnboundedSender<HistoryEntry>,
+    tokio::task::JoinHandle<()>,
+) {
+    let (entries_tx, mut entries_rx) = mpsc::unbounded_channel::<HistoryEntry>();
+    let writer = tokio::task::spawn_blocking(move || {
+        while let Some(entry) = entries_rx.blocking_recv() {
+            if let Err(err) = store.append(&entry) {
+                let _ = task_tx.send(TaskEvent::HistoryFailed(err.to_string()));
+            }
+        }
+    });
+    (entries_tx, writer)
+}
+
+fn start_request(
+    app: &mut App,
+    client: &Arc<Client>,
+    task_tx: &mpsc::UnboundedSender<TaskEvent>,
+    request: Request,
+) -> Option<RunningRequest> {
+    if app.loading {
+        return None;
+    }
+
     app.loading = true;
+    app.progress = None;
+    app.stream_events.clear();
+    app.websocket_messages.clear();
+    app.websocket_session = request.is_websocket();
+    app.notification = None;
     app.response_scroll = 0;
 
-    terminal.draw(|frame| render(frame, app)).ok();
-
-    let timestamp = SystemTime::now();
+    let client = client.clone();
+    let task_tx = task_tx.clone();
+    let variables = app.http_file.variables.clone();
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    let (messages_tx, messages_rx) = mpsc::unbounded_channel();
+    let messages = request.is_websocket().then_some(messages_tx);
 
-    match client.execute(&request, &app.http_file.variables).await {
-        Ok(response) => {
-            let history_entry = HistoryEntry {
-                request: request.clone(),
-                response: response.clone(),
-                timestamp,
+    tokio::spawn(async move {
+        let timestamp = SystemTime::now();
+        let event_tx = task_tx.clone();
+        let response = if request.is_websocket() {
+            let on_message = move |message: &WebSocketMessage| {
+                let _ = event_tx.send(TaskEvent::WebSocket(message.clone()));
+            };
+            client
+                .execute_websocket(&request, &variables, messages_rx, on_message, stop_rx)
+                .await
+        } else {
+            let on_event = move |event| {
+                let _ = event_tx.send(match event {
+                    ResponseEvent::Progress(progress) => TaskEvent::Progress(progress),
+                    ResponseEvent::ServerSent(event) => TaskEvent::ServerSent(event),
+                });
             };
-            app.add_history_entry(history_entry);
-            app.last_response = Some(response);
+            client
+                .execute_streaming(&request, &variables, on_event, stop_rx)
+                .await
+        }
+        .unwrap_or_else(|e| Response::error(format!("Error: {}", e)));
+        let sent = response.request.clone().unwrap_or_else(|| {
+            request
+                .substituted(&variables)
+                .unwrap_or_else(|_| request.clone())
+                .into()
+        });
+        let _ = task_tx.send(TaskEvent::Finished(Box::new(HistoryEntry {
+            request,
+            sent,
+            response,
+            timestamp,
+        })));
+    });
+
+    Some(RunningRequest {
+        stop: stop_tx,
+        messages,
+    })
+}
+
+fn handle_task_event(app: &mut App, event: TaskEvent) {
+    match event {
+        TaskEvent::Progress(progress) => app.progress = Some(progress),
+        TaskEvent::ServerSent(event) => app.stream_events.push(event),
+        TaskEvent::WebSocket(message) => app.websocket_messages.push(message),
+        TaskEvent::Finished(entry) => {
+            app.set_response(entry.response.clone());
+            app.add_history_entry(*entry);
+            app.loading = false;
+            app.progress = None;
+            app.stream_events.clear();
+            app.websocket_messages.clear();
+            app.websocket_session = false;
+            app.websocket_input = None;
+
+            if app.history_view_active {
+                app.select_history_index(app.history.len().saturating_sub(1));
+            }
+        }
+        TaskEvent::HistoryFailed(err) => {
+            app.notification = Some(format!("Failed to save history: {}", err));
+        }
+    }
+}
+
+fn save_response_body(app: &mut App) {
+    let Some(ref response) = app.last_response else {
+        return;
+    };
+
+    let dir = app.http_file.path.parent().unwrap_or(Path::new(""));
+    let timestamp: DateTime<Local> = Local::now();
+    let file_name = format!(
+        "response-{}.{}",
+        timestamp.format("%Y%m%d-%H%M%S"),
+        body_extension(response.content_type())
+    );
+    let path = dir.join(file_name);
+
+    app.notification = Some(match response.save_body(&path) {
+        Ok(()) => format!("Saved body to {}", path.display()),
+        Err(e) => format!("Saving body failed: {}", e),
+    });
+}
+
+fn copy_as_curl(app: &mut App) {
+    let Some(request) = app.selected_request() else {
+        return;
+    };
+    if request.method == Method::Grpc {
+        app.notification = Some("gRPC requests cannot be copied as curl".to_string());
+        return;
+    }
+
+    app.notification = Some(
+        match curl::to_curl(request, &app.http_file.variables)
+            .map_err(|e| e.to_string())
+            .and_then(|command| copy_to_clipboard(&command).map_err(|e| e.to_string()))
+        {
+            Ok(()) => "Copied curl command to the clipboard".to_string(),
+            Err(e) => format!("Copy as curl failed: {}", e),
+        },
+    );
+}
+
+fn copy_codegen(app: &mut App) {
+    let Some(language) = app.codegen_language else {
+        return;
+    };
+    let Some(output) = app.codegen_output() else {
+        return;
+    };
+
+    app.notification = Some(
+        match output
+            .map_err(|e| e.to_string())
+            .and_then(|code| copy_to_clipboard(&code).map_err(|e| e.to_string()))
+        {
+            Ok(()) => format!("Copied {} code to the clipboard", language.label()),
+            Err(e) => format!("Copy as code failed: {}", e),
+        },
+    );
+    app.codegen_language = None;
+}
+
+/// Sets the system clipboard through the terminal with an OSC 52 sequence, which also
+/// works over SSH.
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", BASE64.encode(text))?;
+    stdout.flush()
+}
+
+fn import_curl(app: &mut App, command: &str) {
+    let result = curl::parse_curl(command).and_then(|request| {
+        let name = request.display_name();
+        app.http_file.append_request(request)?;
+        Ok(name)
+    });
+
+    app.notification = Some(match result {
+        Ok(name) => {
+            if app.filter_active {
+                app.exit_filter_mode();
+            }
+            app.selected = app.http_file.requests.len() - 1;
+            app.selected_variable = 0;
+            app.request_details_scroll = 0;
+            format!("Added {} to {}", name, app.http_file.path.display())
+        }
+        Err(e) => format!("Importing curl command failed: {}", e),
+    });
+}
+
+/// Applies the editor's request to the file and saves it. The editor stays open with
+/// the error if the request is not valid or the file cannot be written.
+fn save_editor(app: &mut App) {
+    let Some(mut editor) = app.editor.take() else {
+        return;
+    };
+    if let Err(e) = editor.validate() {
+        editor.error = Some(e);
+        app.editor = Some(editor);
+        return;
+    }
+
+    let request = editor.to_request();
+    let name = request.display_name();
+    let index = editor.index;
+    let result = save_requests(app, |requests| {
+        if editor.is_new {
+            requests.insert(index, request);
+        } else {
+            requests[index] = request;
+        }
+    });
+
+    match result {
+        Ok(()) => {
+            app.select_index(index);
+            app.notification = Some(format!(
+                "Saved {} to {}",
+                name,
+                app.http_file.path.display()
+            ));
         }
         Err(e) => {
-            let error_response = crate::client::Response {
-                status: 0,
-                status_text: "Error".to_string(),
-                headers: vec![],
-                body: format!("Error: {}", e),
-                duration: Duration::ZERO,
-            };
+            editor.error = Some(format!("Saving failed: {}", e));
+            app.editor = Some(editor);
+        }
+    }
+}
 
-            let history_entry = HistoryEntry {
-                request: request.clone(),
-                response: error_response.clone(),
-                timestamp,
-            };
-            app.add_history_entry(history_entry);
-            app.last_response = Some(error_response);
+fn duplicate_request(app: &mut App) {
+    let Some(index) = app.selected_index() else {
+        return;
+    };
+    let mut copy = app.http_file.requests[index].clone();
+    copy.name = Some(format!("{} (copy)", copy.display_name()));
+    let name = copy.display_name();
+
+    app.notification = Some(
+        match save_requests(app, |requests| requests.insert(index + 1, copy)) {
+            Ok(()) => {
+                app.select_index(index + 1);
+                format!("Added {}", name)
+            }
+            Err(e) => format!("Duplicating failed: {}", e),
+        },
+    );
+}
+
+fn delete_request(app: &mut App) {
+    let Some(index) = app.selected_index() else {
+        return;
+    };
+    let name = app.http_file.requests[index].display_name();
+
+    app.notification = Some(
+        match save_requests(app, |requests| {
+            requests.remove(index);
+        }) {
+            Ok(()) => {
+                app.select_index(index);
+                format!("Deleted {}", name)
+            }
+            Err(e) => format!("Deleting failed: {}", e),
+        },
+    );
+}
+
+/// Suspends the TUI to edit the file in `$EDITOR` at the selected request, then reads
+/// the file again, keeping the same request selected. Fails only if the terminal
+/// cannot be set up again afterwards, which leaves it unusable for the TUI.
+async fn open_in_editor(terminal: &mut Tui, app: &mut App) -> io::Result<()> {
+    let index = app.selected_index();
+    let name = index.and_then(|index| app.http_file.requests[index].name.clone());
+    let line = index.and_then(|index| app.http_file.request_line(index));
+
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let mut command = Command::new(program);
+    command.args(words);
+    if let Some(line) = line {
+        command.arg(format!("+{}", line));
+    }
+    command.arg(&app.http_file.path);
+
+    if let Err(e) = restore_terminal(terminal) {
+        app.notification = Some(format!("Could not hand the terminal to {}: {}", program, e));
+        return Ok(());
+    }
+    let status = command.status().await;
+    *terminal = init_terminal()?;
+
+    if let Err(e) = status {
+        app.notification = Some(format!("Could not run {}: {}", program, e));
+        return Ok(());
+    }
+    app.notification = Some(match app.http_file.reload() {
+        Ok(()) => {
+            let requests = &app.http_file.requests;
+            let selected = requests
+                .iter()
+                .position(|request| name.is_some() && request.name == name)
+                .or(index);
+            app.select_index(selected.unwrap_or(0));
+            format!("Reloaded {}", app.http_file.path.display())
         }
+        Err(e) => format!("Reloading failed: {}", e),
+    });
+    Ok(())
+}
+
+/// Changes the requests and writes the file, undoing the change if writing fails.
+fn save_requests(
+    app: &mut App,
+    change: impl FnOnce(&mut Vec<Request>),
+) -> crate::error::Result<()> {
+    let requests = app.http_file.requests.clone();
+    change(&mut app.http_file.requests);
+    let result = app.http_file.save();
+    if result.is_err() {
+        app.http_file.requests = requests;
+    }
+    result
+}
+
+fn export_har(app: &mut App, all: bool) {
+    let entries = app.history_export_entries(all);
+    if entries.is_empty() {
+        return;
+    }
+    let count = entries.len();
+    let har = Har::from_entries(entries);
+
+    let dir = app.http_file.path.parent().unwrap_or(Path::new(""));
+    let stem = app
+        .http_file
+        .path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "requests".to_string());
+    let timestamp: DateTime<Local> = Local::now();
+    let path = dir.join(format!(
+        "{}-{}.har",
+        stem,
+        timestamp.format("%Y%m%d-%H%M%S")
+    ));
+
+    app.notification = Some(match std::fs::write(&path, har.to_json()) {
+        Ok(()) => format!("Exported {} entries to {}", count, path.display()),
+        Err(e) => format!("HAR export failed: {}", e),
+    });
+}
+
+fn body_extension(content_type: Option<&str>) -> &'static str {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .unwrap_or_default()
+        .trim();
+
+    match mime {
+        m if m.ends_with("json") => "json",
+        m if m.ends_with("xml") => "xml",
+        "text/html" => "html",
+        "text/csv" => "csv",
+        m if m.starts_with("text/") => "txt",
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        "application/gzip" | "application/x-gzip" => "gz",
+        _ => "bin",
     }
+}
 
-    app.loading = false;
+fn remove_body_files(app: &App) {
+    let responses = app
+        .history
+        .iter()
+        .map(|entry| &entry.response)
+        .chain(app.last_response.as_ref());
 
-    if app.history_view_active {
-        app.selected_history = app.history.len().saturating_sub(1);
+    for response in responses {
+        if let Some(ref body_file) = response.body_file {
+            let _ = std::fs::remove_file(body_file);
+        }
     }
 }
@@ -1,15 +1,23 @@
 use super::app::{App, Focus, ResponseTab};
-use chrono::{DateTime, Local};
+use super::editor::Field;
+use crate::client::{Progress, Response, SseEvent, Timings, WebSocketMessage};
+use crate::codegen::Language;
+use crate::diff::{BodyDiff, Change, DiffLine, ResponseDiff};
+use crate::jwt;
+use chrono::{DateTime, Local, Utc};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Padding, Paragraph, Wrap},
 };
 
 pub fn render(frame: &mut Frame, app: &mut App) {
-    if app.history_view_active {
+    if app.cookies_view_active {
+        render_cookies_view(frame, app);
+    } else if app.history_view_active {
+        app.update_history_formatted_body();
         render_history_view(frame, app);
     } else {
         render_main_view(frame, app);
@@ -36,6 +44,309 @@ fn render_main_view(frame: &mut Frame, app: &mut App) {
     render_response_panel(frame, app, top_chunks[1]);
     render_request_details(frame, app, bottom_chunks[0]);
     render_variables_panel(frame, app, bottom_chunks[1]);
+
+    if app.jwt_popup_active {
+        render_jwt_popup(frame, app);
+    }
+
+    if app.codegen_language.is_some() {
+        render_codegen_popup(frame, app);
+    }
+
+    if app.editor.is_some() {
+        render_request_editor(frame, app);
+    }
+}
+
+fn render_request_editor(frame: &mut Frame, app: &mut App) {
+    let Some(ref mut editor) = app.editor else {
+        return;
+    };
+    const LABEL_WIDTH: u16 = 9;
+
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(if editor.is_new {
+            " New request "
+        } else {
+            " Edit request "
+        })
+        .title_bottom(
+            " Tab next field, ←/→ method, Ctrl+N add header, Ctrl+D remove header, Ctrl+S save, Esc cancel ",
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let header_rows = editor.headers.len().max(1) as u16;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4 + header_rows),
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let label = |text: &str, focused: bool| {
+        let style = if focused {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Yellow)
+        };
+        Span::styled(format!("{:1$}", text, LABEL_WIDTH as usize), style)
+    };
+    let in_headers = matches!(editor.field, Field::HeaderName(_) | Field::HeaderValue(_));
+
+    let method_color = match editor.method {
+        crate::http::Method::Get => Color::Green,
+        crate::http::Method::Post => Color::Yellow,
+        crate::http::Method::Put => Color::Blue,
+        crate::http::Method::Patch => Color::Cyan,
+        crate::http::Method::Delete => Color::Red,
+        crate::http::Method::WebSocket => Color::Magenta,
+        crate::http::Method::Grpc => Color::LightBlue,
+        _ => Color::White,
+    };
+    let mut lines = vec![
+        Line::from(vec![
+            label("Name", editor.field == Field::Name),
+            Span::raw(editor.name.clone()),
+        ]),
+        Line::from(vec![
+            label("Method", editor.field == Field::Method),
+            Span::styled(
+                format!("◀ {} ▶", editor.method),
+                Style::default()
+                    .fg(method_color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(vec![
+            label("URL", editor.field == Field::Url),
+            Span::raw(editor.url.clone()),
+        ]),
+        Line::from(label("Headers", in_headers)),
+    ];
+    if editor.headers.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  Ctrl+N adds a header",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for (key, value) in &editor.headers {
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::styled(key.clone(), Style::default().fg(Color::Cyan)),
+            Span::raw(": "),
+            Span::raw(value.clone()),
+        ]));
+    }
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+    frame.render_widget(
+        Paragraph::new(Line::from(label("Body", editor.field == Field::Body))),
+        chunks[1],
+    );
+
+    let body_height = chunks[2].height;
+    let body_line = editor.body_line as u16;
+    if body_line < editor.body_scroll {
+        editor.body_scroll = body_line;
+    } else if body_height > 0 && body_line >= editor.body_scroll + body_height {
+        editor.body_scroll = body_line + 1 - body_height;
+    }
+    let body: Vec<Line> = editor
+        .body
+        .iter()
+        .map(|line| Line::raw(line.clone()))
+        .collect();
+    frame.render_widget(
+        Paragraph::new(body).scroll((editor.body_scroll, 0)),
+        chunks[2],
+    );
+
+    if let Some(ref error) = editor.error {
+        frame.render_widget(
+            Paragraph::new(error.as_str()).style(Style::default().fg(Color::Red)),
+            chunks[3],
+        );
+    }
+
+    let column = editor.column as u16;
+    let (x, y) = match editor.field {
+        Field::Name => (chunks[0].x + LABEL_WIDTH + column, chunks[0].y),
+        Field::Method => (chunks[0].x + LABEL_WIDTH, chunks[0].y + 1),
+        Field::Url => (chunks[0].x + LABEL_WIDTH + column, chunks[0].y + 2),
+        Field::HeaderName(row) => (chunks[0].x + 2 + column, chunks[0].y + 4 + row as u16),
+        Field::HeaderValue(row) => {
+            let key = editor.headers[row].0.chars().count() as u16;
+            (chunks[0].x + 4 + key + column, chunks[0].y + 4 + row as u16)
+        }
+        Field::Body => (
+            chunks[2].x + column,
+            chunks[2].y + body_line - editor.body_scroll,
+        ),
+    };
+    if editor.field != Field::Method {
+        let right = inner.x + inner.width.saturating_sub(1);
+        frame.set_cursor_position((x.min(right), y));
+    }
+}
+
+fn render_codegen_popup(frame: &mut Frame, app: &App) {
+    let area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Copy as code ")
+        .title_bottom(" h/l language, j/k scroll, y copy, Esc close ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(22), Constraint::Min(0)])
+        .split(inner);
+
+    let languages: Vec<ListItem> = Language::ALL
+        .iter()
+        .map(|language| {
+            let style = if app.codegen_language == Some(*language) {
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(Span::styled(format!(" {} ", language.label()), style))
+        })
+        .collect();
+    frame.render_widget(
+        List::new(languages).block(Block::default().borders(Borders::RIGHT)),
+        chunks[0],
+    );
+
+    let lines: Vec<Line> = match app.codegen_output() {
+        Some(Ok(code)) => code
+            .lines()
+            .map(|line| Line::raw(line.to_string()))
+            .collect(),
+        Some(Err(e)) => vec![Line::from(Span::styled(
+            e.to_string(),
+            Style::default().fg(Color::Red),
+        ))],
+        None => vec![Line::from(Span::styled(
+            "No request selected",
+            Style::default().fg(Color::DarkGray),
+        ))],
+    };
+    let code = Paragraph::new(lines)
+        .block(Block::default().padding(Padding::horizontal(1)))
+        .scroll((app.codegen_scroll, 0));
+    frame.render_widget(code, chunks[1]);
+}
+
+fn render_jwt_popup(frame: &mut Frame, app: &App) {
+    let area = centered_rect(80, 80, frame.area());
+    let now = Utc::now();
+    let tokens = app.jwts();
+
+    let mut lines: Vec<Line> = Vec::new();
+    if tokens.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No JWTs in the selected request or the last response",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (source, token) in &tokens {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            source.clone(),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+
+        for (label, time) in [
+            ("Expires", token.expires_at()),
+            ("Issued", token.issued_at()),
+            ("Not before", token.not_before()),
+        ] {
+            let Some(time) = time else {
+                continue;
+            };
+            let color = if label == "Expires" && time <= now {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:11}", label), Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    format!(
+                        "{} ({})",
+                        time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                        jwt::format_relative(time, now)
+                    ),
+                    Style::default().fg(color),
+                ),
+            ]));
+        }
+
+        for (title, value) in [("Header", &token.header), ("Payload", &token.payload)] {
+            lines.push(Line::from(Span::styled(
+                title,
+                Style::default().fg(Color::Yellow),
+            )));
+            let json = serde_json::to_string_pretty(value).unwrap_or_default();
+            lines.extend(json.lines().map(|line| Line::raw(line.to_string())));
+        }
+    }
+
+    let popup = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(" JWT ")
+                .title_bottom(" j/k scroll, Esc close ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .scroll((app.jwt_popup_scroll, 0));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup, area);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
 }
 
 fn render_request_list(frame: &mut Frame, app: &App, area: Rect) {
@@ -59,6 +370,8 @@ fn render_request_list(frame: &mut Frame, app: &App, area: Rect) {
                 crate::http::Method::Put => Color::Blue,
                 crate::http::Method::Patch => Color::Cyan,
                 crate::http::Method::Delete => Color::Red,
+                crate::http::Method::WebSocket => Color::Magenta,
+                crate::http::Method::Grpc => Color::LightBlue,
                 _ => Color::White,
             };
 
@@ -128,6 +441,8 @@ fn render_request_details(frame: &mut Frame, app: &mut App, area: Rect) {
             crate::http::Method::Put => Color::Blue,
             crate::http::Method::Patch => Color::Cyan,
             crate::http::Method::Delete => Color::Red,
+            crate::http::Method::WebSocket => Color::Magenta,
+            crate::http::Method::Grpc => Color::LightBlue,
             _ => Color::White,
         };
 
@@ -144,6 +459,19 @@ fn render_request_details(frame: &mut Frame, app: &mut App, area: Rect) {
 
         for (key, value) in &request.headers {
             lines.push(Line::from(format!("{}: {}", key, value)));
+            if let Some(hint) = app.authorization_hint(key, value) {
+                lines.push(Line::from(Span::styled(
+                    format!("  ↳ {}", hint),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+            if let Some((hint, expired)) = app.jwt_hint(value) {
+                let color = if expired { Color::Red } else { Color::DarkGray };
+                lines.push(Line::from(Span::styled(
+                    format!("  ↳ {}", hint),
+                    Style::default().fg(color),
+                )));
+            }
         }
 
         if !request.headers.is_empty() && request.body.is_some() {
@@ -186,10 +514,20 @@ fn render_request_details(frame: &mut Frame, app: &mut App, area: Rect) {
 fn render_variables_panel(frame: &mut Frame, app: &App, area: Rect) {
     let used_variables = app.get_used_variables();
 
-    let items: Vec<ListItem> = used_variables
+    let mut items: Vec<ListItem> = used_variables
         .iter()
         .map(|(name, value)| ListItem::new(format!("@{} = {}", name, value)))
         .collect();
+    items.extend(app.get_used_auth_tokens().into_iter().map(|(name, token)| {
+        let (status, color) = match token {
+            None => ("not fetched yet".to_string(), Color::DarkGray),
+            Some(token) => format_token_expiry(token.expires_at),
+        };
+        ListItem::new(Line::from(vec![
+            Span::raw(format!("$auth.token(\"{}\") ", name)),
+            Span::styled(status, Style::default().fg(color)),
+        ]))
+    }));
 
     let border_style = if app.focus == Focus::VariablesList {
         Style::default().fg(Color::Cyan)
@@ -223,14 +561,37 @@ fn render_variables_panel(frame: &mut Frame, app: &App, area: Rect) {
 fn render_response_panel(frame: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .constraints([Constraint::Length(4), Constraint::Min(0)])
         .split(area);
 
     let status_content = if app.loading {
-        Line::from(vec![Span::styled(
-            "Loading...",
+        let loading = match app.progress {
+            _ if app.websocket_session => format!(
+                "WebSocket open... {} messages – press i to type, x to close",
+                app.websocket_messages.len()
+            ),
+            _ if !app.stream_events.is_empty() => format!(
+                "Streaming... {} events – press x to stop",
+                app.stream_events.len()
+            ),
+            Some(Progress {
+                received,
+                total: Some(total),
+            }) if total > 0 => format!(
+                "Downloading... {} / {} ({}%)",
+                format_size(received),
+                format_size(total),
+                received * 100 / total
+            ),
+            Some(Progress { received, .. }) => {
+                format!("Downloading... {}", format_size(received))
+            }
+            None => "Loading...".to_string(),
+        };
+        vec![Line::from(vec![Span::styled(
+            loading,
             Style::default().fg(Color::Yellow),
-        )])
+        )])]
     } else if let Some(ref response) = app.last_response {
         let status_color = if response.status < 300 {
             Color::Green
@@ -240,32 +601,45 @@ fn render_response_panel(frame: &mut Frame, app: &App, area: Rect) {
             Color::Red
         };
 
-        Line::from(vec![
-            Span::styled(
-                format!("{} {}", response.status, response.status_text),
-                Style::default()
-                    .fg(status_color)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw("  "),
-            Span::styled(
-                format!("{:.2?}", response.duration),
-                Style::default().fg(Color::DarkGray),
-            ),
-        ])
+        vec![
+            Line::from(vec![
+                Span::styled(
+                    format!("{} {}", response.status, response.status_text),
+                    Style::default()
+                        .fg(status_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::styled(
+                    format!("{:.2?}", response.duration),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw("  "),
+                Span::styled(
+                    body_size_label(response),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]),
+            timing_waterfall(&response.timings),
+        ]
     } else {
-        Line::from(Span::styled(
+        vec![Line::from(Span::styled(
             "No response yet. Press Enter to send request.",
             Style::default().fg(Color::DarkGray),
-        ))
+        ))]
     };
 
-    let status_block = Paragraph::new(status_content).block(
-        Block::default()
-            .title(" Status ")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::DarkGray)),
-    );
+    let mut status_block = Block::default()
+        .title(" Status ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    if let Some(ref notification) = app.notification {
+        status_block = status_block.title_bottom(Line::from(Span::styled(
+            format!(" {} ", notification),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    let status_block = Paragraph::new(status_content).block(status_block);
 
     frame.render_widget(status_block, chunks[0]);
 
@@ -282,21 +656,82 @@ fn render_response_panel(frame: &mut Frame, app: &App, area: Rect) {
 
     match app.response_tab {
         ResponseTab::Body => {
-            let body_content = app
-                .last_response
-                .as_ref()
-                .map(|r| format_body(&r.body))
-                .unwrap_or_default();
+            let mut block = Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(border_style);
+
+            if let Some(ref response) = app.last_response
+                && response.is_truncated()
+            {
+                block = block.title_bottom(Line::from(Span::styled(
+                    format!(
+                        " truncated at {} of {} – press s to save full body ",
+                        format_size(response.body.len() as u64),
+                        format_size(response.size())
+                    ),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
 
-            let body_block = Paragraph::new(body_content)
-                .block(
-                    Block::default()
-                        .title(title)
-                        .borders(Borders::ALL)
-                        .border_style(border_style),
-                )
-                .wrap(Wrap { trim: false })
-                .scroll((app.response_scroll, 0));
+            if app.loading && app.websocket_session {
+                let hint = match app.websocket_input {
+                    Some(ref input) => format!(" > {}█ ", input),
+                    None => " i: type message  x: close ".to_string(),
+                };
+                block = block.title_bottom(Line::from(Span::styled(
+                    hint,
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+
+            let body_block = match app.last_response {
+                _ if app.loading && app.websocket_session => {
+                    let rows = chunks[1].height.saturating_sub(2) as usize;
+                    let start = app.websocket_messages.len().saturating_sub(rows);
+                    let text = format_websocket_messages(&app.websocket_messages[start..]);
+                    let lines: Vec<&str> = text.lines().collect();
+                    let visible: Vec<Line> = lines[lines.len().saturating_sub(rows)..]
+                        .iter()
+                        .map(|line| Line::raw(line.to_string()))
+                        .collect();
+                    Paragraph::new(visible).block(block)
+                }
+                _ if app.loading && !app.stream_events.is_empty() => {
+                    // Follow the live stream by showing the most recent events.
+                    let rows = chunks[1].height.saturating_sub(2) as usize;
+                    let start = app.stream_events.len().saturating_sub(rows);
+                    let text = format_sse_events(&app.stream_events[start..]);
+                    let lines: Vec<&str> = text.lines().collect();
+                    let visible: Vec<Line> = lines[lines.len().saturating_sub(rows)..]
+                        .iter()
+                        .map(|line| Line::raw(line.to_string()))
+                        .collect();
+                    Paragraph::new(visible).block(block)
+                }
+                Some(ref response) if response.is_binary() => {
+                    // Only the visible rows are rendered, so large payloads stay cheap.
+                    let rows = chunks[1].height.saturating_sub(2) as usize;
+                    let offset = app.response_scroll as usize * HEX_BYTES_PER_LINE;
+                    Paragraph::new(hex_dump_lines(&response.body, offset, rows)).block(block)
+                }
+                Some(_) => {
+                    // Scrolling skips whole lines of the cached body instead of handing the
+                    // full text to the paragraph on every frame.
+                    let rows = chunks[1].height.saturating_sub(2) as usize;
+                    let visible: Vec<Line> = app
+                        .formatted_body
+                        .lines()
+                        .skip(app.response_scroll as usize)
+                        .take(rows)
+                        .map(Line::raw)
+                        .collect();
+                    Paragraph::new(visible)
+                        .block(block)
+                        .wrap(Wrap { trim: false })
+                }
+                None => Paragraph::new("").block(block),
+            };
 
             frame.render_widget(body_block, chunks[1]);
         }
@@ -334,9 +769,18 @@ fn render_history_view(frame: &mut Frame, app: &App) {
         .constraints([Constraint::Length(1), Constraint::Min(0)])
         .split(frame.area());
 
-    let help = Paragraph::new(
-        " History | ESC/H: back | Tab: switch panels | Enter: re-execute | j/k: navigate",
-    )
+    if let Some(ref diff) = app.history_diff {
+        let help = Paragraph::new(" Diff | ESC/d: back | j/k: scroll")
+            .style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(help, main_chunks[0]);
+        render_history_diff(frame, app, diff, main_chunks[1]);
+        return;
+    }
+
+    let help = Paragraph::new(format!(
+        " History | ESC/H: back | Tab: switch panels | Enter: re-execute | j/k: navigate | /: filter | s: sort | m: mark ({}/2) | d: diff | e/E: export HAR",
+        app.history_marks.len()
+    ))
     .style(Style::default().fg(Color::DarkGray));
     frame.render_widget(help, main_chunks[0]);
 
@@ -350,18 +794,29 @@ fn render_history_view(frame: &mut Frame, app: &App) {
 }
 
 fn render_history_list(frame: &mut Frame, app: &App, area: Rect) {
-    let items: Vec<ListItem> = app
-        .history
+    let show_filter = app.history_filter_active || !app.history_filter_text.is_empty();
+    let (list_area, filter_area) = if show_filter {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+
+    let filtered = app.filtered_history();
+    let items: Vec<ListItem> = filtered
         .iter()
-        .enumerate()
-        .rev()
-        .map(|(_, entry)| {
+        .map(|(index, entry)| {
             let method_color = match entry.request.method {
                 crate::http::Method::Get => Color::Green,
                 crate::http::Method::Post => Color::Yellow,
                 crate::http::Method::Put => Color::Blue,
                 crate::http::Method::Patch => Color::Cyan,
                 crate::http::Method::Delete => Color::Red,
+                crate::http::Method::WebSocket => Color::Magenta,
+                crate::http::Method::Grpc => Color::LightBlue,
                 _ => Color::White,
             };
 
@@ -376,7 +831,18 @@ fn render_history_list(frame: &mut Frame, app: &App, area: Rect) {
             let timestamp: DateTime<Local> = entry.timestamp.into();
             let time_str = timestamp.format("%H:%M:%S").to_string();
 
+            let mark = match app.history_mark_label(*index) {
+                Some(label) => Span::styled(
+                    format!("{} ", label),
+                    Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                None => Span::raw("  "),
+            };
+
             let content = Line::from(vec![
+                mark,
                 Span::styled(time_str, Style::default().fg(Color::DarkGray)),
                 Span::raw(" "),
                 Span::styled(
@@ -404,29 +870,220 @@ fn render_history_list(frame: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(Color::DarkGray)
     };
 
-    let title = format!(" History ({}) ", app.history.len());
+    let count = if filtered.len() == app.history.len() {
+        app.history.len().to_string()
+    } else {
+        format!("{}/{}", filtered.len(), app.history.len())
+    };
+    let title = format!(" History ({}, by {}) ", count, app.history_sort.label());
+
+    let mut block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(border_style);
+    if let Some(ref notification) = app.notification {
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {} ", notification),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
 
-    let list = List::new(items)
-        .block(
-            Block::default()
-                .title(title)
-                .borders(Borders::ALL)
-                .border_style(border_style),
+    let mut list_state = ListState::default().with_selected(Some(app.selected_history));
+    frame.render_stateful_widget(list, list_area, &mut list_state);
+
+    if let Some(filter_area) = filter_area {
+        let color = if app.history_filter_active {
+            Color::Yellow
+        } else {
+            Color::DarkGray
+        };
+        let filter_line = Paragraph::new(format!("/{}", app.history_filter_text))
+            .style(Style::default().fg(color));
+        frame.render_widget(filter_line, filter_area);
+    }
+}
+
+fn render_history_diff(frame: &mut Frame, app: &App, diff: &ResponseDiff, area: Rect) {
+    let entry_label = |position: usize| {
+        let entry = &app.history[app.history_marks[position]];
+        let timestamp: DateTime<Local> = entry.timestamp.into();
+        format!(
+            "{} {} {} {}",
+            timestamp.format("%H:%M:%S"),
+            entry.response.status,
+            entry.request.method,
+            entry.request.name.as_deref().unwrap_or(&entry.request.url)
         )
-        .highlight_style(
-            Style::default()
-                .bg(Color::DarkGray)
-                .add_modifier(Modifier::BOLD),
-        );
+    };
 
-    let display_index = if app.history.is_empty() {
-        0
+    let mut summary: Vec<Line> = vec![
+        Line::from(vec![
+            Span::styled("A ", Style::default().fg(Color::Red)),
+            Span::raw(entry_label(0)),
+        ]),
+        Line::from(vec![
+            Span::styled("B ", Style::default().fg(Color::Green)),
+            Span::raw(entry_label(1)),
+        ]),
+        Line::from(""),
+    ];
+
+    summary.push(match diff.status {
+        Some((old, new)) => Line::from(Span::styled(
+            format!("Status: {} → {}", old, new),
+            Style::default().fg(Color::Yellow),
+        )),
+        None => Line::from(Span::styled(
+            "Status: same",
+            Style::default().fg(Color::DarkGray),
+        )),
+    });
+    if diff.headers.is_empty() {
+        summary.push(Line::from(Span::styled(
+            "Headers: same",
+            Style::default().fg(Color::DarkGray),
+        )));
     } else {
-        app.history.len() - 1 - app.selected_history
+        summary.push(Line::from("Headers:"));
+        summary.extend(diff.headers.iter().map(change_line));
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((summary.len() as u16 + 2).min(area.height / 2)),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let summary = Paragraph::new(summary).block(
+        Block::default()
+            .title(" Diff A → B ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    frame.render_widget(summary, chunks[0]);
+
+    let body_block = |title: &'static str| {
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
     };
 
-    let mut list_state = ListState::default().with_selected(Some(display_index));
-    frame.render_stateful_widget(list, area, &mut list_state);
+    match &diff.body {
+        BodyDiff::Json(changes) => {
+            let lines: Vec<Line> = if changes.is_empty() {
+                vec![Line::from(Span::styled(
+                    "JSON bodies are equal",
+                    Style::default().fg(Color::DarkGray),
+                ))]
+            } else {
+                changes.iter().map(change_line).collect()
+            };
+            let body = Paragraph::new(lines)
+                .block(body_block(" Body (JSON) "))
+                .scroll((app.history_diff_scroll, 0));
+            frame.render_widget(body, chunks[1]);
+        }
+        BodyDiff::Text(diff_lines) => {
+            let (old_lines, new_lines) = side_by_side(diff_lines);
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(chunks[1]);
+            for (lines, title, area) in [
+                (old_lines, " Body A ", columns[0]),
+                (new_lines, " Body B ", columns[1]),
+            ] {
+                let body = Paragraph::new(lines)
+                    .block(body_block(title))
+                    .scroll((app.history_diff_scroll, 0));
+                frame.render_widget(body, area);
+            }
+        }
+        BodyDiff::Binary {
+            old_size,
+            new_size,
+            equal,
+        } => {
+            let text = format!(
+                "Binary bodies: {} → {}, {}",
+                format_size(*old_size),
+                format_size(*new_size),
+                if *equal { "equal" } else { "different" }
+            );
+            let body = Paragraph::new(text).block(body_block(" Body "));
+            frame.render_widget(body, chunks[1]);
+        }
+    }
+}
+
+fn change_line(change: &Change) -> Line<'static> {
+    match change {
+        Change::Added { path, value } => Line::from(Span::styled(
+            format!("+ {}: {}", path, value),
+            Style::default().fg(Color::Green),
+        )),
+        Change::Removed { path, value } => Line::from(Span::styled(
+            format!("- {}: {}", path, value),
+            Style::default().fg(Color::Red),
+        )),
+        Change::Changed { path, old, new } => Line::from(vec![
+            Span::styled(format!("~ {}: ", path), Style::default().fg(Color::Yellow)),
+            Span::styled(old.clone(), Style::default().fg(Color::Red)),
+            Span::raw(" → "),
+            Span::styled(new.clone(), Style::default().fg(Color::Green)),
+        ]),
+    }
+}
+
+/// Lays a line diff out in two aligned columns, pairing each run of removed lines with
+/// the added lines that follow it.
+fn side_by_side(lines: &[DiffLine]) -> (Vec<Line<'static>>, Vec<Line<'static>>) {
+    let mut old_column = Vec::new();
+    let mut new_column = Vec::new();
+    let mut removed: Vec<Line<'static>> = Vec::new();
+    let mut added: Vec<Line<'static>> = Vec::new();
+
+    fn flush(
+        removed: &mut Vec<Line<'static>>,
+        added: &mut Vec<Line<'static>>,
+        old_column: &mut Vec<Line<'static>>,
+        new_column: &mut Vec<Line<'static>>,
+    ) {
+        let rows = removed.len().max(added.len());
+        removed.resize(rows, Line::from(""));
+        added.resize(rows, Line::from(""));
+        old_column.append(removed);
+        new_column.append(added);
+    }
+
+    for line in lines {
+        match line {
+            DiffLine::Same(text) => {
+                flush(&mut removed, &mut added, &mut old_column, &mut new_column);
+                old_column.push(Line::raw(text.clone()));
+                new_column.push(Line::raw(text.clone()));
+            }
+            DiffLine::Removed(text) => removed.push(Line::from(Span::styled(
+                text.clone(),
+                Style::default().fg(Color::Red),
+            ))),
+            DiffLine::Added(text) => added.push(Line::from(Span::styled(
+                text.clone(),
+                Style::default().fg(Color::Green),
+            ))),
+        }
+    }
+    flush(&mut removed, &mut added, &mut old_column, &mut new_column);
+    (old_column, new_column)
 }
 
 fn render_history_detail(frame: &mut Frame, app: &App, area: Rect) {
@@ -448,53 +1105,42 @@ fn render_history_detail(frame: &mut Frame, app: &App, area: Rect) {
         .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
         .split(area);
 
-    // Request section
-    let mut request_lines: Vec<Line> = Vec::new();
-
-    let method_color = match entry.request.method {
-        crate::http::Method::Get => Color::Green,
-        crate::http::Method::Post => Color::Yellow,
-        crate::http::Method::Put => Color::Blue,
-        crate::http::Method::Patch => Color::Cyan,
-        crate::http::Method::Delete => Color::Red,
-        _ => Color::White,
-    };
-
-    request_lines.push(Line::from(vec![
-        Span::styled(
-            format!("{}", entry.request.method),
-            Style::default()
-                .fg(method_color)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::raw(" "),
-        Span::raw(&entry.request.url),
-    ]));
-
-    for (key, value) in &entry.request.headers {
-        request_lines.push(Line::from(format!("{}: {}", key, value)));
-    }
+    // Request section: as written on the left, as sent on the right
+    let request_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[0]);
+
+    let mut template_headers: Vec<_> = entry.request.headers.iter().collect();
+    template_headers.sort();
+    let template_lines = request_lines(
+        &entry.request.method,
+        &entry.request.url,
+        template_headers.into_iter(),
+        entry.request.body.as_deref(),
+    );
+    let sent_lines = request_lines(
+        &entry.sent.method,
+        &entry.sent.url,
+        entry.sent.headers.iter().map(|(key, value)| (key, value)),
+        entry.sent.body.as_deref(),
+    );
 
-    if let Some(ref body) = entry.request.body {
-        if !entry.request.headers.is_empty() {
-            request_lines.push(Line::from(""));
-        }
-        for line in body.lines() {
-            request_lines.push(Line::from(line.to_string()));
-        }
+    for (lines, title, area) in [
+        (template_lines, " Request ", request_chunks[0]),
+        (sent_lines, " Sent ", request_chunks[1]),
+    ] {
+        let request_block = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::DarkGray)),
+            )
+            .wrap(Wrap { trim: false });
+        frame.render_widget(request_block, area);
     }
 
-    let request_block = Paragraph::new(request_lines)
-        .block(
-            Block::default()
-                .title(" Request ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::DarkGray)),
-        )
-        .wrap(Wrap { trim: false });
-
-    frame.render_widget(request_block, chunks[0]);
-
     // Response section
     let border_style = if app.focus == Focus::HistoryDetail {
         Style::default().fg(Color::Cyan)
@@ -524,13 +1170,38 @@ fn render_history_detail(frame: &mut Frame, app: &App, area: Rect) {
             format!("{:.2?}", entry.response.duration),
             Style::default().fg(Color::DarkGray),
         ),
+        Span::raw("  "),
+        Span::styled(
+            body_size_label(&entry.response),
+            Style::default().fg(Color::DarkGray),
+        ),
     ]));
 
+    response_lines.push(timing_waterfall(&entry.response.timings));
     response_lines.push(Line::from(""));
 
-    let formatted_body = format_body(&entry.response.body);
-    for line in formatted_body.lines() {
-        response_lines.push(Line::from(line.to_string()));
+    match entry.response.text {
+        Some(_) => {
+            let formatted_body = app
+                .history_formatted_body
+                .as_ref()
+                .map(|(_, body)| body.as_str())
+                .unwrap_or_default();
+            for line in formatted_body.lines() {
+                response_lines.push(Line::from(line.to_string()));
+            }
+        }
+        None => {
+            let rows = HISTORY_HEX_PREVIEW_BYTES.div_ceil(HEX_BYTES_PER_LINE);
+            response_lines.extend(hex_dump_lines(&entry.response.body, 0, rows));
+            let preview_bytes = HISTORY_HEX_PREVIEW_BYTES as u64;
+            if entry.response.size() > preview_bytes {
+                response_lines.push(Line::from(Span::styled(
+                    format!("… {} more bytes", entry.response.size() - preview_bytes),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+        }
     }
 
     let response_block = Paragraph::new(response_lines)
@@ -546,6 +1217,337 @@ fn render_history_detail(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(response_block, chunks[1]);
 }
 
+fn request_lines<'a>(
+    method: &crate::http::Method,
+    url: &'a str,
+    headers: impl Iterator<Item = (&'a String, &'a String)>,
+    body: Option<&'a str>,
+) -> Vec<Line<'a>> {
+    let method_color = match method {
+        crate::http::Method::Get => Color::Green,
+        crate::http::Method::Post => Color::Yellow,
+        crate::http::Method::Put => Color::Blue,
+        crate::http::Method::Patch => Color::Cyan,
+        crate::http::Method::Delete => Color::Red,
+        crate::http::Method::WebSocket => Color::Magenta,
+        crate::http::Method::Grpc => Color::LightBlue,
+        _ => Color::White,
+    };
+
+    let mut lines = vec![Line::from(vec![
+        Span::styled(
+            format!("{}", method),
+            Style::default()
+                .fg(method_color)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(" "),
+        Span::raw(url),
+    ])];
+
+    let mut has_headers = false;
+    for (key, value) in headers {
+        lines.push(Line::from(format!("{}: {}", key, value)));
+        has_headers = true;
+    }
+
+    if let Some(body) = body {
+        if has_headers {
+            lines.push(Line::from(""));
+        }
+        for line in body.lines() {
+            lines.push(Line::from(line));
+        }
+    }
+    lines
+}
+
+fn render_cookies_view(frame: &mut Frame, app: &App) {
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(frame.area());
+
+    let help = Paragraph::new(
+        " Cookies | ESC/C: back | j/k: navigate | d: delete cookie | D: delete domain | r: refresh",
+    )
+    .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(help, main_chunks[0]);
+
+    let domain_width = app
+        .cookies
+        .iter()
+        .map(|cookie| cookie.domain.len())
+        .max()
+        .unwrap_or(0);
+
+    let items: Vec<ListItem> = app
+        .cookies
+        .iter()
+        .enumerate()
+        .map(|(i, cookie)| {
+            let first_in_domain = i == 0 || app.cookies[i - 1].domain != cookie.domain;
+            let domain = if first_in_domain { &cookie.domain } else { "" };
+
+            let expires = match cookie.expires {
+                Some(expires) => {
+                    let expires: DateTime<Local> = expires.into();
+                    expires.format("%Y-%m-%d %H:%M").to_string()
+                }
+                None => "session".to_string(),
+            };
+
+            let mut flags = Vec::new();
+            if cookie.secure {
+                flags.push("Secure");
+            }
+            if cookie.http_only {
+                flags.push("HttpOnly");
+            }
+
+            let content = Line::from(vec![
+                Span::styled(
+                    format!("{:width$}", domain, width = domain_width),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::styled(
+                    cookie.name.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("="),
+                Span::raw(cookie.value.clone()),
+                Span::raw("  "),
+                Span::styled(
+                    format!("{}  {}  {}", cookie.path, expires, flags.join(" ")),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]);
+
+            ListItem::new(content)
+        })
+        .collect();
+
+    let title = if items.is_empty() {
+        " Cookies (none) ".to_string()
+    } else {
+        format!(" Cookies ({}) ", items.len())
+    };
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let mut list_state = ListState::default().with_selected(Some(app.selected_cookie));
+    frame.render_stateful_widget(list, main_chunks[1], &mut list_state);
+}
+
+const WATERFALL_WIDTH: usize = 16;
+
+fn timing_waterfall(timings: &Timings) -> Line<'static> {
+    let phases = timings.phases();
+    let total: f64 = phases.iter().map(|(_, d)| d.as_secs_f64()).sum();
+    if total == 0.0 {
+        return Line::default();
+    }
+
+    let color = |label: &str| match label {
+        "DNS" => Color::Magenta,
+        "TLS" => Color::Cyan,
+        "TTFB" => Color::Yellow,
+        "Download" => Color::Green,
+        _ => Color::Blue,
+    };
+
+    let mut spans = Vec::new();
+    for (label, duration) in &phases {
+        let share = duration.as_secs_f64() / total;
+        let mut width = (share * WATERFALL_WIDTH as f64).round() as usize;
+        if width == 0 && !duration.is_zero() {
+            width = 1;
+        }
+        spans.push(Span::styled(
+            "█".repeat(width),
+            Style::default().fg(color(label)),
+        ));
+    }
+
+    for (label, duration) in phases {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!("{} {:.1?}", label, duration),
+            Style::default().fg(color(label)),
+        ));
+    }
+
+    Line::from(spans)
+}
+
+const HEX_BYTES_PER_LINE: usize = 16;
+const HISTORY_HEX_PREVIEW_BYTES: usize = 4096;
+
+fn hex_dump_lines(bytes: &[u8], offset: usize, rows: usize) -> Vec<Line<'static>> {
+    let start = offset.min(bytes.len()) / HEX_BYTES_PER_LINE * HEX_BYTES_PER_LINE;
+
+    bytes[start..]
+        .chunks(HEX_BYTES_PER_LINE)
+        .take(rows)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut hex = String::with_capacity(HEX_BYTES_PER_LINE * 3 + 1);
+            for column in 0..HEX_BYTES_PER_LINE {
+                if column == HEX_BYTES_PER_LINE / 2 {
+                    hex.push(' ');
+                }
+                match chunk.get(column) {
+                    Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+                    None => hex.push_str("   "),
+                }
+            }
+
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+
+            Line::from(vec![
+                Span::styled(
+                    format!("{:08x}  ", start + i * HEX_BYTES_PER_LINE),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(hex),
+                Span::styled(format!(" |{}|", ascii), Style::default().fg(Color::Cyan)),
+            ])
+        })
+        .collect()
+}
+
+fn body_size_label(response: &crate::client::Response) -> String {
+    let size = format_size(response.size());
+    if response.is_binary() {
+        format!("{} binary", size)
+    } else {
+        size
+    }
+}
+
+/// Describes when an OAuth2 token expires, colored by how soon that is.
+fn format_token_expiry(expires_at: Option<DateTime<Utc>>) -> (String, Color) {
+    let Some(expires_at) = expires_at else {
+        return ("no expiry".to_string(), Color::Green);
+    };
+
+    let now = Utc::now();
+    let remaining = (expires_at - now).num_seconds();
+    if remaining <= 0 {
+        return ("expired".to_string(), Color::Red);
+    }
+
+    let text = format!("expires {}", jwt::format_relative(expires_at, now));
+    let color = if remaining < 60 {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+    (text, color)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Formats a response body for display, listing events for `text/event-stream` responses.
+pub(super) fn format_response_body(response: &Response) -> String {
+    if response.is_event_stream() {
+        format_sse_events(&response.events)
+    } else if response.is_websocket() {
+        format_websocket_messages(&response.messages)
+    } else {
+        response
+            .text
+            .as_deref()
+            .map(format_body)
+            .unwrap_or_default()
+    }
+}
+
+fn format_sse_events(events: &[SseEvent]) -> String {
+    let mut out = String::new();
+    for event in events {
+        out.push_str(&format!(
+            "[+{:.2}s] event: {}",
+            event.elapsed.as_secs_f64(),
+            event.event
+        ));
+        if let Some(ref id) = event.id {
+            out.push_str(&format!("  id: {}", id));
+        }
+        out.push('\n');
+        for line in format_body(&event.data).lines() {
+            out.push_str("  ");
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn format_websocket_messages(messages: &[WebSocketMessage]) -> String {
+    let mut out = String::new();
+    for message in messages {
+        out.push_str(&format!(
+            "[+{:.2}s] {}",
+            message.elapsed.as_secs_f64(),
+            message.direction.arrow()
+        ));
+        let body = if message.binary {
+            message.data.clone()
+        } else {
+            format_body(&message.data)
+        };
+        let mut lines = body.lines();
+        if let Some(first) = lines.next() {
+            out.push(' ');
+            out.push_str(first);
+        }
+        out.push('\n');
+        for line in lines {
+            out.push_str("  ");
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
 fn format_body(body: &str) -> String {
     if let Ok(json) = serde_json::from_str::<serde_json::Value>(body) {
         serde_json::to_string_pretty(&json).unwrap_or_else(|_| body.to_string())
@@ -0,0 +1,8 @@
+const response = await fetch("https://api.example.com/items/1", {
+  headers: {
+    "Accept": "application/json",
+  },
+});
+
+console.log(response.status);
+console.log(await response.text());
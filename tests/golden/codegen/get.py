@@ -0,0 +1,11 @@
+import requests
+
+url = "https://api.example.com/items/1"
+headers = {
+    "Accept": "application/json",
+}
+
+response = requests.request("GET", url, headers=headers)
+
+print(response.status_code)
+print(response.text)
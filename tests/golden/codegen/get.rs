@@ -0,0 +1,13 @@
+#[tokio::main]
+async fn main() -> Result<(), reqwest::Error> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get("https://api.example.com/items/1")
+        .header("Accept", "application/json")
+        .send()
+        .await?;
+
+    println!("{}", response.status());
+    println!("{}", response.text().await?);
+    Ok(())
+}
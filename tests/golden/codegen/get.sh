@@ -0,0 +1,3 @@
+http \
+  GET https://api.example.com/items/1 \
+  Accept:application/json
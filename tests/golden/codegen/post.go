@@ -0,0 +1,32 @@
+package main
+
+import (
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+)
+
+func main() {
+	body := strings.NewReader("{\n  \"name\": \"Widget \\\"Deluxe\\\"\",\n  \"price\": \"9€\"\n}")
+	req, err := http.NewRequest("POST", "https://api.example.com/items?draft=true", body)
+	if err != nil {
+		panic(err)
+	}
+	req.Header.Set("Authorization", "Basic YWRtaW46czNjcmV0")
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set("X-Request-Id", "42")
+
+	resp, err := http.DefaultClient.Do(req)
+	if err != nil {
+		panic(err)
+	}
+	defer resp.Body.Close()
+
+	respBody, err := io.ReadAll(resp.Body)
+	if err != nil {
+		panic(err)
+	}
+	fmt.Println(resp.Status)
+	fmt.Println(string(respBody))
+}
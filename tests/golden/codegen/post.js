@@ -0,0 +1,12 @@
+const response = await fetch("https://api.example.com/items?draft=true", {
+  method: "POST",
+  headers: {
+    "Authorization": "Basic YWRtaW46czNjcmV0",
+    "Content-Type": "application/json",
+    "X-Request-Id": "42",
+  },
+  body: "{\n  \"name\": \"Widget \\\"Deluxe\\\"\",\n  \"price\": \"9€\"\n}",
+});
+
+console.log(response.status);
+console.log(await response.text());
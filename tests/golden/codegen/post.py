@@ -0,0 +1,14 @@
+import requests
+
+url = "https://api.example.com/items?draft=true"
+headers = {
+    "Authorization": "Basic YWRtaW46czNjcmV0",
+    "Content-Type": "application/json",
+    "X-Request-Id": "42",
+}
+data = "{\n  \"name\": \"Widget \\\"Deluxe\\\"\",\n  \"price\": \"9€\"\n}"
+
+response = requests.request("POST", url, headers=headers, data=data.encode("utf-8"))
+
+print(response.status_code)
+print(response.text)
@@ -0,0 +1,19 @@
+#[tokio::main]
+async fn main() -> Result<(), reqwest::Error> {
+    let client = reqwest::Client::new();
+    let response = client
+        .post("https://api.example.com/items?draft=true")
+        .header("Authorization", "Basic YWRtaW46czNjcmV0")
+        .header("Content-Type", "application/json")
+        .header("X-Request-Id", "42")
+        .body(r#"{
+  "name": "Widget \"Deluxe\"",
+  "price": "9€"
+}"#)
+        .send()
+        .await?;
+
+    println!("{}", response.status());
+    println!("{}", response.text().await?);
+    Ok(())
+}
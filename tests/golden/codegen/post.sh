@@ -0,0 +1,9 @@
+http \
+  --raw '{
+  "name": "Widget \"Deluxe\"",
+  "price": "9€"
+}' \
+  POST 'https://api.example.com/items?draft=true' \
+  'Authorization:Basic YWRtaW46czNjcmV0' \
+  Content-Type:application/json \
+  X-Request-Id:42